@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::path::PathBuf;
+use tasks::{Status, TaskManager};
+
+const TASKS: usize = 50_000;
+
+// Half of them done, each with a change in its history like a file that has been used for a while
+fn large_task_manager() -> TaskManager {
+    let mut task_manager = TaskManager::new();
+    for index in 0..TASKS {
+        task_manager.add_task(format!("task {}", index));
+        task_manager.set_task_description(index, "some history".to_string());
+        if index % 2 == 0 {
+            task_manager.set_task_status(index, Status::Done);
+        }
+    }
+    task_manager
+}
+
+fn data_file() -> PathBuf {
+    let data_dir = std::env::temp_dir().join(format!("tasks-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&data_dir).unwrap();
+    data_dir.join("task.json")
+}
+
+fn large_files(c: &mut Criterion) {
+    let data_file = data_file();
+    let mut task_manager = large_task_manager();
+    task_manager.save_to_file(&data_file).unwrap();
+
+    let mut group = c.benchmark_group("50k tasks");
+    group.sample_size(10);
+    group.bench_function("save", |b| {
+        b.iter(|| task_manager.save_to_file(&data_file).unwrap())
+    });
+    group.bench_function("load", |b| {
+        b.iter(|| TaskManager::load_from_file(&data_file).unwrap())
+    });
+    group.bench_function("urgencies", |b| {
+        b.iter_batched(
+            || TaskManager::load_from_file(&data_file).unwrap(),
+            |mut loaded| {
+                loaded.calculate_urgencies();
+                loaded.sort_by_urgencies();
+                loaded
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+
+    std::fs::remove_dir_all(data_file.parent().unwrap()).unwrap();
+}
+
+criterion_group!(benches, large_files);
+criterion_main!(benches);
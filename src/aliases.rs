@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+// Options before the subcommand that are followed by a value, see Selection
+const SELECTION_OPTIONS: [&str; 3] = ["--filter", "--tag", "--due-before"];
+
+// Replaces the subcommand with its alias from config, e.g. "ls": "list --sort due". Later
+// arguments are kept after the expansion, and an alias is only expanded once so they can't loop
+pub fn expand_aliases(args: Vec<String>, aliases: &BTreeMap<String, String>) -> Vec<String> {
+    // The program name, global flags and the task selection come before the subcommand
+    let mut position = 1;
+    while position < args.len() && args[position].starts_with('-') {
+        let takes_value = SELECTION_OPTIONS.contains(&args[position].as_str());
+        position += if takes_value { 2 } else { 1 };
+    }
+    if position >= args.len() {
+        return args;
+    }
+    match aliases.get(&args[position]) {
+        Some(expansion) => {
+            let mut expanded = args[..position].to_vec();
+            expanded.extend(expansion.split_whitespace().map(str::to_string));
+            expanded.extend(args[position + 1..].iter().cloned());
+            expanded
+        }
+        None => args,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand_aliases;
+    use std::collections::BTreeMap;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn expands_the_subcommand_only() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert("ls".to_string(), "list --sort due".to_string());
+        aliases.insert("d".to_string(), "done".to_string());
+        assert_eq!(
+            expand_aliases(args("tasks -q ls -l 3"), &aliases),
+            args("tasks -q list --sort due -l 3")
+        );
+        assert_eq!(
+            expand_aliases(args("tasks add d"), &aliases),
+            args("tasks add d")
+        );
+        assert_eq!(expand_aliases(args("tasks -v"), &aliases), args("tasks -v"));
+        assert_eq!(
+            expand_aliases(args("tasks --filter ls d"), &aliases),
+            args("tasks --filter ls done")
+        );
+    }
+}
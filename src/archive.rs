@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dates::{from_local, parse_day};
+use crate::error::TaskError;
+use crate::events::{Event, FieldValue};
+use crate::history::history_datetime;
+use crate::i18n::{self, tr};
+use crate::{Status, Task, TaskManager};
+
+// Done tasks moved out of the task list, kept for searching and restoring
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedTask {
+    pub task: Task,
+    #[serde(deserialize_with = "crate::dates::serde_utc::deserialize")]
+    pub archived_at: DateTime<Utc>,
+}
+
+// The end of a day given like a due date, so the task stays for all of that day
+pub fn parse_until(input: &str, input_format: &str) -> Result<DateTime<Utc>, TaskError> {
+    parse_day(input, input_format)
+        .and_then(|day| day.and_hms_opt(23, 59, 59))
+        .map(from_local)
+        .ok_or(TaskError::InvalidDate {
+            input: input.to_string(),
+            expected: input_format.to_string(),
+        })
+}
+
+impl Task {
+    // When the task was last marked done, older tasks without history use modified_at
+    pub fn completed_at(&self) -> Option<DateTime<Utc>> {
+        self.history
+            .iter()
+            .rev()
+            .find(|change| change.field == "status" && change.new_value == Status::Done.as_str())
+            .map(|change| change.time)
+            .or(self.modified_at)
+    }
+}
+
+// Days the task stays done before it is archived, from the longest archive rule matching its
+// tags or project, else archive_done_after_days. None keeps it
+fn archive_after_days(task: &Task, config: &Config) -> Option<i64> {
+    let matching: Vec<i64> = config
+        .archive_rules
+        .iter()
+        .filter(|(rule, _)| match rule.strip_prefix('+') {
+            Some(tag) => task.tags.iter().any(|other| other == tag),
+            None => rule
+                .strip_prefix("project:")
+                .is_some_and(|project| task.project.as_deref() == Some(project)),
+        })
+        .map(|(_, days)| *days)
+        .collect();
+    let days = if matching.iter().any(|days| *days <= 0) {
+        0
+    } else {
+        matching
+            .into_iter()
+            .max()
+            .unwrap_or(config.archive_done_after_days)
+    };
+    Some(days).filter(|days| *days > 0)
+}
+
+impl TaskManager {
+    // Moves tasks done for longer than their archive rule or archive_done_after_days, reporting
+    // what was moved
+    pub fn archive_done_tasks(&mut self, config: &Config) {
+        if config.archive_done_after_days <= 0 && config.archive_rules.is_empty() {
+            return;
+        }
+        let now = Utc::now();
+        self.archive_where(
+            |task| {
+                task.status == Status::Done
+                    && archive_after_days(task, config).is_some_and(|days| {
+                        task.completed_at()
+                            .is_some_and(|completed| completed < now - Duration::days(days))
+                    })
+            },
+            "",
+        );
+    }
+
+    // Moves every task matching expired in one pass, removing them one at a time is quadratic
+    // with tens of thousands of tasks. The report goes to stderr, out of the way of the output of
+    // the command that triggered it
+    fn archive_where(&mut self, expired: impl Fn(&Task) -> bool, reason: &str) {
+        let now = Utc::now();
+        for task in self.remove_tasks_where(expired) {
+            eprintln!(
+                "{}",
+                tr!("archived_tasks", "Archived {0}{1}", task.title, reason)
+            );
+            self.archive.push(ArchivedTask {
+                task,
+                archived_at: now,
+            });
+        }
+    }
+
+    pub fn set_until(&mut self, id: usize, until: Option<DateTime<Utc>>) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "until",
+                history_datetime(task.until),
+                history_datetime(until),
+            );
+            self.set_field(id, FieldValue::Until(until));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // Open tasks past their until date are archived as they were, they can still be restored
+    pub fn archive_expired_tasks(&mut self) {
+        let now = Utc::now();
+        self.archive_where(
+            |task| task.status != Status::Done && task.until.is_some_and(|until| until < now),
+            ", its until date has passed",
+        );
+    }
+
+    pub fn restore_archived(&mut self, archive_id: usize) {
+        if archive_id < self.archive.len() {
+            let mut task = self.archive.remove(archive_id).task;
+            // Otherwise an expired task would be archived again on the next run
+            if task.until.is_some_and(|until| until < Utc::now()) {
+                task.record_change(
+                    "until",
+                    history_datetime(task.until),
+                    history_datetime(None),
+                );
+                task.until = None;
+            }
+            self.apply(Event::TaskAdded { task });
+        } else {
+            eprintln!("{}", TaskError::InvalidId(archive_id));
+        }
+    }
+
+    pub fn list_archive(&self, config: &Config) {
+        if self.archive.is_empty() {
+            println!("{}", i18n::text("archive_empty", "The archive is empty"));
+        }
+        for (index, archived) in self.archive.iter().enumerate() {
+            println!(
+                "{}",
+                tr!(
+                    "archive_row",
+                    " -{0}- {1} --- archived {2}",
+                    index,
+                    archived.task.title,
+                    config.format_datetime(archived.archived_at)
+                )
+            );
+        }
+    }
+}
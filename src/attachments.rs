@@ -0,0 +1,87 @@
+use std::fs::{canonicalize, copy, create_dir_all};
+use std::path::{Path, PathBuf};
+
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::i18n::tr;
+use crate::links::open_command;
+use crate::TaskManager;
+
+impl TaskManager {
+    // Attaches a file by its absolute path, with copy it is first copied into
+    // attachments/<uid> next to the data file so it survives the original moving
+    pub fn attach_file(&mut self, id: usize, path: &Path, copy_to: Option<&Path>) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let attach = || -> std::io::Result<PathBuf> {
+            let path = canonicalize(path)?;
+            let data_dir = match copy_to {
+                Some(data_dir) => data_dir,
+                None => return Ok(path),
+            };
+            let mut directory = data_dir.join("attachments");
+            directory.push(self.tasks[id].uid.to_string());
+            create_dir_all(&directory)?;
+            let copied = directory.join(path.file_name().unwrap_or_default());
+            copy(&path, &copied)?;
+            Ok(copied)
+        };
+        match attach() {
+            Ok(attached) => {
+                let task = &mut self.tasks[id];
+                if !task.attachments.contains(&attached) {
+                    task.record_change(
+                        "attachment",
+                        "none".to_string(),
+                        attached.display().to_string(),
+                    );
+                    let mut attachments = task.attachments.clone();
+                    attachments.push(attached);
+                    self.set_field(id, FieldValue::Attachments(attachments));
+                }
+            }
+            Err(err) => eprintln!(
+                "{}",
+                tr!(
+                    "attachment_failed",
+                    "Unable to attach {0}: {1}",
+                    path.display(),
+                    err
+                )
+            ),
+        }
+    }
+
+    pub fn open_attachment(&self, id: usize, index: usize) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let attachments = &self.tasks[id].attachments;
+        match attachments.get(index) {
+            Some(path) => {
+                let path = path.display().to_string();
+                if let Err(err) = open_command(&path).status() {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            "attachment_open_failed",
+                            "Unable to open {0}: {1}",
+                            path,
+                            err
+                        )
+                    );
+                }
+            }
+            None => eprintln!(
+                "{}",
+                TaskError::InvalidAttachment {
+                    index,
+                    count: attachments.len(),
+                }
+            ),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use term_size::dimensions;
+
+use crate::config::Config;
+use crate::filter::Filter;
+use crate::i18n::tr;
+use crate::reports::GroupBy;
+use crate::text::{pad, truncate};
+use crate::theme::Element;
+use crate::{Status, TaskManager, DEFAULT_TERMINAL_WIDTH};
+
+const LANE_SEPARATOR: &str = " │ ";
+const SEPARATOR_WIDTH: usize = 3;
+
+// Lanes narrower than this are unreadable, those that don't fit are left out
+const MIN_LANE_WIDTH: usize = 12;
+
+impl TaskManager {
+    // (heading, ids) of each lane, by status unless grouped by something else like list --group-by
+    fn lanes(
+        &self,
+        filter: &Filter,
+        group_by: Option<&GroupBy>,
+        config: &Config,
+    ) -> Vec<(String, Vec<usize>)> {
+        let ids = self.filtered_ids(filter);
+        match group_by {
+            Some(group_by) => self.group_ids(&ids, group_by, config),
+            None => [Status::Inactive, Status::Active, Status::Done]
+                .iter()
+                .map(|status| {
+                    let lane = ids
+                        .iter()
+                        .copied()
+                        .filter(|&id| self.tasks[id].status == *status)
+                        .collect();
+                    (status.as_str().to_string(), lane)
+                })
+                .collect(),
+        }
+    }
+
+    // Lanes side by side, each in the order of the list so the most urgent tasks are at the top
+    pub fn board(
+        &self,
+        filter: &Filter,
+        group_by: Option<&GroupBy>,
+        width: Option<usize>,
+        config: &Config,
+    ) -> String {
+        let lanes = self.lanes(filter, group_by, config);
+        if config.plain {
+            return lanes
+                .iter()
+                .map(|(heading, ids)| {
+                    tr!(
+                        "board_column",
+                        "{0}: {1} tasks.\n{2}",
+                        heading,
+                        ids.len(),
+                        self.list_plain(ids, config)
+                    )
+                })
+                .collect();
+        }
+        let term_width = width
+            .or_else(|| dimensions().map(|(w, _)| w))
+            .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+        let fitting = (term_width + SEPARATOR_WIDTH) / (MIN_LANE_WIDTH + SEPARATOR_WIDTH);
+        let lanes = &lanes[..lanes.len().min(fitting.max(1))];
+        let separators = lanes.len().saturating_sub(1) * SEPARATOR_WIDTH;
+        let lane_width = term_width.saturating_sub(separators) / lanes.len().max(1);
+
+        let theme = config.theme();
+        let headings: Vec<String> = lanes
+            .iter()
+            .map(|(heading, ids)| {
+                let heading = format!("{} ({})", heading.to_uppercase(), ids.len());
+                theme.paint(
+                    Element::Header,
+                    &pad(truncate(&heading, lane_width), lane_width),
+                )
+            })
+            .collect();
+        let mut lines = vec![headings.join(LANE_SEPARATOR)];
+        let height = lanes.iter().map(|(_, ids)| ids.len()).max().unwrap_or(0);
+        for row in 0..height {
+            let cells: Vec<String> = lanes
+                .iter()
+                .map(|(_, ids)| {
+                    let cell = match ids.get(row) {
+                        Some(&id) => format!("{:>2} {}", id, self.tasks[id].title),
+                        None => String::new(),
+                    };
+                    pad(truncate(&cell, lane_width), lane_width)
+                })
+                .collect();
+            lines.push(cells.join(LANE_SEPARATOR).trim_end().to_string());
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::filter::Filter;
+    use crate::{Status, TaskManager};
+
+    #[test]
+    fn board_shows_a_lane_per_status() {
+        let mut task_manager = TaskManager::new();
+        for title in ["Write report", "Fix login", "Book flights"] {
+            task_manager.add_task(title.to_string());
+        }
+        task_manager.set_task_status(1, Status::Active);
+        task_manager.set_task_status(2, Status::Done);
+        let config = Config {
+            plain: true,
+            ..Config::default()
+        };
+        let filter = Filter::parse("", &config).unwrap();
+        let plain = task_manager.board(&filter, None, Some(60), &config);
+        assert!(plain.starts_with("Inactive: 1 tasks.\nID: 0. Title: Write report."));
+
+        let config = Config::default();
+        let board = task_manager.board(&filter, None, Some(60), &config);
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("INACTIVE (1)"));
+        assert_eq!(
+            lines[1],
+            " 0 Write report    │  1 Fix login       │  2 Book flights"
+        );
+    }
+}
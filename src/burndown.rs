@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use crate::config::Config;
+use crate::dates::from_local;
+use crate::i18n::tr;
+use crate::{Status, Task, TaskManager};
+
+// Rows of the chart above the axis
+const CHART_HEIGHT: usize = 10;
+
+// Width of the value labels left of the axis
+const LABEL_WIDTH: usize = 6;
+
+// Charts go back two years at most, a day per column is unreadable well before that
+pub const MAX_WEEKS: u32 = 104;
+
+impl Task {
+    // Whether the task had been added and wasn't done yet at a point in time, tasks done and then
+    // reopened count as open all along
+    fn open_at(&self, at: DateTime<Utc>) -> bool {
+        let added = self
+            .created_at
+            .or(self.start_time)
+            .is_some_and(|created| created <= at);
+        let done = self.status == Status::Done
+            && self.completed_at().is_some_and(|completed| completed <= at);
+        added && !done
+    }
+}
+
+fn format_value(value: f64, estimate: bool) -> String {
+    if estimate {
+        format!("{:.0}h", value)
+    } else {
+        format!("{:.0}", value)
+    }
+}
+
+// A column per day, as tall as the value is of the highest one
+fn chart(days: &[NaiveDate], values: &[f64], estimate: bool, config: &Config) -> Vec<String> {
+    let max = values.iter().cloned().fold(1.0, f64::max);
+    let mut lines = Vec::new();
+    for row in (1..=CHART_HEIGHT).rev() {
+        let threshold = max * (row as f64 - 0.5) / CHART_HEIGHT as f64;
+        let label = match row {
+            CHART_HEIGHT => format_value(max, estimate),
+            row if row == CHART_HEIGHT / 2 => format_value(max / 2.0, estimate),
+            _ => String::new(),
+        };
+        let cells: String = values
+            .iter()
+            .map(|&value| if value >= threshold { '█' } else { ' ' })
+            .collect();
+        lines.push(format!("{:>LABEL_WIDTH$} │{}", label, cells.trim_end()));
+    }
+    lines.push(format!(
+        "{:>LABEL_WIDTH$} └{}",
+        format_value(0.0, estimate),
+        "─".repeat(values.len())
+    ));
+    if let (Some(first), Some(last)) = (days.first(), days.last()) {
+        let (first, last) = (config.format_date(*first), config.format_date(*last));
+        let gap = values
+            .len()
+            .saturating_sub(first.chars().count() + last.chars().count());
+        lines.push(format!(
+            "{} {}{}{}",
+            " ".repeat(LABEL_WIDTH),
+            first,
+            " ".repeat(gap.max(1)),
+            last
+        ));
+    }
+    lines
+}
+
+impl TaskManager {
+    // Open tasks at the end of each day, or the hours they are estimated to take. Archived tasks
+    // count until they were done and removed ones until they went to the trash
+    fn burndown_values(&self, days: &[NaiveDate], estimate: bool) -> Vec<f64> {
+        let ends: Vec<DateTime<Utc>> = days
+            .iter()
+            .map(|day| from_local(day.and_hms_opt(23, 59, 59).unwrap()))
+            .collect();
+        let archived = self.archive.iter().map(|archived| (&archived.task, None));
+        let trashed = self
+            .trash
+            .iter()
+            .map(|trashed| (&trashed.task, Some(trashed.deleted_at)));
+        let tasks: Vec<(&Task, Option<DateTime<Utc>>)> = self
+            .tasks
+            .iter()
+            .map(|task| (task, None))
+            .chain(archived)
+            .chain(trashed)
+            .collect();
+        ends.iter()
+            .map(|&end| {
+                tasks
+                    .iter()
+                    .filter(|(task, deleted_at)| {
+                        task.open_at(end) && deleted_at.is_none_or(|deleted_at| deleted_at > end)
+                    })
+                    .map(|(task, _)| match (estimate, task.estimate) {
+                        (true, Some(estimate)) => estimate.num_minutes() as f64 / 60.0,
+                        (true, None) => 0.0,
+                        (false, _) => 1.0,
+                    })
+                    .sum::<f64>()
+                    // An empty sum is -0, which would be shown as such
+                    + 0.0
+            })
+            .collect()
+    }
+
+    // How the open tasks went over the last weeks, ending today
+    pub fn burndown(
+        &self,
+        weeks: u32,
+        estimate: bool,
+        today: NaiveDate,
+        config: &Config,
+    ) -> String {
+        let first = today - Duration::weeks(weeks.clamp(1, MAX_WEEKS) as i64);
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|day| *day <= today).collect();
+        let values = self.burndown_values(&days, estimate);
+        let (start, now) = (values[0], values[values.len() - 1]);
+        let what = if estimate {
+            "Open estimate"
+        } else {
+            "Open tasks"
+        };
+        let trend = if now < start {
+            tr!(
+                "burndown_down",
+                "down {0}",
+                format_value(start - now, estimate)
+            )
+        } else if now > start {
+            format!("up {}", format_value(now - start, estimate))
+        } else {
+            "no change".to_string()
+        };
+        let summary = tr!(
+            "burndown_summary",
+            "{0}: {1} on {2}, {3} now, {4}\n",
+            what,
+            format_value(start, estimate),
+            config.format_date(first),
+            format_value(now, estimate),
+            trend
+        );
+        // A line a week instead of a chart
+        if config.plain {
+            let weekly: String = days
+                .iter()
+                .zip(values.iter())
+                .step_by(7)
+                .map(|(day, value)| {
+                    format!(
+                        "{}: {} {}.\n",
+                        config.format_date(*day),
+                        format_value(*value, estimate),
+                        what.to_lowercase()
+                    )
+                })
+                .collect();
+            return weekly + summary.as_str();
+        }
+        chart(&days, &values, estimate, config).join("\n") + "\n" + summary.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_WEEKS;
+    use crate::config::Config;
+    use crate::dates::from_local;
+    use crate::{Status, TaskManager};
+    use chrono::{Duration, Local, NaiveDate};
+
+    #[test]
+    fn burndown_counts_tasks_open_each_day() {
+        let today = Local::now().date_naive();
+        let days_ago = |days: i64| {
+            from_local(
+                (today - Duration::days(days))
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+            )
+        };
+        let mut task_manager = TaskManager::new();
+        for title in ["Old bug", "Docs", "New feature"] {
+            task_manager.add_task(title.to_string());
+        }
+        task_manager.tasks[0].created_at = Some(days_ago(20));
+        task_manager.tasks[1].created_at = Some(days_ago(10));
+        task_manager.tasks[2].created_at = Some(days_ago(3));
+        task_manager.set_task_status(0, Status::Done);
+        task_manager.tasks[0].history.last_mut().unwrap().time = days_ago(5);
+        task_manager.trash_task(1);
+        task_manager.trash[0].deleted_at = days_ago(1);
+
+        let days: Vec<NaiveDate> = [21, 10, 5, 3, 0]
+            .iter()
+            .map(|&days| today - Duration::days(days))
+            .collect();
+        assert_eq!(
+            task_manager.burndown_values(&days, false),
+            vec![0.0, 2.0, 1.0, 2.0, 1.0]
+        );
+
+        let config = Config::default();
+        let chart = task_manager.burndown(4, false, today, &config);
+        assert_eq!(chart.lines().count(), 13);
+        assert!(chart.lines().last().unwrap().ends_with("1 now, up 1"));
+        assert_eq!(
+            task_manager.burndown(100_000, false, today, &config),
+            task_manager.burndown(MAX_WEEKS, false, today, &config)
+        );
+    }
+}
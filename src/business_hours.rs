@@ -0,0 +1,105 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc, Weekday};
+use std::collections::BTreeSet;
+
+use crate::dates::{from_local, to_local};
+
+// Days and hours counted as working time when urgency_business_hours is set
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkWeek {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub holidays: BTreeSet<NaiveDate>,
+}
+
+impl WorkWeek {
+    pub fn is_work_day(&self, day: NaiveDate) -> bool {
+        self.days.contains(&day.weekday()) && !self.holidays.contains(&day)
+    }
+
+    // Seconds of working time between two times, 0 when to is before from
+    pub fn working_seconds(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+        if to <= from {
+            return 0;
+        }
+        let mut seconds = 0;
+        let mut day = to_local(from).date();
+        let last_day = to_local(to).date();
+        while day <= last_day {
+            if self.is_work_day(day) {
+                let start = from_local(day.and_time(self.start)).max(from);
+                let end = from_local(day.and_time(self.end)).min(to);
+                if end > start {
+                    seconds += (end - start).num_seconds();
+                }
+            }
+            day += Duration::days(1);
+        }
+        seconds
+    }
+
+    // How far from start to due now is in working time, None when no working time is between
+    // them, e.g. a task added on saturday and due on sunday
+    pub fn due_ratio(
+        &self,
+        start: DateTime<Utc>,
+        due: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<f32> {
+        let total = self.working_seconds(start, due);
+        if total == 0 {
+            return None;
+        }
+        Some(self.working_seconds(start, now) as f32 / total as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkWeek;
+    use crate::dates::from_local;
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+    use std::collections::BTreeSet;
+
+    fn at(day: u32, hour: u32) -> chrono::DateTime<chrono::Utc> {
+        // 2024-03-01 is a friday
+        from_local(
+            NaiveDate::from_ymd_opt(2024, 3, day)
+                .and_then(|date| date.and_hms_opt(hour, 0, 0))
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn weekends_and_nights_are_not_counted() {
+        let week = WorkWeek {
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            holidays: BTreeSet::new(),
+        };
+        // Friday 13:00 to monday 13:00 is 4 hours friday and 4 hours monday
+        assert_eq!(week.working_seconds(at(1, 13), at(4, 13)), 8 * 3600);
+        // Over the weekend nothing has passed since friday evening
+        let ratio = week.due_ratio(at(1, 13), at(4, 13), at(3, 20)).unwrap();
+        assert_eq!(ratio, 0.5);
+        assert_eq!(week.due_ratio(at(2, 10), at(3, 10), at(3, 9)), None);
+    }
+
+    #[test]
+    fn holidays_are_not_counted() {
+        let week = WorkWeek {
+            days: vec![Weekday::Fri, Weekday::Mon],
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            holidays: [NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()].into(),
+        };
+        assert_eq!(week.working_seconds(at(1, 9), at(5, 9)), 8 * 3600);
+    }
+}
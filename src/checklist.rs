@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::i18n::tr;
+use crate::TaskManager;
+
+// A step inside one task, for work too small to be tasks of its own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    // e.g. [x] write tests
+    pub fn line(&self) -> String {
+        format!("[{}] {}", if self.done { "x" } else { " " }, self.text)
+    }
+}
+
+// e.g. 2 of 3 done
+pub fn checklist_count(checklist: &[ChecklistItem]) -> String {
+    let done = checklist.iter().filter(|item| item.done).count();
+    tr!("checklist_done", "{0} of {1} done", done, checklist.len())
+}
+
+impl TaskManager {
+    pub fn add_checklist_item(&mut self, id: usize, text: String) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let task = &mut self.tasks[id];
+        let item = ChecklistItem { text, done: false };
+        task.record_change("checklist", "none".to_string(), item.line());
+        let mut checklist = task.checklist.clone();
+        checklist.push(item);
+        self.set_field(id, FieldValue::Checklist(checklist));
+    }
+
+    // A copy of the checklist to change, None when there is no such item
+    fn checklist_with_item(&self, id: usize, index: usize) -> Option<Vec<ChecklistItem>> {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return None;
+        }
+        let checklist = &self.tasks[id].checklist;
+        if index >= checklist.len() {
+            eprintln!(
+                "{}",
+                TaskError::InvalidChecklistItem {
+                    index,
+                    count: checklist.len()
+                }
+            );
+            return None;
+        }
+        Some(checklist.clone())
+    }
+
+    // Items count from 0, in the order they were added
+    pub fn set_checklist_item_done(&mut self, id: usize, index: usize, done: bool) {
+        let mut checklist = match self.checklist_with_item(id, index) {
+            Some(checklist) => checklist,
+            None => return,
+        };
+        let old_line = checklist[index].line();
+        checklist[index].done = done;
+        let new_line = checklist[index].line();
+        self.tasks[id].record_change("checklist", old_line, new_line);
+        self.set_field(id, FieldValue::Checklist(checklist));
+    }
+
+    pub fn remove_checklist_item(&mut self, id: usize, index: usize) {
+        let mut checklist = match self.checklist_with_item(id, index) {
+            Some(checklist) => checklist,
+            None => return,
+        };
+        let item = checklist.remove(index);
+        self.tasks[id].record_change("checklist", item.line(), "none".to_string());
+        self.set_field(id, FieldValue::Checklist(checklist));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::checklist_count;
+    use crate::TaskManager;
+
+    #[test]
+    fn checklist_items_are_added_and_checked() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Release".to_string());
+        for text in ["write tests", "update changelog", "tag"] {
+            task_manager.add_checklist_item(0, text.to_string());
+        }
+        task_manager.set_checklist_item_done(0, 1, true);
+        task_manager.set_checklist_item_done(0, 5, true);
+        task_manager.remove_checklist_item(0, 2);
+        let task = &task_manager.tasks[0];
+        let lines: Vec<String> = task.checklist.iter().map(|item| item.line()).collect();
+        assert_eq!(lines, vec!["[ ] write tests", "[x] update changelog"]);
+        assert_eq!(checklist_count(&task.checklist), "1 of 2 done");
+        let last = task.history.last().unwrap();
+        assert_eq!(
+            (last.old_value.as_str(), last.new_value.as_str()),
+            ("[ ] tag", "none")
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Local, Utc};
+use std::error::Error;
+use std::fs::{create_dir_all, read_dir, File};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::TaskError;
+use crate::i18n;
+use crate::TaskManager;
+
+// Saved by snapshot restore first, so a restore can be undone too
+pub const BEFORE_RESTORE: &str = "before-restore";
+
+// Full copies of the task data, kept in a snapshots directory next to it
+fn snapshot_dir(data_file: &Path) -> PathBuf {
+    data_file.with_file_name("snapshots")
+}
+
+fn snapshot_path(data_file: &Path, label: &str) -> Result<PathBuf, TaskError> {
+    // Labels become file names, so nothing that could leave the directory
+    let valid = !label.is_empty()
+        && !label.starts_with('.')
+        && label
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(TaskError::InvalidSnapshotLabel(label.to_string()));
+    }
+    Ok(snapshot_dir(data_file).join(format!("{}.json", label)))
+}
+
+// Labels of the saved snapshots with when they were taken, oldest first
+pub fn snapshots(data_file: &Path) -> Vec<(String, DateTime<Utc>)> {
+    let entries = match read_dir(snapshot_dir(data_file)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut snapshots: Vec<(String, DateTime<Utc>)> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let label = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".json")?
+                .to_string();
+            let modified = path.metadata().ok()?.modified().ok()?;
+            Some((label, DateTime::<Utc>::from(modified)))
+        })
+        .collect();
+    snapshots.sort_by_key(|(label, taken)| (*taken, label.clone()));
+    snapshots
+}
+
+impl TaskManager {
+    // Saves every task as it is now under label, by default the current time e.g. 2024-03-01-1700
+    pub fn create_snapshot(
+        &self,
+        data_file: &Path,
+        label: Option<String>,
+    ) -> Result<String, Box<dyn Error>> {
+        let label = label.unwrap_or_else(|| Local::now().format("%Y-%m-%d-%H%M").to_string());
+        let path = snapshot_path(data_file, &label)?;
+        create_dir_all(snapshot_dir(data_file))?;
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(label)
+    }
+
+    // The tasks saved under label, read like the data file so older snapshots are migrated
+    pub fn load_snapshot(data_file: &Path, label: &str) -> Result<TaskManager, Box<dyn Error>> {
+        let path = snapshot_path(data_file, label)?;
+        if !path.exists() {
+            return Err(TaskError::UnknownSnapshot(label.to_string()).into());
+        }
+        TaskManager::load_from_file(&path)
+    }
+}
+
+pub fn list_snapshots(data_file: &Path, config: &Config) {
+    let snapshots = snapshots(data_file);
+    if snapshots.is_empty() {
+        println!(
+            "{}",
+            i18n::text(
+                "snapshots_empty",
+                "No snapshots, take one with snapshot create"
+            )
+        );
+    }
+    for (label, taken) in snapshots {
+        println!(" {} --- {}", label, config.format_datetime(taken));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::TaskError;
+    use crate::TaskManager;
+
+    #[test]
+    fn snapshots_restore_the_tasks_saved() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-snapshot-{}", std::process::id()));
+        let data_file = data_dir.join("task.json");
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Before the import".to_string());
+        let label = task_manager
+            .create_snapshot(&data_file, Some("pre-import".to_string()))
+            .unwrap();
+        task_manager.add_task("Imported".to_string());
+        let restored = TaskManager::load_snapshot(&data_file, &label).unwrap();
+        assert_eq!(restored.tasks.len(), 1);
+        assert_eq!(super::snapshots(&data_file)[0].0, "pre-import");
+        assert!(task_manager
+            .create_snapshot(&data_file, Some("../escape".to_string()))
+            .is_err());
+        let missing = TaskManager::load_snapshot(&data_file, "missing").unwrap_err();
+        assert_eq!(
+            missing.downcast_ref::<TaskError>(),
+            Some(&TaskError::UnknownSnapshot("missing".to_string()))
+        );
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -0,0 +1,774 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write;
+use std::fs::{metadata, read_to_string, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::business_hours::WorkWeek;
+use crate::custom::CustomField;
+use crate::dates::to_local;
+use crate::error::TaskError;
+use crate::holidays::parse_ics_holidays;
+use crate::i18n::{self, tr, Locale};
+use crate::reports::Report;
+use crate::tags::parse_tag;
+use crate::theme::{parse_style, Element, THEMES};
+use crate::{
+    UrgencySettings, DEFAULT_URGENCY, MAXIMUM_URGENCY, MINIMUM_URGENCY, URGENCY_MULTIPLIER,
+};
+
+const DEFAULT_BREAK_REMINDER_MINUTES: i64 = 60;
+const DEFAULT_WORK_HOURS_PER_DAY: f32 = 8.0;
+const DEFAULT_WORK_DAYS: [&str; 5] = ["mon", "tue", "wed", "thu", "fri"];
+const DEFAULT_WORK_DAY_START: &str = "09:00";
+const DEFAULT_WORK_DAY_END: &str = "17:00";
+const DEFAULT_DATE_FORMAT: &str = "%d/%m/%Y";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M";
+const DEFAULT_HIDE_DESCRIPTION_BELOW: usize = 48;
+const DEFAULT_HIDE_STATUS_BELOW: usize = 24;
+const DEFAULT_JOURNAL_COMPACT_AFTER: usize = 200;
+const STORAGES: [&str; 4] = ["file", "journal", "webdav", "s3"];
+const DEFAULT_SHOW_DUE_FROM: usize = 80;
+const DEFAULT_SHOW_TAGS_FROM: usize = 110;
+
+// User settings read from XDG_CONFIG/task/config.json, every field is optional in the file
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Minutes a task can be continuously active before a break is suggested, 0 disables
+    pub break_reminder_minutes: i64,
+    // Hours of each weekday available for planned work, used by workload
+    pub work_hours_per_day: f32,
+    // Name matched by list --mine and assignee:me, the login name when not set
+    pub user: Option<String>,
+    // Days of the work week, e.g. ["sun", "mon", "tue", "wed", "thu"], and when each day starts
+    // and ends as HH:MM
+    pub work_days: Vec<String>,
+    pub work_day_start: String,
+    pub work_day_end: String,
+    // Count only working time from start to due in urgency, so nothing climbs over the weekend
+    pub urgency_business_hours: bool,
+    // Minimum urgency gained each day by tasks without a due date, the minimum urgency tasks
+    // reach at their due time and the urgency new tasks start with
+    pub urgency_per_day: f32,
+    pub urgency_at_due: f32,
+    pub default_urgency: f32,
+    // Times of day as HH:MM between which notify sends nothing, e.g. "22:00" to "07:00". Missed
+    // reminders are sent once the quiet hours are over
+    pub quiet_hours_start: Option<String>,
+    pub quiet_hours_end: Option<String>,
+    // Days off on top of the work week as YYYY-MM-DD, and an iCalendar file of more of them
+    pub holidays: Vec<String>,
+    pub holiday_calendar: Option<PathBuf>,
+    // Match different forms of english words when searching, e.g. testing and tests
+    pub search_stemming: bool,
+    // Show times like "due in 3 days" next to dates, false for absolute dates only
+    pub relative_times: bool,
+    // Always use the screen reader friendly output of --plain
+    pub plain: bool,
+    // Always draw urgency bars in list, like --bars
+    pub urgency_bars: bool,
+    // en-eu, en-us or the name of a file in locales/ next to the config, see i18n.rs
+    pub locale: String,
+    // strftime style formats, e.g. "%m/%d/%Y" for month first dates, by default the locale's
+    pub input_date_format: String,
+    pub date_format: String,
+    pub time_format: String,
+    // Severities grouped into one notification by notify --digest, others are sent one per task
+    pub digest_severities: Vec<String>,
+    // Days a task stays done before it is moved to the archive, 0 disables archiving
+    pub archive_done_after_days: i64,
+    // Days over archive_done_after_days for done tasks with a tag or in a project, e.g.
+    // "+errands": 7, "+work": 90 or "project:home": 30. The longest matching rule wins, 0 keeps
+    // the tasks
+    pub archive_rules: BTreeMap<String, i64>,
+    // Days a task stays done before it is deleted for good, archived or not, 0 keeps them
+    pub delete_done_after_days: i64,
+    // Days without any change after which an open task is tagged STALE and comes up in review
+    pub stale_after_days: i64,
+    // Shared secret of sync-server and sync remote, unless --token is given
+    pub sync_token: Option<String>,
+    // "file" rewrites the data file on every save, "journal" appends only what changed to
+    // events.jsonl and rewrites the file once compact_after events were logged since, "webdav"
+    // and "s3" keep the file on a WebDAV server or in a bucket as well, see remote.rs
+    pub storage: String,
+    pub journal_compact_after: usize,
+    // File the webdav storage keeps the tasks in, e.g. https://cloud.example.com/remote.php/dav/
+    // files/me/tasks.json, and the user to log in as. The password is read from
+    // TASKS_WEBDAV_PASSWORD, or curl finds both in ~/.netrc
+    pub webdav_url: Option<String>,
+    pub webdav_user: Option<String>,
+    // Object the s3 storage keeps the tasks in, e.g. https://bucket.s3.eu-west-1.amazonaws.com/
+    // tasks.json or http://localhost:9000/bucket/tasks.json for minio, and the bucket's region.
+    // The keys are read from AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY
+    pub s3_url: Option<String>,
+    pub s3_region: String,
+    // Named list --format templates, e.g. "short": "{id} {title}"
+    pub list_formats: BTreeMap<String, String>,
+    // Terminal widths below which list drops the description and then the status column
+    pub hide_description_below: usize,
+    pub hide_status_below: usize,
+    // Terminal width from which list adds a due column, unless --due or --no-due is given
+    pub show_due_from: usize,
+    // Terminal width from which list adds project and tags columns, when any listed task has
+    // either, unless --tags or --no-tags is given
+    pub show_tags_from: usize,
+    // Extra fields tasks can have, set with edit --set name=value
+    pub custom_fields: BTreeMap<String, CustomField>,
+    // Saved lists run with report <name>, one named list replaces the defaults of list
+    pub reports: BTreeMap<String, Report>,
+    // Subcommand aliases expanded before parsing, e.g. "d": "done", over the built in ones
+    pub aliases: BTreeMap<String, String>,
+    // Built in colour theme, one of default, solarized or mono
+    pub theme: String,
+    // Styles over the theme's by element, e.g. "overdue": "bold red" or "header": "none"
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            break_reminder_minutes: DEFAULT_BREAK_REMINDER_MINUTES,
+            work_hours_per_day: DEFAULT_WORK_HOURS_PER_DAY,
+            user: None,
+            work_days: DEFAULT_WORK_DAYS
+                .iter()
+                .map(|day| day.to_string())
+                .collect(),
+            work_day_start: DEFAULT_WORK_DAY_START.to_string(),
+            work_day_end: DEFAULT_WORK_DAY_END.to_string(),
+            urgency_business_hours: false,
+            urgency_per_day: URGENCY_MULTIPLIER,
+            urgency_at_due: MAXIMUM_URGENCY,
+            default_urgency: DEFAULT_URGENCY,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            holidays: Vec::new(),
+            holiday_calendar: None,
+            search_stemming: true,
+            relative_times: true,
+            plain: false,
+            urgency_bars: false,
+            locale: "en-eu".to_string(),
+            input_date_format: DEFAULT_DATE_FORMAT.to_string(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            time_format: DEFAULT_TIME_FORMAT.to_string(),
+            digest_severities: ["overdue", "due-today", "reminder", "due-soon"]
+                .iter()
+                .map(|severity| severity.to_string())
+                .collect(),
+            archive_done_after_days: 0,
+            archive_rules: BTreeMap::new(),
+            delete_done_after_days: 0,
+            stale_after_days: 30,
+            sync_token: None,
+            storage: "file".to_string(),
+            journal_compact_after: DEFAULT_JOURNAL_COMPACT_AFTER,
+            webdav_url: None,
+            webdav_user: None,
+            s3_url: None,
+            s3_region: "us-east-1".to_string(),
+            list_formats: BTreeMap::new(),
+            hide_description_below: DEFAULT_HIDE_DESCRIPTION_BELOW,
+            hide_status_below: DEFAULT_HIDE_STATUS_BELOW,
+            show_due_from: DEFAULT_SHOW_DUE_FROM,
+            show_tags_from: DEFAULT_SHOW_TAGS_FROM,
+            custom_fields: BTreeMap::new(),
+            reports: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            theme: "default".to_string(),
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+// Path of a file stored in the task config directory
+pub fn config_path(filename: &str) -> Option<PathBuf> {
+    let mut path = config_dir()?;
+    path.push("task");
+    path.push(filename);
+    Some(path)
+}
+
+impl Config {
+    // The config in the file with anything invalid replaced by its default, and what was replaced
+    pub fn load_from_file(filename: &PathBuf) -> Result<(Self, Vec<String>), Box<dyn Error>> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+        let raw: Value = serde_json::from_reader(reader)?;
+        let mut config: Config = serde_json::from_value(raw.clone())?;
+        let mut problems = Vec::new();
+        if let Err(err) = config.apply_locale(&raw) {
+            problems.push(err.to_string());
+        }
+        problems.extend(config.check());
+        Ok((config, problems))
+    }
+
+    // Date and time formats not set in the config follow the locale, whose messages replace the
+    // english ones from then on. An unknown locale leaves the english ones
+    fn apply_locale(&mut self, raw: &Value) -> Result<(), TaskError> {
+        let (locale, result) = match Locale::load(&self.locale) {
+            Ok(locale) => (locale, Ok(())),
+            Err(err) => (Locale::default(), Err(err)),
+        };
+        for (key, format, locale_format) in [
+            (
+                "input_date_format",
+                &mut self.input_date_format,
+                &locale.date_format,
+            ),
+            ("date_format", &mut self.date_format, &locale.date_format),
+            ("time_format", &mut self.time_format, &locale.time_format),
+        ] {
+            if raw.get(key).is_none() {
+                *format = locale_format.clone();
+            }
+        }
+        i18n::set_messages(locale.messages);
+        result
+    }
+
+    // Missing config is normal, a broken one is worth telling the user about
+    pub fn load() -> Self {
+        let path = match config_path("config.json") {
+            Some(path) => path,
+            None => return Config::default(),
+        };
+        if !path.exists() {
+            return Config::default();
+        }
+        match Config::load_from_file(&path) {
+            Ok((config, problems)) => {
+                for problem in problems {
+                    eprintln!("{}", problem);
+                }
+                config
+            }
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        "config_unreadable",
+                        "Failed to read config {0}: {1}, using defaults",
+                        path.display(),
+                        err
+                    )
+                );
+                Config::default()
+            }
+        }
+    }
+
+    // Anything invalid is replaced by its default, returning what was replaced
+    fn check(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        self.check_formats(&mut problems);
+        self.check_colors(&mut problems);
+        self.check_work_week(&mut problems);
+        self.check_urgency(&mut problems);
+        self.check_quiet_hours(&mut problems);
+        self.check_storage(&mut problems);
+        self.check_archive_rules(&mut problems);
+        problems
+    }
+
+    // A bad format string would make chrono fail while printing, so fall back early
+    fn check_formats(&mut self, problems: &mut Vec<String>) {
+        let sample = NaiveDate::from_ymd_opt(2000, 1, 31)
+            .and_then(|date| date.and_hms_opt(12, 0, 0))
+            .unwrap();
+        for (format, default) in [
+            (&mut self.input_date_format, DEFAULT_DATE_FORMAT),
+            (&mut self.date_format, DEFAULT_DATE_FORMAT),
+            (&mut self.time_format, DEFAULT_TIME_FORMAT),
+        ] {
+            let mut output = String::new();
+            if write!(output, "{}", sample.format(format)).is_err() {
+                problems.push(tr!(
+                    "config_invalid_date_format",
+                    "Invalid date format {0} in config, using {1}",
+                    format,
+                    default
+                ));
+                *format = default.to_string();
+            }
+        }
+    }
+
+    // Unknown themes, elements and styles are reported once here and then left out
+    fn check_colors(&mut self, problems: &mut Vec<String>) {
+        if !THEMES.contains(&self.theme.as_str()) {
+            problems.push(tr!(
+                "config_unknown_theme",
+                "Unknown theme {0} in config, expected one of {1}",
+                self.theme,
+                THEMES.join(", ")
+            ));
+            self.theme = "default".to_string();
+        }
+        self.colors.retain(|name, style| {
+            let valid = Element::parse(name).is_some() && parse_style(style).is_some();
+            if !valid {
+                problems.push(tr!(
+                    "config_invalid_color",
+                    "Invalid color {0}: {1} in config, ignoring it",
+                    name,
+                    style
+                ));
+            }
+            valid
+        });
+    }
+
+    // Rules are +tag or project:name, anything else would never match
+    fn check_archive_rules(&mut self, problems: &mut Vec<String>) {
+        self.archive_rules.retain(|rule, _| {
+            let valid = match rule.strip_prefix('+') {
+                Some(tag) => parse_tag(tag).is_ok_and(|parsed| parsed == tag),
+                None => rule
+                    .strip_prefix("project:")
+                    .is_some_and(|project| !project.trim().is_empty()),
+            };
+            if !valid {
+                problems.push(tr!(
+                    "config_invalid_archive_rule",
+                    "Invalid archive rule {0} in config, expected +tag or project:name, ignoring it",
+                    rule
+                ));
+            }
+            valid
+        });
+    }
+
+    fn check_storage(&mut self, problems: &mut Vec<String>) {
+        if !STORAGES.contains(&self.storage.as_str()) {
+            problems.push(tr!(
+                "config_unknown_storage",
+                "Unknown storage {0} in config, expected one of {1}",
+                self.storage,
+                STORAGES.join(", ")
+            ));
+            self.storage = "file".to_string();
+        }
+        let url = match self.storage.as_str() {
+            "webdav" => Some(("webdav_url", &self.webdav_url)),
+            "s3" => Some(("s3_url", &self.s3_url)),
+            _ => None,
+        };
+        if let Some((key, None)) = url {
+            problems.push(tr!(
+                "config_storage_url_missing",
+                "The {0} storage needs {1} in config, using file",
+                self.storage,
+                key
+            ));
+            self.storage = "file".to_string();
+        }
+    }
+
+    pub fn journal_storage(&self) -> bool {
+        self.storage == "journal"
+    }
+
+    // A work week that can't be read falls back to monday to friday, 9 to 5
+    fn check_work_week(&mut self, problems: &mut Vec<String>) {
+        let days_valid = self
+            .work_days
+            .iter()
+            .all(|day| day.parse::<Weekday>().is_ok());
+        if !days_valid {
+            problems.push(tr!(
+                "config_invalid_work_days",
+                "Invalid work days {0} in config, using {1}",
+                self.work_days.join(", "),
+                DEFAULT_WORK_DAYS.join(", ")
+            ));
+            self.work_days = DEFAULT_WORK_DAYS
+                .iter()
+                .map(|day| day.to_string())
+                .collect();
+        }
+        let start = NaiveTime::parse_from_str(&self.work_day_start, "%H:%M");
+        let end = NaiveTime::parse_from_str(&self.work_day_end, "%H:%M");
+        self.holidays.retain(|day| {
+            let valid = NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok();
+            if !valid {
+                problems.push(tr!(
+                    "config_invalid_holiday",
+                    "Invalid holiday {0} in config, expected YYYY-MM-DD",
+                    day
+                ));
+            }
+            valid
+        });
+        let hours_valid = matches!((start, end), (Ok(start), Ok(end)) if start < end);
+        if !hours_valid {
+            problems.push(tr!(
+                "config_invalid_work_hours",
+                "Invalid work day {0} to {1} in config, using {2} to {3}",
+                self.work_day_start,
+                self.work_day_end,
+                DEFAULT_WORK_DAY_START,
+                DEFAULT_WORK_DAY_END
+            ));
+            self.work_day_start = DEFAULT_WORK_DAY_START.to_string();
+            self.work_day_end = DEFAULT_WORK_DAY_END.to_string();
+        }
+    }
+
+    // Urgencies that would never rise or start out of range fall back to their defaults
+    fn check_urgency(&mut self, problems: &mut Vec<String>) {
+        let rising: fn(f32) -> bool = |urgency| urgency > 0.0;
+        let in_range: fn(f32) -> bool =
+            |urgency| (MINIMUM_URGENCY..=MAXIMUM_URGENCY).contains(&urgency);
+        for (key, value, valid, default) in [
+            (
+                "urgency_per_day",
+                &mut self.urgency_per_day,
+                rising,
+                URGENCY_MULTIPLIER,
+            ),
+            (
+                "urgency_at_due",
+                &mut self.urgency_at_due,
+                rising,
+                MAXIMUM_URGENCY,
+            ),
+            (
+                "default_urgency",
+                &mut self.default_urgency,
+                in_range,
+                DEFAULT_URGENCY,
+            ),
+        ] {
+            if !valid(*value) {
+                problems.push(tr!(
+                    "config_invalid_urgency",
+                    "Invalid {0} {1} in config, using {2}",
+                    key,
+                    value,
+                    default
+                ));
+                *value = default;
+            }
+        }
+    }
+
+    // Quiet hours need both ends, otherwise there are none
+    fn check_quiet_hours(&mut self, problems: &mut Vec<String>) {
+        let unset = self.quiet_hours_start.is_none() && self.quiet_hours_end.is_none();
+        if unset || self.quiet_hours().is_some() {
+            return;
+        }
+        problems.push(tr!(
+            "config_invalid_quiet_hours",
+            "Invalid quiet hours {0} to {1} in config, expected HH:MM, ignoring them",
+            self.quiet_hours_start.as_deref().unwrap_or("?"),
+            self.quiet_hours_end.as_deref().unwrap_or("?")
+        ));
+        self.quiet_hours_start = None;
+        self.quiet_hours_end = None;
+    }
+
+    fn quiet_hours(&self) -> Option<(NaiveTime, NaiveTime)> {
+        let parse =
+            |time: &Option<String>| NaiveTime::parse_from_str(time.as_deref()?, "%H:%M").ok();
+        Some((
+            parse(&self.quiet_hours_start)?,
+            parse(&self.quiet_hours_end)?,
+        ))
+    }
+
+    // Quiet hours that end before they start run past midnight
+    pub fn quiet_at(&self, time: NaiveTime) -> bool {
+        match self.quiet_hours() {
+            Some((start, end)) if start <= end => start <= time && time < end,
+            Some((start, end)) => start <= time || time < end,
+            None => false,
+        }
+    }
+
+    pub fn user_name(&self) -> Option<String> {
+        self.user
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+    }
+
+    pub fn work_days(&self) -> Vec<Weekday> {
+        self.work_days
+            .iter()
+            .filter_map(|day| day.parse().ok())
+            .collect()
+    }
+
+    // Configured days and the calendar's, a calendar that can't be read is reported and skipped
+    pub fn holidays(&self) -> BTreeSet<NaiveDate> {
+        let mut holidays: BTreeSet<NaiveDate> = self
+            .holidays
+            .iter()
+            .filter_map(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+            .collect();
+        if let Some(path) = &self.holiday_calendar {
+            match read_to_string(path) {
+                Ok(text) => holidays.extend(parse_ics_holidays(&text)),
+                Err(err) => eprintln!(
+                    "{}",
+                    tr!(
+                        "holiday_calendar_unreadable",
+                        "Failed to read holiday calendar {0}: {1}",
+                        path.display(),
+                        err
+                    )
+                ),
+            }
+        }
+        holidays
+    }
+
+    // Used for urgency only when urgency_business_hours is set
+    pub fn work_week(&self) -> Option<WorkWeek> {
+        if !self.urgency_business_hours {
+            return None;
+        }
+        Some(WorkWeek {
+            days: self.work_days(),
+            holidays: self.holidays(),
+            start: NaiveTime::parse_from_str(&self.work_day_start, "%H:%M").ok()?,
+            end: NaiveTime::parse_from_str(&self.work_day_end, "%H:%M").ok()?,
+        })
+    }
+
+    pub fn urgency_settings(&self) -> UrgencySettings {
+        UrgencySettings {
+            work_week: self.work_week(),
+            per_day: self.urgency_per_day,
+            at_due: self.urgency_at_due,
+            default: self.default_urgency,
+        }
+    }
+
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        date.format(&self.date_format).to_string()
+    }
+
+    // e.g. 17:00, 31/01/2000
+    pub fn format_datetime(&self, datetime: DateTime<Utc>) -> String {
+        to_local(datetime)
+            .format(&format!("{}, {}", self.time_format, self.date_format))
+            .to_string()
+    }
+
+    pub fn format_time(&self, datetime: DateTime<Utc>) -> String {
+        to_local(datetime).format(&self.time_format).to_string()
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Settings whose values differ between two configs, by their name in the file
+fn changed_settings(old: &Config, new: &Config) -> Vec<String> {
+    let (old, new) = match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(Value::Object(old)), Ok(Value::Object(new))) => (old, new),
+        _ => return Vec::new(),
+    };
+    new.iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+// Lets the modes that keep running, like serve and watch, pick up changes to config.json as
+// they are made
+pub struct ConfigWatch {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    // Given with --plain, which stays on whatever the file says
+    plain: bool,
+}
+
+impl ConfigWatch {
+    pub fn new(plain: bool) -> Self {
+        ConfigWatch::at(config_path("config.json"), plain)
+    }
+
+    fn at(path: Option<PathBuf>, plain: bool) -> Self {
+        let modified = path.as_ref().and_then(modified);
+        ConfigWatch {
+            path,
+            modified,
+            plain,
+        }
+    }
+
+    // The file was written since it was last read
+    pub fn changed(&self) -> bool {
+        self.path.as_ref().and_then(modified) != self.modified
+    }
+
+    // Reads the file again when it changed, telling which settings were reloaded. A config that
+    // can't be read or has invalid settings is reported and the one in use kept, along with its
+    // messages
+    pub fn reload(&mut self, config: &mut Config) -> Option<String> {
+        let path = match &self.path {
+            Some(path) if self.changed() => path,
+            _ => return None,
+        };
+        self.modified = modified(path);
+        let previous_messages = i18n::messages();
+        let mut new_config = if path.exists() {
+            let loaded = match Config::load_from_file(path) {
+                Ok((new_config, problems)) if problems.is_empty() => Ok(new_config),
+                Ok((_, problems)) => Err(problems.join("; ")),
+                Err(err) => Err(err.to_string()),
+            };
+            match loaded {
+                Ok(new_config) => new_config,
+                Err(problem) => {
+                    i18n::set_messages(previous_messages);
+                    return Some(tr!(
+                        "config_reload_rejected",
+                        "Failed to reload config {0}: {1}, keeping the current one",
+                        path.display(),
+                        problem
+                    ));
+                }
+            }
+        } else {
+            i18n::set_messages(BTreeMap::new());
+            Config::default()
+        };
+        new_config.plain |= self.plain;
+        let changed = changed_settings(config, &new_config);
+        if changed.is_empty() {
+            return None;
+        }
+        *config = new_config;
+        Some(tr!(
+            "config_reloaded",
+            "Reloaded config: {0}",
+            changed.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigWatch};
+    use crate::TaskManager;
+    use chrono::{NaiveTime, Utc};
+    use std::fs::{create_dir_all, remove_dir_all, write, File};
+    use std::time::{Duration, SystemTime};
+
+    // Written a second after the last write, file times can be too coarse to tell writes apart
+    // otherwise
+    fn rewrite(path: &std::path::Path, text: &str, written: &mut SystemTime) {
+        write(path, text).unwrap();
+        *written += Duration::from_secs(1);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(*written)
+            .unwrap();
+    }
+
+    #[test]
+    fn changed_config_is_reloaded_unless_broken() {
+        let config_dir = std::env::temp_dir().join(format!("tasks-config-{}", std::process::id()));
+        create_dir_all(&config_dir).unwrap();
+        let path = config_dir.join("config.json");
+        write(&path, "{}").unwrap();
+        let mut watch = ConfigWatch::at(Some(path.clone()), false);
+        let mut config = Config::default();
+        assert_eq!(watch.reload(&mut config), None);
+
+        let mut written = SystemTime::now();
+        let mut rewrite = |text: &str| rewrite(&path, text, &mut written);
+        rewrite(r#"{"theme": "mono", "stale_after_days": 3}"#);
+        assert!(watch.changed());
+        assert_eq!(
+            watch.reload(&mut config).as_deref(),
+            Some("Reloaded config: stale_after_days, theme")
+        );
+        assert_eq!(config.theme, "mono");
+        assert_eq!(config.stale_after_days, 3);
+
+        rewrite(r#"{"theme": "#);
+        assert!(watch.reload(&mut config).unwrap().starts_with("Failed"));
+        assert_eq!(config.theme, "mono");
+
+        rewrite(r#"{"theme": "neon", "stale_after_days": 5}"#);
+        let message = watch.reload(&mut config).unwrap();
+        assert!(message.contains("Unknown theme neon"));
+        assert_eq!(config.theme, "mono");
+        assert_eq!(config.stale_after_days, 3);
+        remove_dir_all(&config_dir).unwrap();
+    }
+
+    #[test]
+    fn reloaded_coefficients_change_urgency() {
+        let config_dir =
+            std::env::temp_dir().join(format!("tasks-urgency-config-{}", std::process::id()));
+        create_dir_all(&config_dir).unwrap();
+        let path = config_dir.join("config.json");
+        write(&path, "{}").unwrap();
+        let mut watch = ConfigWatch::at(Some(path.clone()), false);
+        let mut config = Config::default();
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Clean gutters".to_string());
+        task_manager.tasks[0].start_time = Some(Utc::now() - chrono::Duration::days(10));
+        task_manager.urgency = config.urgency_settings();
+        task_manager.calculate_urgencies();
+        assert_eq!(task_manager.tasks[0].effective_urgency, 5.0);
+
+        let mut written = SystemTime::now();
+        rewrite(
+            &path,
+            r#"{"urgency_per_day": 0.8, "default_urgency": 1}"#,
+            &mut written,
+        );
+        assert!(watch.reload(&mut config).is_some());
+        task_manager.urgency = config.urgency_settings();
+        task_manager.calculate_urgencies();
+        assert_eq!(task_manager.tasks[0].effective_urgency, 8.0);
+        task_manager.add_task("Sweep porch".to_string());
+        assert_eq!(task_manager.tasks[1].urgency, 1.0);
+
+        rewrite(&path, r#"{"urgency_per_day": -1}"#, &mut written);
+        assert!(watch
+            .reload(&mut config)
+            .unwrap()
+            .contains("Invalid urgency_per_day -1"));
+        assert_eq!(config.urgency_per_day, 0.8);
+        remove_dir_all(&config_dir).unwrap();
+    }
+
+    #[test]
+    fn quiet_hours_can_run_past_midnight() {
+        let at = |time: &str| NaiveTime::parse_from_str(time, "%H:%M").unwrap();
+        let mut config = Config {
+            quiet_hours_start: Some("22:00".to_string()),
+            quiet_hours_end: Some("07:00".to_string()),
+            ..Config::default()
+        };
+        assert!(config.quiet_at(at("23:30")));
+        assert!(config.quiet_at(at("06:59")));
+        assert!(!config.quiet_at(at("07:00")));
+        assert!(!config.quiet_at(at("12:00")));
+
+        config.quiet_hours_end = Some("7am".to_string());
+        assert_eq!(config.check().len(), 1);
+        assert_eq!(config.quiet_hours_start, None);
+        assert!(!config.quiet_at(at("23:30")));
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dates::parse_day;
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::TaskManager;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    #[default]
+    String,
+    Number,
+    Date,
+}
+
+// A field declared under custom_fields in config, e.g. "customer": {"type": "string"}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomField {
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    // When not empty the only values allowed, compared ignoring case
+    pub values: Vec<String>,
+}
+
+impl CustomField {
+    // The value as it is stored, dates are kept as 2024-01-31 so they sort as text
+    pub fn check(&self, name: &str, value: &str, config: &Config) -> Result<String, TaskError> {
+        let invalid = |expected: String| TaskError::InvalidCustomValue {
+            field: name.to_string(),
+            value: value.to_string(),
+            expected,
+        };
+        if !self.values.is_empty() {
+            return self
+                .values
+                .iter()
+                .find(|allowed| allowed.eq_ignore_ascii_case(value))
+                .cloned()
+                .ok_or_else(|| invalid(format!("one of {}", self.values.join(", "))));
+        }
+        match self.field_type {
+            FieldType::String => Ok(value.to_string()),
+            FieldType::Number => value
+                .parse::<f64>()
+                .map(|_| value.to_string())
+                .map_err(|_| invalid("a number".to_string())),
+            FieldType::Date => parse_day(value, &config.input_date_format)
+                .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .ok_or_else(|| invalid("a date".to_string())),
+        }
+    }
+}
+
+// The field and value of name=value as stored, none for an empty value
+pub fn parse_assignment(
+    assignment: &str,
+    config: &Config,
+) -> Result<(String, Option<String>), TaskError> {
+    let (name, value) = match assignment.split_once('=') {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => return Err(TaskError::UnknownField(assignment.to_string())),
+    };
+    let field = config
+        .custom_fields
+        .get(name)
+        .ok_or_else(|| TaskError::UnknownField(name.to_string()))?;
+    if value.is_empty() {
+        return Ok((name.to_string(), None));
+    }
+    Ok((name.to_string(), Some(field.check(name, value, config)?)))
+}
+
+impl TaskManager {
+    // Each assignment is name=value, an empty value removes the field from the task
+    pub fn set_custom_fields(&mut self, id: usize, assignments: &[String], config: &Config) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        for assignment in assignments {
+            let (name, value) = match parse_assignment(assignment, config) {
+                Ok(assignment) => assignment,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    continue;
+                }
+            };
+            let task = &mut self.tasks[id];
+            let old_value = task.custom.get(&name).cloned();
+            if old_value == value {
+                continue;
+            }
+            task.record_change(
+                &name,
+                old_value.unwrap_or_else(|| "none".to_string()),
+                value.clone().unwrap_or_else(|| "none".to_string()),
+            );
+            let mut custom = task.custom.clone();
+            match value {
+                Some(value) => custom.insert(name, value),
+                None => custom.remove(&name),
+            };
+            self.set_field(id, FieldValue::Custom(custom));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomField, FieldType};
+    use crate::config::Config;
+    use crate::TaskManager;
+
+    #[test]
+    fn values_are_checked_against_the_declaration() {
+        let mut config = Config::default();
+        config.custom_fields.insert(
+            "customer".to_string(),
+            CustomField {
+                field_type: FieldType::String,
+                values: vec!["ACME".to_string(), "Initech".to_string()],
+            },
+        );
+        config.custom_fields.insert(
+            "points".to_string(),
+            CustomField {
+                field_type: FieldType::Number,
+                values: Vec::new(),
+            },
+        );
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("invoice".to_string());
+        let assignments = ["customer=acme", "points=three", "colour=blue"].map(str::to_string);
+        debug_manager.set_custom_fields(0, &assignments, &config);
+        let custom = &debug_manager.tasks[0].custom;
+        assert_eq!(custom.get("customer").map(String::as_str), Some("ACME"));
+        assert!(!custom.contains_key("points"));
+
+        debug_manager.set_custom_fields(0, &["customer=".to_string()], &config);
+        assert!(debug_manager.tasks[0].custom.is_empty());
+        assert_eq!(debug_manager.tasks[0].history.len(), 2);
+    }
+}
@@ -0,0 +1,219 @@
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+
+use crate::i18n::tr;
+
+// Parses short durations such as 90m, 2h, 1h30m or 3d
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let mut total_minutes: i64 = 0;
+    let mut number = String::new();
+    let mut found_unit = false;
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let amount: i64 = number.parse().ok()?;
+        number.clear();
+        total_minutes += match c.to_ascii_lowercase() {
+            'w' => amount * 7 * 24 * 60,
+            'd' => amount * 24 * 60,
+            'h' => amount * 60,
+            'm' => amount,
+            _ => return None,
+        };
+        found_unit = true;
+    }
+    // A bare trailing number is treated as minutes
+    if !number.is_empty() {
+        total_minutes += number.parse::<i64>().ok()?;
+    } else if !found_unit {
+        return None;
+    }
+    Some(Duration::minutes(total_minutes))
+}
+
+// Render a duration as hours and minutes, e.g. 1h 05m
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+// Coarser rendering for long spans, whole days once past a day
+pub fn format_days(duration: Duration) -> String {
+    if duration.num_days().abs() >= 1 {
+        format!("{}d", duration.num_days())
+    } else {
+        format_duration(duration)
+    }
+}
+
+// Largest sensible unit for a span of time, e.g. 3 days or 5 weeks
+pub fn format_span(duration: Duration) -> String {
+    let minutes = duration.num_minutes().abs();
+    let (amount, unit) = if minutes < 60 {
+        (minutes, "minute")
+    } else if minutes < 48 * 60 {
+        (minutes / 60, "hour")
+    } else if minutes < 14 * 24 * 60 {
+        (minutes / (24 * 60), "day")
+    } else if minutes < 60 * 24 * 60 {
+        (minutes / (7 * 24 * 60), "week")
+    } else if minutes < 365 * 24 * 60 {
+        (minutes / (30 * 24 * 60), "month")
+    } else {
+        (minutes / (365 * 24 * 60), "year")
+    };
+    if amount == 1 {
+        format!("{} {}", amount, unit)
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
+// Times are stored in UTC so they stay correct across DST changes and machines in other zones
+pub fn to_local(datetime: DateTime<Utc>) -> NaiveDateTime {
+    datetime.with_timezone(&Local).naive_local()
+}
+
+// Typed dates and older data files are local wall clock times
+pub fn from_local(datetime: NaiveDateTime) -> DateTime<Utc> {
+    match Local.from_local_datetime(&datetime).earliest() {
+        Some(local) => local.with_timezone(&Utc),
+        // Skipped by a DST change, use the same time after the gap
+        None => from_local(datetime + Duration::hours(1)),
+    }
+}
+
+// e.g. 5 weeks ago or in 2 days
+pub fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    if time <= now {
+        tr!("ago", "{0} ago", format_span(now - time))
+    } else {
+        format!("in {}", format_span(time - now))
+    }
+}
+
+// e.g. due in 3 days or overdue by 2 hours
+pub fn format_due(due_time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    if due_time < now {
+        tr!("overdue_by", "overdue by {0}", format_span(now - due_time))
+    } else {
+        tr!("due_in", "due in {0}", format_span(due_time - now))
+    }
+}
+
+// Accepts today, tomorrow, yesterday, a weekday name (the next one, counting today) or a date in input_format
+pub fn parse_day(input: &str, input_format: &str) -> Option<NaiveDate> {
+    let today = Local::now().date_naive();
+    match input.trim().to_lowercase().as_str() {
+        "today" => Some(today),
+        "tomorrow" => Some(today + Duration::days(1)),
+        "yesterday" => Some(today - Duration::days(1)),
+        other => match other.parse::<Weekday>() {
+            Ok(weekday) => {
+                let days_ahead = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                Some(today + Duration::days(days_ahead as i64))
+            }
+            Err(_) => NaiveDate::parse_from_str(other, input_format).ok(),
+        },
+    }
+}
+
+// Reads times written with an offset, or naive local times from before times were stored in UTC
+pub mod serde_utc {
+    use super::from_local;
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    fn parse<E: Error>(value: &str) -> Result<DateTime<Utc>, E> {
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Ok(datetime.with_timezone(&Utc));
+        }
+        value
+            .parse::<NaiveDateTime>()
+            .map(from_local)
+            .map_err(E::custom)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DateTime<Utc>, D::Error> {
+        parse(&String::deserialize(d)?)
+    }
+
+    pub fn deserialize_option<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|value| parse(&value))
+            .transpose()
+    }
+}
+
+// Store durations as whole seconds in the data file
+pub mod serde_seconds {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => s.serialize_some(&duration.num_seconds()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<i64>::deserialize(d)?.map(Duration::seconds))
+    }
+}
+
+// Durations by name, e.g. a time goal for each tag, also as whole seconds
+pub mod serde_seconds_map {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(
+        value: &BTreeMap<String, Duration>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.collect_map(
+            value
+                .iter()
+                .map(|(name, duration)| (name, duration.num_seconds())),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<BTreeMap<String, Duration>, D::Error> {
+        Ok(BTreeMap::<String, i64>::deserialize(d)?
+            .into_iter()
+            .map(|(name, seconds)| (name, Duration::seconds(seconds)))
+            .collect())
+    }
+}
+
+// Lists of durations, also as whole seconds
+pub mod serde_seconds_list {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[Duration], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(value.iter().map(|duration| duration.num_seconds()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Duration>, D::Error> {
+        Ok(Vec::<i64>::deserialize(d)?
+            .into_iter()
+            .map(Duration::seconds)
+            .collect())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::{Status, Task, TaskManager};
+
+// Dependencies are kept by uid, as IDs change whenever the list is re-sorted
+pub fn dependencies_value(depends_on: &[u64]) -> String {
+    if depends_on.is_empty() {
+        "none".to_string()
+    } else {
+        let uids: Vec<String> = depends_on.iter().map(|uid| format!("#{}", uid)).collect();
+        uids.join(" ")
+    }
+}
+
+impl TaskManager {
+    // Adds the tasks at ids as dependencies, clearing the existing ones first when clear is set
+    pub fn set_dependencies(&mut self, id: usize, ids: &[usize], clear: bool) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let mut depends_on = if clear {
+            Vec::new()
+        } else {
+            self.tasks[id].depends_on.clone()
+        };
+        for &dependency in ids {
+            if !self.verify_id(dependency) || dependency == id {
+                eprintln!("{}", TaskError::InvalidId(dependency));
+                continue;
+            }
+            if self.waits_on(dependency, self.tasks[id].uid) {
+                eprintln!("{}", TaskError::DependencyCycle { id, dependency });
+                continue;
+            }
+            let uid = self.tasks[dependency].uid;
+            if !depends_on.contains(&uid) {
+                depends_on.push(uid);
+            }
+        }
+        let task = &mut self.tasks[id];
+        task.record_change(
+            "depends on",
+            dependencies_value(&task.depends_on),
+            dependencies_value(&depends_on),
+        );
+        self.set_field(id, FieldValue::DependsOn(depends_on));
+    }
+
+    // Progress and BLOCKED both follow the tasks depended on. Those no longer in the list were
+    // archived or removed, so they don't block
+    pub fn derive_from_dependencies(&mut self) {
+        self.derive_progress();
+        let blocked: Vec<bool> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                self.dependencies(task)
+                    .iter()
+                    .any(|(_, dependency)| dependency.status != Status::Done)
+            })
+            .collect();
+        for (task, blocked) in self.tasks.iter_mut().zip(blocked) {
+            task.blocked = blocked;
+        }
+    }
+
+    // Whether the task at id waits on the task with uid, directly or through the tasks it waits on
+    fn waits_on(&self, id: usize, uid: u64) -> bool {
+        let mut seen = Vec::new();
+        let mut queue = self.tasks[id].depends_on.clone();
+        while let Some(next) = queue.pop() {
+            if next == uid {
+                return true;
+            }
+            if seen.contains(&next) {
+                continue;
+            }
+            seen.push(next);
+            if let Some(task) = self.tasks.iter().find(|task| task.uid == next) {
+                queue.extend(task.depends_on.iter().copied());
+            }
+        }
+        false
+    }
+
+    // Current index and task of each dependency still in the list
+    pub fn dependencies(&self, task: &Task) -> Vec<(usize, &Task)> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| task.depends_on.contains(&other.uid))
+            .collect()
+    }
+}
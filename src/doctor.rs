@@ -0,0 +1,193 @@
+use std::fs::{metadata, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::config::config_path;
+use crate::i18n::{self, tr};
+use crate::migrate::SCHEMA_VERSION;
+use crate::{Status, TaskManager, MAXIMUM_URGENCY, MINIMUM_URGENCY};
+
+// Something odd in the data file and how to fix it
+struct Problem {
+    id: usize,
+    issue: String,
+    fix: String,
+}
+
+fn describe_path(label: &str, path: &Path) {
+    match metadata(path) {
+        Ok(metadata) => println!(
+            "{}",
+            tr!(
+                "doctor_file",
+                "{0}: {1} ({2} bytes)",
+                label,
+                path.display(),
+                metadata.len()
+            )
+        ),
+        Err(_) => println!(
+            "{}",
+            tr!(
+                "doctor_file_missing",
+                "{0}: {1} (missing)",
+                label,
+                path.display()
+            )
+        ),
+    }
+}
+
+impl TaskManager {
+    fn problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for (id, task) in self.tasks.iter().enumerate() {
+            if task.start_time.is_none() {
+                problems.push(Problem {
+                    id,
+                    issue: i18n::text(
+                        "doctor_no_start_time",
+                        "has no start_time, so its urgency never rises",
+                    ),
+                    fix: i18n::text(
+                        "doctor_fix_start_time",
+                        "add a start_time to the task in the data file",
+                    ),
+                });
+            }
+            if task.urgency.is_nan() || !(MINIMUM_URGENCY..=MAXIMUM_URGENCY).contains(&task.urgency)
+            {
+                problems.push(Problem {
+                    id,
+                    issue: tr!(
+                        "doctor_invalid_urgency",
+                        "has an invalid urgency of {0}",
+                        task.urgency
+                    ),
+                    fix: tr!(
+                        "doctor_fix_urgency",
+                        "tasks edit {0} -u {1}",
+                        id,
+                        MAXIMUM_URGENCY / 2.0
+                    ),
+                });
+            }
+            if let (Some(start_time), Some(due_time)) = (task.start_time, task.due_time) {
+                if due_time < start_time && task.status != Status::Done {
+                    problems.push(Problem {
+                        id,
+                        issue: i18n::text("doctor_due_before_start", "is due before it started"),
+                        fix: tr!("doctor_fix_due", "tasks edit {0} -D <new due date>", id),
+                    });
+                }
+            }
+            if let Some(watched) = &task.watch_file {
+                if !watched.path.exists() {
+                    problems.push(Problem {
+                        id,
+                        issue: tr!(
+                            "doctor_watch_missing",
+                            "watches {0} which doesn't exist",
+                            watched.path.display()
+                        ),
+                        fix: tr!("doctor_fix_watch", "tasks edit {0} --unwatch", id),
+                    });
+                }
+            }
+        }
+        problems
+    }
+
+    // Where data lives, what is in it and anything that looks wrong
+    pub fn doctor(&self, data_path: &Path) {
+        describe_path(&i18n::text("doctor_data_file", "Data file"), data_path);
+        match config_path("config.json") {
+            Some(path) => describe_path(&i18n::text("doctor_config_file", "Config file"), &path),
+            None => println!(
+                "{}",
+                i18n::text(
+                    "doctor_config_dir_unknown",
+                    "Config file: unable to determine config directory"
+                )
+            ),
+        }
+        // The data was already loaded, reading it again shows errors that loading hides
+        if let Ok(file) = File::open(data_path) {
+            let parsed: Result<TaskManager, _> = serde_json::from_reader(BufReader::new(file));
+            match parsed {
+                Ok(on_disk) => println!(
+                    "{}",
+                    tr!(
+                        "doctor_schema_version",
+                        "Schema version: {0} (current {1})",
+                        on_disk.version,
+                        SCHEMA_VERSION
+                    )
+                ),
+                Err(err) => {
+                    println!(
+                        "{}",
+                        tr!(
+                            "doctor_unreadable_data",
+                            "Data file can't be read: {0}",
+                            err
+                        )
+                    );
+                    println!(
+                        "{}",
+                        i18n::text(
+                            "doctor_fix_repair",
+                            "  fix: tasks repair, which keeps the broken file"
+                        )
+                    );
+                    return;
+                }
+            }
+        }
+
+        let count = |status: Status| {
+            self.tasks
+                .iter()
+                .filter(|task| task.status == status)
+                .count()
+        };
+        println!(
+            "{}",
+            tr!(
+                "doctor_task_counts",
+                "Tasks: {0} ({1} inactive, {2} active, {3} done), {4} in trash, {5} archived",
+                self.tasks.len(),
+                count(Status::Inactive),
+                count(Status::Active),
+                count(Status::Done),
+                self.trash.len(),
+                self.archive.len()
+            )
+        );
+
+        let problems = self.problems();
+        if problems.is_empty() {
+            println!("{}", i18n::text("no_problems_found", "No problems found"));
+        }
+        for problem in problems {
+            println!(" -{}- {}", problem.id, problem.issue);
+            println!("{}", tr!("doctor_fix", "  fix: {0}", problem.fix));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TaskManager;
+
+    #[test]
+    fn finds_due_before_start() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("backwards".to_string());
+        let due = debug_manager.tasks[0].start_time.unwrap() - chrono::Duration::days(1);
+        debug_manager.set_due_date(0, due);
+        let problems = debug_manager.problems();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].issue, "is due before it started");
+    }
+}
@@ -0,0 +1,138 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::i18n::{self, tr};
+
+// Changed on nearly every command and only noise next to the fields that changed
+const IGNORED_FIELDS: [&str; 2] = ["history", "modified_at"];
+
+fn tasks(data: &Value) -> Vec<&Value> {
+    data["tasks"]
+        .as_array()
+        .map_or(Vec::new(), |tasks| tasks.iter().collect())
+}
+
+fn find<'a>(tasks: &[&'a Value], uid: &Value) -> Option<&'a Value> {
+    tasks.iter().find(|task| &task["uid"] == uid).copied()
+}
+
+fn label(task: &Value) -> String {
+    format!(
+        "#{} {}",
+        task["uid"],
+        task["title"].as_str().unwrap_or_default()
+    )
+}
+
+// Values as short as a line allows, lists by how many entries they have
+fn show(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "none".to_string(),
+        Some(Value::Array(entries)) => tr!("dry_run_entries", "{0} entries", entries.len()),
+        Some(Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn changed_fields(before: &Map<String, Value>, after: &Map<String, Value>) -> Vec<String> {
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut lines = Vec::new();
+    for name in names {
+        if IGNORED_FIELDS.contains(&name.as_str()) || before.get(name) == after.get(name) {
+            continue;
+        }
+        lines.push(format!("-   {}: {}", name, show(before.get(name))));
+        lines.push(format!("+   {}: {}", name, show(after.get(name))));
+    }
+    lines
+}
+
+// What a command changed, as added (+), removed (-) and changed (~) tasks with the fields that
+// differ, followed by anything else in the data that changed
+pub fn changes(before: &Value, after: &Value) -> Vec<String> {
+    let (old_tasks, new_tasks) = (tasks(before), tasks(after));
+    let empty = Map::new();
+    let mut lines = Vec::new();
+    for task in new_tasks.iter() {
+        match find(&old_tasks, &task["uid"]) {
+            None => lines.push(format!("+ {}", label(task))),
+            Some(old) if old != *task => {
+                let fields = changed_fields(
+                    old.as_object().unwrap_or(&empty),
+                    task.as_object().unwrap_or(&empty),
+                );
+                if !fields.is_empty() {
+                    lines.push(format!("~ {}", label(task)));
+                    lines.extend(fields);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    for task in old_tasks.iter() {
+        if find(&new_tasks, &task["uid"]).is_none() {
+            lines.push(format!("- {}", label(task)));
+        }
+    }
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    for name in names.into_iter().filter(|name| *name != "tasks") {
+        if before.get(name) != after.get(name) {
+            lines.push(format!(
+                "~ {}: {} -> {}",
+                name,
+                show(before.get(name)),
+                show(after.get(name))
+            ));
+        }
+    }
+    lines
+}
+
+pub fn print_changes(before: &Value, after: &Value) {
+    let changes = changes(before, after);
+    if changes.is_empty() {
+        println!(
+            "{}",
+            i18n::text("dry_run_nothing_would", "Dry run, nothing would change")
+        );
+        return;
+    }
+    for line in changes {
+        println!("{}", line);
+    }
+    println!(
+        "{}",
+        i18n::text("dry_run_nothing_saved", "Dry run, nothing was saved")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::changes;
+    use crate::{Status, TaskManager};
+
+    #[test]
+    fn changes_show_tasks_and_fields() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Pay rent".to_string());
+        task_manager.add_task("Old".to_string());
+        let before = serde_json::to_value(&task_manager).unwrap();
+        task_manager.set_task_status(0, Status::Done);
+        task_manager.tasks.remove(1);
+        task_manager.add_task("New".to_string());
+        let after = serde_json::to_value(&task_manager).unwrap();
+        assert_eq!(
+            changes(&before, &after),
+            vec![
+                "~ #1 Pay rent",
+                "-   status: Inactive",
+                "+   status: Done",
+                "+ #3 New",
+                "- #2 Old",
+                "~ next_uid: 3 -> 4",
+            ]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{Status, TaskManager};
+
+// Titles at least this similar once normalized are taken for the same task
+const DUPLICATE_SIMILARITY: f64 = 0.9;
+
+// Lowercase words without punctuation, so "Renew passport!" and "renew  passport" are equal
+fn normalize(title: &str) -> Vec<String> {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn ratio(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+// 1 for the same words, less for each typo, the words in any order
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (mut a, mut b) = (normalize(a), normalize(b));
+    let in_order = ratio(&a.join(" "), &b.join(" "));
+    a.sort();
+    b.sort();
+    in_order.max(ratio(&a.join(" "), &b.join(" ")))
+}
+
+// What to do about a new task that looks like one already open
+#[derive(Debug, PartialEq)]
+pub enum Duplicate {
+    Add,
+    Note,
+    Cancel,
+}
+
+impl Duplicate {
+    pub fn parse(answer: Option<&str>) -> Self {
+        match answer.map(str::to_lowercase).as_deref() {
+            Some("a" | "add") => Duplicate::Add,
+            Some("n" | "note") => Duplicate::Note,
+            _ => Duplicate::Cancel,
+        }
+    }
+}
+
+impl TaskManager {
+    // The open task most like the title, if any is close enough to be the same
+    pub fn find_duplicate(&self, title: &str) -> Option<usize> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.status != Status::Done)
+            .map(|(id, task)| (id, similarity(title, &task.title)))
+            .filter(|(_, similarity)| *similarity >= DUPLICATE_SIMILARITY)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+    }
+
+    // Adds the new title as a line at the end of the existing task's description
+    pub fn note_duplicate(&mut self, id: usize, title: &str) {
+        let description = &self.tasks[id].description;
+        let description = if description.is_empty() {
+            title.to_string()
+        } else {
+            format!("{}\n{}", description, title)
+        };
+        self.set_task_description(id, description);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{similarity, Duplicate};
+    use crate::{Status, TaskManager};
+
+    #[test]
+    fn similar_open_tasks_are_duplicates() {
+        assert_eq!(similarity("Renew passport!", "renew  passport"), 1.0);
+        assert_eq!(similarity("passport renew", "Renew passport"), 1.0);
+        assert!(similarity("Renew pasport", "Renew passport") > 0.9);
+        assert!(similarity("Call mom", "Call tom") < 0.9);
+
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Book dentist".to_string());
+        task_manager.add_task("Renew passport".to_string());
+        assert_eq!(task_manager.find_duplicate("renew the passport"), None);
+        assert_eq!(task_manager.find_duplicate("Renew pasport"), Some(1));
+        task_manager.set_task_status(1, Status::Done);
+        assert_eq!(task_manager.find_duplicate("Renew pasport"), None);
+
+        task_manager.note_duplicate(0, "Book dentist for Friday");
+        assert_eq!(task_manager.tasks[0].description, "Book dentist for Friday");
+        assert_eq!(Duplicate::parse(Some("N")), Duplicate::Note);
+        assert_eq!(Duplicate::parse(None), Duplicate::Cancel);
+    }
+}
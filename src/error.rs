@@ -0,0 +1,351 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fmt;
+
+use crate::i18n;
+use crate::migrate::SCHEMA_VERSION;
+use crate::{MAXIMUM_URGENCY, MINIMUM_URGENCY};
+
+// Errors reported to the user. Variants and keys are stable, so other front ends can match on
+// them or look up their own text by key instead of parsing the english messages
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskError {
+    InvalidId(usize),
+    InvalidUrgency(f32),
+    InvalidProgress(u8),
+    InvalidWeeks(u32),
+    InvalidDate {
+        input: String,
+        expected: String,
+    },
+    InvalidDay(String),
+    InvalidEstimate(String),
+    InvalidReminder(String),
+    InvalidGoal(String),
+    InvalidDuration(String),
+    InvalidRecurrence(String),
+    InvalidTag(String),
+    UnknownFilterTerm(String),
+    InvalidFilterValue {
+        field: &'static str,
+        value: String,
+    },
+    UnknownSort(String),
+    UnknownGroup(String),
+    UnknownField(String),
+    InvalidCustomValue {
+        field: String,
+        value: String,
+        expected: String,
+    },
+    InvalidFormat(String),
+    UnknownTemplate(String),
+    UnknownReport(String),
+    EmptyFind,
+    NewerDataVersion(u32),
+    UnreadableData {
+        path: String,
+        reason: String,
+    },
+    InvalidRequest(String),
+    InvalidLink {
+        index: usize,
+        count: usize,
+    },
+    InvalidAttachment {
+        index: usize,
+        count: usize,
+    },
+    InvalidChecklistItem {
+        index: usize,
+        count: usize,
+    },
+    OutOfOfficeEndsBeforeStart,
+    MissingSelection,
+    UnknownSnapshot(String),
+    InvalidSnapshotLabel(String),
+    DryRunUnsupported,
+    InvalidRule {
+        name: String,
+        reason: String,
+    },
+    UnknownCommand(String),
+    UnknownLocale(String),
+    CompletedInFuture(String),
+    EventLogOutOfDate,
+    DependencyCycle {
+        id: usize,
+        dependency: usize,
+    },
+}
+
+impl TaskError {
+    pub fn key(&self) -> &'static str {
+        match self {
+            TaskError::InvalidId(_) => "invalid_id",
+            TaskError::InvalidUrgency(_) => "invalid_urgency",
+            TaskError::InvalidProgress(_) => "invalid_progress",
+            TaskError::InvalidWeeks(_) => "invalid_weeks",
+            TaskError::InvalidDate { .. } => "invalid_date",
+            TaskError::InvalidDay(_) => "invalid_day",
+            TaskError::InvalidEstimate(_) => "invalid_estimate",
+            TaskError::InvalidReminder(_) => "invalid_reminder",
+            TaskError::InvalidGoal(_) => "invalid_goal",
+            TaskError::InvalidDuration(_) => "invalid_duration",
+            TaskError::InvalidRecurrence(_) => "invalid_recurrence",
+            TaskError::InvalidTag(_) => "invalid_tag",
+            TaskError::UnknownFilterTerm(_) => "unknown_filter_term",
+            TaskError::InvalidFilterValue { .. } => "invalid_filter_value",
+            TaskError::UnknownSort(_) => "unknown_sort",
+            TaskError::UnknownGroup(_) => "unknown_group",
+            TaskError::UnknownField(_) => "unknown_field",
+            TaskError::InvalidCustomValue { .. } => "invalid_custom_value",
+            TaskError::InvalidFormat(_) => "invalid_format",
+            TaskError::UnknownTemplate(_) => "unknown_template",
+            TaskError::UnknownReport(_) => "unknown_report",
+            TaskError::EmptyFind => "empty_find",
+            TaskError::NewerDataVersion(_) => "newer_data_version",
+            TaskError::UnreadableData { .. } => "unreadable_data",
+            TaskError::InvalidRequest(_) => "invalid_request",
+            TaskError::InvalidLink { .. } => "invalid_link",
+            TaskError::InvalidAttachment { .. } => "invalid_attachment",
+            TaskError::InvalidChecklistItem { .. } => "invalid_checklist_item",
+            TaskError::OutOfOfficeEndsBeforeStart => "out_of_office_ends_before_start",
+            TaskError::MissingSelection => "missing_selection",
+            TaskError::UnknownSnapshot(_) => "unknown_snapshot",
+            TaskError::InvalidSnapshotLabel(_) => "invalid_snapshot_label",
+            TaskError::DryRunUnsupported => "dry_run_unsupported",
+            TaskError::InvalidRule { .. } => "invalid_rule",
+            TaskError::UnknownCommand(_) => "unknown_command",
+            TaskError::UnknownLocale(_) => "unknown_locale",
+            TaskError::CompletedInFuture(_) => "completed_in_future",
+            TaskError::EventLogOutOfDate => "event_log_out_of_date",
+            TaskError::DependencyCycle { .. } => "dependency_cycle",
+        }
+    }
+
+    // The values shown in the message, in order, for translations to place as {0}, {1}, ...
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            TaskError::InvalidId(id) => vec![id.to_string()],
+            TaskError::InvalidUrgency(urgency) => vec![
+                urgency.to_string(),
+                MINIMUM_URGENCY.to_string(),
+                MAXIMUM_URGENCY.to_string(),
+            ],
+            TaskError::InvalidProgress(progress) => vec![progress.to_string()],
+            TaskError::InvalidWeeks(weeks) => vec![weeks.to_string()],
+            TaskError::InvalidDate { input, expected } => vec![input.clone(), expected.clone()],
+            TaskError::InvalidFilterValue { field, value } => {
+                vec![field.to_string(), value.clone()]
+            }
+            TaskError::InvalidCustomValue {
+                field,
+                value,
+                expected,
+            } => vec![field.clone(), value.clone(), expected.clone()],
+            TaskError::NewerDataVersion(version) => {
+                vec![version.to_string(), SCHEMA_VERSION.to_string()]
+            }
+            TaskError::UnreadableData { path, reason } => vec![path.clone(), reason.clone()],
+            TaskError::InvalidLink { index, count }
+            | TaskError::InvalidAttachment { index, count }
+            | TaskError::InvalidChecklistItem { index, count } => {
+                vec![index.to_string(), count.to_string()]
+            }
+            TaskError::InvalidRule { name, reason } => vec![name.clone(), reason.clone()],
+            TaskError::UnknownLocale(name) => {
+                vec![name.clone(), i18n::BUILT_IN_LOCALES.join(", ")]
+            }
+            TaskError::DependencyCycle { id, dependency } => {
+                vec![id.to_string(), dependency.to_string()]
+            }
+            TaskError::InvalidDay(value)
+            | TaskError::InvalidEstimate(value)
+            | TaskError::InvalidReminder(value)
+            | TaskError::InvalidGoal(value)
+            | TaskError::InvalidDuration(value)
+            | TaskError::InvalidRecurrence(value)
+            | TaskError::InvalidTag(value)
+            | TaskError::UnknownFilterTerm(value)
+            | TaskError::UnknownSort(value)
+            | TaskError::UnknownGroup(value)
+            | TaskError::UnknownField(value)
+            | TaskError::InvalidFormat(value)
+            | TaskError::UnknownTemplate(value)
+            | TaskError::UnknownReport(value)
+            | TaskError::InvalidRequest(value)
+            | TaskError::UnknownSnapshot(value)
+            | TaskError::InvalidSnapshotLabel(value)
+            | TaskError::UnknownCommand(value)
+            | TaskError::CompletedInFuture(value) => vec![value.clone()],
+            TaskError::EmptyFind
+            | TaskError::OutOfOfficeEndsBeforeStart
+            | TaskError::MissingSelection
+            | TaskError::DryRunUnsupported
+            | TaskError::EventLogOutOfDate => Vec::new(),
+        }
+    }
+
+    // e.g. {"error": "invalid_id", "message": "Invalid ID: 7"}
+    pub fn to_json(&self) -> Value {
+        json!({ "error": self.key(), "message": self.to_string() })
+    }
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(message) = i18n::translate(self.key(), &self.args()) {
+            return write!(f, "{}", message);
+        }
+        match self {
+            TaskError::InvalidId(id) => write!(f, "Invalid ID: {}", id),
+            TaskError::InvalidUrgency(urgency) => write!(
+                f,
+                "Urgency must be between {MINIMUM_URGENCY} and {MAXIMUM_URGENCY}, you inputted {}",
+                urgency
+            ),
+            TaskError::InvalidProgress(progress) => write!(
+                f,
+                "Progress must be between 0 and 100 percent, you inputted {}",
+                progress
+            ),
+            TaskError::InvalidWeeks(weeks) => write!(
+                f,
+                "Weeks must be at least 1, you inputted {}",
+                weeks
+            ),
+            TaskError::InvalidDate { input, expected } => write!(
+                f,
+                "Invalid date, submitted: {}, expected format {}",
+                input, expected
+            ),
+            TaskError::InvalidDay(input) => write!(
+                f,
+                "Invalid day, submitted: {}, expected a weekday, today, tomorrow, yesterday or a date",
+                input
+            ),
+            TaskError::InvalidEstimate(input) => write!(
+                f,
+                "Invalid estimate, submitted: {}, expected format like 2h or 1h30m",
+                input
+            ),
+            TaskError::InvalidReminder(input) => write!(
+                f,
+                "Invalid reminder, submitted: {}, expected a time before the due date like 1d or 2h",
+                input
+            ),
+            TaskError::InvalidGoal(input) => write!(
+                f,
+                "Invalid goal, submitted: {}, expected a time like 6h or 1h30m",
+                input
+            ),
+            TaskError::InvalidDuration(input) => write!(
+                f,
+                "Invalid duration, submitted: {}, expected a time like 90d or 12w",
+                input
+            ),
+            TaskError::InvalidRecurrence(input) => write!(
+                f,
+                "Invalid recurrence, submitted: {}, expected a rule like FREQ=MONTHLY;BYDAY=1MO or e.g. \"first monday of month\"",
+                input
+            ),
+            TaskError::InvalidTag(input) => write!(
+                f,
+                "Invalid tag, submitted: {}, expected one word without + - , or :, other than a virtual tag like OVERDUE",
+                input
+            ),
+            TaskError::UnknownFilterTerm(term) => write!(f, "Unknown filter term: {}", term),
+            TaskError::InvalidFilterValue { field, value } => {
+                write!(f, "Invalid {} in filter: {}", field, value)
+            }
+            TaskError::UnknownSort(name) => write!(f, "Unknown sort: {}", name),
+            TaskError::UnknownGroup(name) => write!(
+                f,
+                "Unknown group: {}, expected status, due-week, project, tag or a custom field",
+                name
+            ),
+            TaskError::UnknownField(name) => write!(f, "Unknown field: {}", name),
+            TaskError::InvalidCustomValue {
+                field,
+                value,
+                expected,
+            } => write!(f, "Invalid {}: {}, expected {}", field, value, expected),
+            TaskError::InvalidFormat(format) => write!(f, "Invalid list format: {}", format),
+            TaskError::UnknownTemplate(name) => write!(f, "No template named {}", name),
+            TaskError::UnknownReport(name) => write!(f, "No report named {}", name),
+            TaskError::EmptyFind => write!(f, "Text to find can't be empty"),
+            TaskError::NewerDataVersion(version) => write!(
+                f,
+                "Task data is version {}, this version of tasks only reads up to {}, please upgrade",
+                version, SCHEMA_VERSION
+            ),
+            TaskError::UnreadableData { path, reason } => write!(
+                f,
+                "Task data in {} can't be read ({}), nothing was changed. Run repair to salvage what can be read",
+                path, reason
+            ),
+            TaskError::InvalidRequest(message) => write!(f, "Invalid request: {}", message),
+            TaskError::InvalidLink { index, count } => {
+                write!(f, "No link {}, the task has {} links", index, count)
+            }
+            TaskError::InvalidAttachment { index, count } => {
+                write!(f, "No attachment {}, the task has {} attachments", index, count)
+            }
+            TaskError::InvalidChecklistItem { index, count } => write!(
+                f,
+                "No checklist item {}, the task has {} items",
+                index, count
+            ),
+            TaskError::OutOfOfficeEndsBeforeStart => {
+                write!(f, "Out of office must end on or after it starts")
+            }
+            TaskError::MissingSelection => write!(
+                f,
+                "Give a task ID, or pick tasks with --filter, --tag or --due-before before the command"
+            ),
+            TaskError::UnknownSnapshot(label) => write!(f, "No snapshot named {}", label),
+            TaskError::InvalidSnapshotLabel(label) => write!(
+                f,
+                "Invalid snapshot label: {}, use letters, digits, - _ and .",
+                label
+            ),
+            TaskError::DryRunUnsupported => write!(
+                f,
+                "--dry-run can't be used with commands that write other files or send anything"
+            ),
+            TaskError::InvalidRule { name, reason } => {
+                write!(f, "Invalid rule {}: {}, leaving it out", name, reason)
+            }
+            TaskError::UnknownCommand(name) => write!(
+                f,
+                "Unknown command {}, and no task-{} plugin was found on PATH",
+                name, name
+            ),
+            TaskError::UnknownLocale(name) => write!(
+                f,
+                "Unknown locale {}, expected {} or a file locales/{}.json in the config directory",
+                name,
+                i18n::BUILT_IN_LOCALES.join(", "),
+                name
+            ),
+            TaskError::CompletedInFuture(input) => write!(
+                f,
+                "A logged task is already done, {} is still to come",
+                input
+            ),
+            TaskError::EventLogOutOfDate => write!(
+                f,
+                "The tasks were changed without going through the event log, so there is nothing \
+                 to undo. The log starts over from the tasks as they are now"
+            ),
+            TaskError::DependencyCycle { id, dependency } => write!(
+                f,
+                "Task {} can't wait on task {}, which already waits on it",
+                id, dependency
+            ),
+        }
+    }
+}
+
+impl Error for TaskError {}
@@ -0,0 +1,1133 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fs::{metadata, read_to_string, rename, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::archive::parse_until;
+use crate::checklist::ChecklistItem;
+use crate::config::Config;
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::scheduled::parse_scheduled;
+use crate::watch::WatchedFile;
+use crate::{Status, Task, TaskManager};
+
+// Events logged after the last snapshot before another is taken, so a replay never has to go
+// through more than this many
+const SNAPSHOT_EVERY: usize = 200;
+
+// Kept in the data file for the log, not part of the state it logs
+const LOG_FIELDS: [&str; 5] = [
+    "tasks",
+    "version",
+    "events_since_snapshot",
+    "logged_events",
+    "event_log_length",
+];
+
+// One change to the tasks. Every setter makes its change by applying one of these, only changes
+// made to whole tasks, such as merging in another copy or editing a task in $EDITOR, log the
+// whole task as TaskUpdated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Event {
+    // Every task, when the log is started and then every SNAPSHOT_EVERY events
+    Snapshot {
+        tasks: Vec<Task>,
+    },
+    TaskAdded {
+        task: Task,
+    },
+    TitleChanged {
+        uid: u64,
+        title: String,
+    },
+    StatusChanged {
+        uid: u64,
+        status: Status,
+        active_since: Option<DateTime<Utc>>,
+    },
+    DueChanged {
+        uid: u64,
+        due: Option<DateTime<Utc>>,
+    },
+    UrgencyChanged {
+        uid: u64,
+        urgency: f32,
+    },
+    // Any other field, e.g. {"type": "FieldChanged", "uid": 4, "change": {"field": "estimate",
+    // "value": 3600}}
+    FieldChanged {
+        uid: u64,
+        change: FieldValue,
+    },
+    TaskUpdated {
+        task: Task,
+    },
+    TaskRemoved {
+        uid: u64,
+    },
+    // Anything saved besides the tasks, e.g. the trash or the goals, set whole
+    StateChanged {
+        field: String,
+        value: Value,
+    },
+}
+
+// A field of a task with the value it is set to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "field", content = "value", rename_all = "snake_case")]
+pub enum FieldValue {
+    Description(String),
+    StartTime(Option<DateTime<Utc>>),
+    // Durations in seconds as in the data file, serde can't read variants through with
+    Estimate(Option<i64>),
+    SoftDue(bool),
+    UrgencyPinned(bool),
+    Assignee(Option<String>),
+    Tags(Vec<String>),
+    Project(Option<String>),
+    Links(Vec<String>),
+    Attachments(Vec<PathBuf>),
+    Custom(BTreeMap<String, String>),
+    DependsOn(Vec<u64>),
+    Recur(Option<String>),
+    Scheduled(Option<DateTime<Utc>>),
+    Until(Option<DateTime<Utc>>),
+    Progress(Option<u8>),
+    Checklist(Vec<ChecklistItem>),
+    Notes(Option<PathBuf>),
+    Reminders(Vec<i64>),
+    Reminded(Option<DateTime<Utc>>),
+    PlannedOn(Option<NaiveDate>),
+    Rollovers(u32),
+    WatchFile(Option<WatchedFile>),
+}
+
+// False when the field already had the value
+fn replace<T: Clone + PartialEq>(field: &mut T, value: &T) -> bool {
+    let changed = field != value;
+    *field = value.clone();
+    changed
+}
+
+impl FieldValue {
+    // Every field of the task that FieldChanged sets, in the order of the variants
+    fn all(task: &Task) -> Vec<FieldValue> {
+        vec![
+            FieldValue::Description(task.description.clone()),
+            FieldValue::StartTime(task.start_time),
+            FieldValue::Estimate(task.estimate.map(|estimate| estimate.num_seconds())),
+            FieldValue::SoftDue(task.soft_due),
+            FieldValue::UrgencyPinned(task.urgency_pinned),
+            FieldValue::Assignee(task.assignee.clone()),
+            FieldValue::Tags(task.tags.clone()),
+            FieldValue::Project(task.project.clone()),
+            FieldValue::Links(task.links.clone()),
+            FieldValue::Attachments(task.attachments.clone()),
+            FieldValue::Custom(task.custom.clone()),
+            FieldValue::DependsOn(task.depends_on.clone()),
+            FieldValue::Recur(task.recur.clone()),
+            FieldValue::Scheduled(task.scheduled),
+            FieldValue::Until(task.until),
+            FieldValue::Progress(task.progress),
+            FieldValue::Checklist(task.checklist.clone()),
+            FieldValue::Notes(task.notes.clone()),
+            FieldValue::Reminders(task.reminders.iter().map(Duration::num_seconds).collect()),
+            FieldValue::Reminded(task.reminded),
+            FieldValue::PlannedOn(task.planned_on),
+            FieldValue::Rollovers(task.rollovers),
+            FieldValue::WatchFile(task.watch_file.clone()),
+        ]
+    }
+
+    fn set(&self, task: &mut Task) -> bool {
+        match self {
+            FieldValue::Description(value) => replace(&mut task.description, value),
+            FieldValue::StartTime(value) => replace(&mut task.start_time, value),
+            FieldValue::Estimate(seconds) => {
+                replace(&mut task.estimate, &seconds.map(Duration::seconds))
+            }
+            FieldValue::SoftDue(value) => replace(&mut task.soft_due, value),
+            FieldValue::UrgencyPinned(value) => replace(&mut task.urgency_pinned, value),
+            FieldValue::Assignee(value) => replace(&mut task.assignee, value),
+            FieldValue::Tags(value) => replace(&mut task.tags, value),
+            FieldValue::Project(value) => replace(&mut task.project, value),
+            FieldValue::Links(value) => replace(&mut task.links, value),
+            FieldValue::Attachments(value) => replace(&mut task.attachments, value),
+            FieldValue::Custom(value) => replace(&mut task.custom, value),
+            FieldValue::DependsOn(value) => replace(&mut task.depends_on, value),
+            FieldValue::Recur(value) => replace(&mut task.recur, value),
+            FieldValue::Scheduled(value) => replace(&mut task.scheduled, value),
+            FieldValue::Until(value) => replace(&mut task.until, value),
+            FieldValue::Progress(value) => replace(&mut task.progress, value),
+            FieldValue::Checklist(value) => replace(&mut task.checklist, value),
+            FieldValue::Notes(value) => replace(&mut task.notes, value),
+            FieldValue::Reminders(seconds) => replace(
+                &mut task.reminders,
+                &seconds.iter().copied().map(Duration::seconds).collect(),
+            ),
+            FieldValue::Reminded(value) => replace(&mut task.reminded, value),
+            FieldValue::PlannedOn(value) => replace(&mut task.planned_on, value),
+            FieldValue::Rollovers(value) => replace(&mut task.rollovers, value),
+            FieldValue::WatchFile(value) => replace(&mut task.watch_file, value),
+        }
+    }
+
+    // As in the history, e.g. "soft due"
+    fn name(&self) -> &'static str {
+        match self {
+            FieldValue::Description(_) => "description",
+            FieldValue::StartTime(_) => "start",
+            FieldValue::Estimate(_) => "estimate",
+            FieldValue::SoftDue(_) => "soft due",
+            FieldValue::UrgencyPinned(_) => "urgency pinned",
+            FieldValue::Assignee(_) => "assignee",
+            FieldValue::Tags(_) => "tags",
+            FieldValue::Project(_) => "project",
+            FieldValue::Links(_) => "links",
+            FieldValue::Attachments(_) => "attachment",
+            FieldValue::Custom(_) => "custom",
+            FieldValue::DependsOn(_) => "depends on",
+            FieldValue::Recur(_) => "recur",
+            FieldValue::Scheduled(_) => "scheduled",
+            FieldValue::Until(_) => "until",
+            FieldValue::Progress(_) => "progress",
+            FieldValue::Checklist(_) => "checklist",
+            FieldValue::Notes(_) => "notes",
+            FieldValue::Reminders(_) => "reminders",
+            FieldValue::Reminded(_) => "reminded",
+            FieldValue::PlannedOn(_) => "planned",
+            FieldValue::Rollovers(_) => "rollovers",
+            FieldValue::WatchFile(_) => "watch file",
+        }
+    }
+}
+
+// A line of events.jsonl, e.g. {"seq": 12, "time": "...", "type": "DueChanged", "uid": 4, "due":
+// null}. seq counts up from the first event logged, lines from before it was added have none
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    #[serde(default)]
+    pub seq: u64,
+    pub time: DateTime<Utc>,
+    // Made on every run rather than by the command, undo leaves these alone
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub housekeeping: bool,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+pub fn events_path(data_file: &Path) -> PathBuf {
+    data_file.with_file_name("events.jsonl")
+}
+
+// A day replays up to its end, or a day and time like "monday 14:00" up to that minute
+pub fn parse_replay_time(input: &str, input_format: &str) -> Result<DateTime<Utc>, TaskError> {
+    if input.contains(':') {
+        parse_scheduled(input, input_format)
+    } else {
+        parse_until(input, input_format)
+    }
+}
+
+// What the typed events leave out of a task, equal for two tasks when those events are the whole
+// difference between them. History and modification times follow from the events themselves.
+// The fields FieldChanged sets are taken from blank
+fn untyped(task: &Task, blank: &Task) -> Task {
+    let mut task = task.clone();
+    for value in FieldValue::all(blank) {
+        value.set(&mut task);
+    }
+    task.title = String::new();
+    task.status = Status::Inactive;
+    task.active_since = None;
+    task.due_time = None;
+    task.urgency = 0.0;
+    task.history = Vec::new();
+    task.modified_at = None;
+    task.effective_urgency = 0.0;
+    task.effective_progress = None;
+    task.blocked = false;
+    task
+}
+
+fn task_events(before: &Task, after: &Task) -> Vec<Event> {
+    let blank = Task::new(String::new());
+    if untyped(before, &blank) != untyped(after, &blank) {
+        return vec![Event::TaskUpdated {
+            task: after.clone(),
+        }];
+    }
+    let uid = after.uid;
+    let mut events = Vec::new();
+    if before.title != after.title {
+        events.push(Event::TitleChanged {
+            uid,
+            title: after.title.clone(),
+        });
+    }
+    if before.status != after.status || before.active_since != after.active_since {
+        events.push(Event::StatusChanged {
+            uid,
+            status: after.status,
+            active_since: after.active_since,
+        });
+    }
+    if before.due_time != after.due_time {
+        events.push(Event::DueChanged {
+            uid,
+            due: after.due_time,
+        });
+    }
+    if before.urgency != after.urgency {
+        events.push(Event::UrgencyChanged {
+            uid,
+            urgency: after.urgency,
+        });
+    }
+    let old_values = FieldValue::all(before);
+    for (old, new) in old_values.iter().zip(FieldValue::all(after)) {
+        if *old != new {
+            events.push(Event::FieldChanged { uid, change: new });
+        }
+    }
+    events
+}
+
+// The events turning the tasks before into the tasks after, matched by uid
+pub fn changes(before: &[Task], after: &[Task]) -> Vec<Event> {
+    let old_tasks: BTreeMap<u64, &Task> = before.iter().map(|task| (task.uid, task)).collect();
+    let mut events = Vec::new();
+    for task in after {
+        match old_tasks.get(&task.uid) {
+            // Most tasks didn't change, comparing them whole is much cheaper than untyped's copies
+            Some(old) if *old == task => {}
+            Some(old) => events.extend(task_events(old, task)),
+            None => events.push(Event::TaskAdded { task: task.clone() }),
+        }
+    }
+    let new_uids: BTreeSet<u64> = after.iter().map(|task| task.uid).collect();
+    for old in before {
+        if !new_uids.contains(&old.uid) {
+            events.push(Event::TaskRemoved { uid: old.uid });
+        }
+    }
+    events
+}
+
+fn find(tasks: &mut [Task], uid: u64) -> Option<&mut Task> {
+    tasks.iter_mut().find(|task| task.uid == uid)
+}
+
+impl Event {
+    // Changes set modified_at to the time they are applied but add nothing to the history, the
+    // setters record that. False when the tasks were already that way
+    fn apply(&self, tasks: &mut Vec<Task>, time: DateTime<Utc>) -> bool {
+        let mut change = |uid: &u64, change: &mut dyn FnMut(&mut Task) -> bool| {
+            find(tasks, *uid).is_some_and(|task| {
+                let changed = change(task);
+                if changed {
+                    task.modified_at = Some(time);
+                }
+                changed
+            })
+        };
+        match self {
+            Event::TitleChanged { uid, title } => change(uid, &mut |task| {
+                let changed = task.title != *title;
+                task.title = title.clone();
+                changed
+            }),
+            Event::StatusChanged {
+                uid,
+                status,
+                active_since,
+            } => change(uid, &mut |task| {
+                let changed = task.status != *status || task.active_since != *active_since;
+                task.status = *status;
+                task.active_since = *active_since;
+                changed
+            }),
+            Event::DueChanged { uid, due } => change(uid, &mut |task| {
+                let changed = task.due_time != *due;
+                task.due_time = *due;
+                changed
+            }),
+            Event::UrgencyChanged { uid, urgency } => change(uid, &mut |task| {
+                let changed = task.urgency != *urgency;
+                task.urgency = *urgency;
+                changed
+            }),
+            Event::FieldChanged { uid, change: value } => change(uid, &mut |task| value.set(task)),
+            Event::Snapshot { tasks: snapshot } => {
+                *tasks = snapshot.clone();
+                true
+            }
+            Event::TaskAdded { task } | Event::TaskUpdated { task } => {
+                match find(tasks, task.uid) {
+                    Some(existing) => *existing = task.clone(),
+                    None => tasks.push(task.clone()),
+                }
+                true
+            }
+            Event::TaskRemoved { uid } => {
+                let count = tasks.len();
+                tasks.retain(|task| task.uid != *uid);
+                tasks.len() != count
+            }
+            Event::StateChanged { .. } => false,
+        }
+    }
+
+    fn uid(&self) -> Option<u64> {
+        match self {
+            Event::Snapshot { .. } | Event::StateChanged { .. } => None,
+            Event::TaskAdded { task } | Event::TaskUpdated { task } => Some(task.uid),
+            Event::TitleChanged { uid, .. }
+            | Event::StatusChanged { uid, .. }
+            | Event::DueChanged { uid, .. }
+            | Event::UrgencyChanged { uid, .. }
+            | Event::FieldChanged { uid, .. }
+            | Event::TaskRemoved { uid } => Some(*uid),
+        }
+    }
+
+    // e.g. "Pay rent: due 20/10/2026", named by the title the task had before the event
+    fn describe(&self, tasks: &[Task], config: &Config) -> String {
+        let title = |uid: &u64| {
+            tasks
+                .iter()
+                .find(|task| task.uid == *uid)
+                .map_or_else(|| format!("-{}-", uid), |task| task.title.clone())
+        };
+        match self {
+            Event::Snapshot { tasks } => {
+                tr!("event_snapshot", "snapshot of {0} tasks", tasks.len())
+            }
+            Event::TaskAdded { task } => tr!("event_added", "added {0}", task.title),
+            Event::TaskUpdated { task } => tr!("event_changed", "changed {0}", task.title),
+            Event::TaskRemoved { uid } => tr!("event_removed", "removed {0}", title(uid)),
+            Event::TitleChanged { uid, title: new } => {
+                tr!("event_renamed", "renamed {0} to {1}", title(uid), new)
+            }
+            Event::StatusChanged { uid, status, .. } => {
+                format!("{}: {}", title(uid), status.as_str())
+            }
+            Event::DueChanged { uid, due } => match due {
+                Some(due) => tr!(
+                    "event_due",
+                    "{0}: due {1}",
+                    title(uid),
+                    config.format_datetime(*due)
+                ),
+                None => tr!("event_no_due", "{0}: no due date", title(uid)),
+            },
+            Event::UrgencyChanged { uid, urgency } => {
+                tr!("event_urgency", "{0}: urgency {1}", title(uid), urgency)
+            }
+            Event::FieldChanged { uid, change } => {
+                tr!("event_field", "{0}: {1} changed", title(uid), change.name())
+            }
+            Event::StateChanged { field, .. } => tr!("event_state", "{0} changed", field),
+        }
+    }
+}
+
+fn read_events(data_file: &Path) -> Result<Option<Vec<LoggedEvent>>, Box<dyn Error>> {
+    let text = match read_to_string(events_path(data_file)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let mut events = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = match serde_json::from_str(line) {
+            Ok(event) => event,
+            // Cut short by a crash
+            Err(err) if err.is_eof() => continue,
+            Err(err) => return Err(format!("events line {}: {}", number + 1, err).into()),
+        };
+        events.push(event);
+    }
+    Ok(Some(events))
+}
+
+impl TaskManager {
+    // Every change with an event of its own goes through here, kept to be logged on save
+    pub fn apply(&mut self, event: Event) {
+        if event.apply(&mut self.tasks, Utc::now()) {
+            self.pending_events.push(event);
+        }
+    }
+
+    // The setters' way of changing a field of the task with that ID, the ID must be valid
+    pub fn set_field(&mut self, id: usize, value: FieldValue) {
+        let uid = self.tasks[id].uid;
+        self.apply(Event::FieldChanged { uid, change: value });
+    }
+
+    // Turns the task with that ID into task through the typed events between the two, or one
+    // TaskUpdated when they differ in anything those events don't set
+    pub fn update_task(&mut self, id: usize, task: Task) {
+        for event in task_events(&self.tasks[id], &task) {
+            self.apply(event);
+        }
+        // Kept in the data file rather than logged, like the setters' record_change
+        self.tasks[id].history = task.history;
+    }
+
+    // Removes every matching task in one pass, applying TaskRemoved for each is quadratic with tens
+    // of thousands of tasks
+    pub fn remove_tasks_where(&mut self, remove: impl Fn(&Task) -> bool) -> Vec<Task> {
+        let (removed, kept): (Vec<Task>, Vec<Task>) =
+            self.tasks.drain(..).partition(|task| remove(task));
+        self.tasks = kept;
+        self.pending_events.extend(
+            removed
+                .iter()
+                .map(|task| Event::TaskRemoved { uid: task.uid }),
+        );
+        removed
+    }
+
+    // The events applied since the tasks were read or last logged, then whatever else changed
+    fn take_changes(&mut self) -> Vec<Event> {
+        let mut tasks = self.loaded_tasks.take().unwrap_or_default();
+        let now = Utc::now();
+        for event in &self.pending_events {
+            event.apply(&mut tasks, now);
+        }
+        let mut events = std::mem::take(&mut self.pending_events);
+        events.extend(changes(&tasks, &self.tasks));
+        let loaded_state = self
+            .loaded_state
+            .take()
+            .unwrap_or_else(|| TaskManager::new().state());
+        let state = self.state();
+        for (field, value) in &state {
+            if loaded_state.get(field) != Some(value) {
+                events.push(Event::StateChanged {
+                    field: field.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        // Saved again later, only what changes from here on is logged then
+        self.loaded_tasks = Some(self.tasks.clone());
+        self.loaded_state = Some(state);
+        events
+    }
+
+    // Called once the changes made on every run, e.g. archiving done tasks or pulling from a
+    // remote, are done. They are logged apart from the command's own, which undo takes back
+    pub fn end_housekeeping(&mut self) {
+        let events = self.take_changes();
+        self.housekeeping.extend(events);
+    }
+
+    // What save appends to the log, each event with whether it was housekeeping. The log starts
+    // with a snapshot of the tasks as read and takes another once SNAPSHOT_EVERY events followed
+    // the last, counted in the data file rather than the log
+    pub fn unlogged_events(&mut self, data_file: &Path) -> Vec<(Event, bool)> {
+        let mut events = Vec::new();
+        let mut housekeeping = std::mem::take(&mut self.housekeeping);
+        if !events_path(data_file).exists() {
+            // Taken after the housekeeping, the events leading up to it aren't logged then
+            let tasks = self.loaded_tasks.clone().unwrap_or_default();
+            events.push((Event::Snapshot { tasks }, false));
+            housekeeping.clear();
+            self.events_since_snapshot = 0;
+        }
+        let command = self.take_changes();
+        self.events_since_snapshot += housekeeping.len() + command.len();
+        events.extend(housekeeping.into_iter().map(|event| (event, true)));
+        events.extend(command.into_iter().map(|event| (event, false)));
+        if self.events_since_snapshot >= SNAPSHOT_EVERY {
+            events.push((
+                Event::Snapshot {
+                    tasks: self.tasks.clone(),
+                },
+                false,
+            ));
+            self.events_since_snapshot = 0;
+        }
+        events
+    }
+
+    // Everything saved besides the tasks and the log's own bookkeeping, by field
+    pub(crate) fn state(&mut self) -> Map<String, Value> {
+        let tasks = std::mem::take(&mut self.tasks);
+        let state = serde_json::to_value(&*self);
+        self.tasks = tasks;
+        let mut state = match state {
+            Ok(Value::Object(state)) => state,
+            _ => Map::new(),
+        };
+        for field in LOG_FIELDS {
+            state.remove(field);
+        }
+        state
+    }
+
+    fn set_state(&mut self, state: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        let tasks = std::mem::take(&mut self.tasks);
+        let mut data = serde_json::to_value(&*self)?;
+        for (field, value) in state {
+            data[field.as_str()] = value;
+        }
+        let changed: TaskManager = serde_json::from_value(data)?;
+        *self = TaskManager {
+            tasks,
+            unsaved_events: self.unsaved_events,
+            ..changed
+        };
+        Ok(())
+    }
+
+    // Appends the changes to the log, which is written ahead of the data file. With a snapshot
+    // among them the log is compacted to the snapshot before it and the data file written
+    // first, as the events left out are only in the file then. True when the file was written
+    fn log_changes(&mut self, data_file: &PathBuf) -> Result<bool, Box<dyn Error>> {
+        let events = self.unlogged_events(data_file);
+        let path = events_path(data_file);
+        let length = match metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == ErrorKind::NotFound => 0,
+            Err(err) => return Err(err.into()),
+        };
+        let compact = length > 0 && matches!(events.last(), Some((Event::Snapshot { .. }, _)));
+        // Written together, so every event of one save has the same time
+        let time = Utc::now();
+        let mut lines = String::new();
+        for (event, housekeeping) in events {
+            self.logged_events += 1;
+            self.unsaved_events += 1;
+            let logged = LoggedEvent {
+                seq: self.logged_events,
+                time,
+                housekeeping,
+                event,
+            };
+            lines += &serde_json::to_string(&logged)?;
+            lines.push('\n');
+        }
+        if compact {
+            let mut text = from_last_snapshot(&read_to_string(&path)?);
+            text += &lines;
+            self.event_log_length = text.len() as u64;
+            self.write_file(data_file)?;
+            let partial = path.with_extension("jsonl.partial");
+            std::fs::write(&partial, text)?;
+            rename(&partial, &path)?;
+            return Ok(true);
+        }
+        if !lines.is_empty() {
+            let mut log = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(&path)?;
+            // A last line cut short by a crash is ended first, the lines after it stay whole
+            if length > 0 {
+                let mut last = [0];
+                log.seek(SeekFrom::Start(length - 1))?;
+                log.read_exact(&mut last)?;
+                if last[0] != b'\n' {
+                    lines.insert(0, '\n');
+                }
+            }
+            log.write_all(lines.as_bytes())?;
+            log.sync_data()?;
+            self.event_log_length = length + lines.len() as u64;
+        }
+        Ok(false)
+    }
+
+    pub fn save_to_file(&mut self, data_file: &PathBuf) -> Result<(), Box<dyn Error>> {
+        if !self.log_changes(data_file)? {
+            self.write_file(data_file)?;
+        }
+        Ok(())
+    }
+
+    // Only appends to the log, the data file is written once more than compact_after events were
+    // logged since it last was. Loading replays the ones it doesn't have yet
+    pub fn save_journaled(
+        &mut self,
+        data_file: &PathBuf,
+        compact_after: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        // A log started over has nothing from before in it, the file has to have that
+        let started = !events_path(data_file).exists();
+        if self.log_changes(data_file)? {
+            return Ok(());
+        }
+        if self.unsaved_events > compact_after || started || !data_file.exists() {
+            self.write_file(data_file)
+        } else {
+            self.save_status(data_file)
+        }
+    }
+
+    // Applies the events logged after the data file was last written, left out of it by a
+    // journaled save or a crash between the two writes
+    pub fn replay_unsaved(&mut self, data_file: &Path) -> Result<(), Box<dyn Error>> {
+        let path = events_path(data_file);
+        let length = match metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        if length <= self.event_log_length {
+            return Ok(());
+        }
+        // From the end of the last line the file has, read along to check it is one
+        let start = self.event_log_length.saturating_sub(1);
+        let mut log = File::open(&path)?;
+        log.seek(SeekFrom::Start(start))?;
+        let mut text = String::new();
+        log.read_to_string(&mut text)?;
+        // Otherwise the log was compacted since and the file has everything in it
+        if start > 0 && !text.starts_with('\n') {
+            return Ok(());
+        }
+        let mut state = Map::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let logged: LoggedEvent = match serde_json::from_str(line) {
+                Ok(logged) => logged,
+                Err(err) if err.is_eof() => continue,
+                Err(err) => return Err(format!("events.jsonl: {}", err).into()),
+            };
+            // Already in the file, a compacted log can be longer than the one the file was saved with
+            if logged.seq <= self.logged_events {
+                continue;
+            }
+            self.logged_events = logged.seq;
+            self.unsaved_events += 1;
+            match logged.event {
+                Event::Snapshot { tasks } => {
+                    self.tasks = tasks;
+                    self.events_since_snapshot = 0;
+                }
+                Event::StateChanged { field, value } => {
+                    state.insert(field, value);
+                    self.events_since_snapshot += 1;
+                }
+                event => {
+                    event.apply(&mut self.tasks, logged.time);
+                    self.events_since_snapshot += 1;
+                }
+            }
+        }
+        self.event_log_length = start + text.len() as u64;
+        if !state.is_empty() {
+            self.set_state(state)?;
+        }
+        Ok(())
+    }
+
+    // Takes over what previous knew of the log, so tasks replaced whole, as restoring a snapshot
+    // does, are logged as the changes between them
+    pub fn continue_log(&mut self, previous: TaskManager) {
+        self.events_since_snapshot = previous.events_since_snapshot;
+        self.logged_events = previous.logged_events;
+        self.event_log_length = previous.event_log_length;
+        self.unsaved_events = previous.unsaved_events;
+        self.pending_events = previous.pending_events;
+        self.housekeeping = previous.housekeeping;
+        self.loaded_tasks = previous.loaded_tasks;
+        self.loaded_state = previous.loaded_state;
+    }
+}
+
+#[derive(Deserialize)]
+struct EventType {
+    #[serde(rename = "type")]
+    name: String,
+}
+
+// The lines from the last snapshot on, which undo needs to tell the tasks before the last save.
+// A line cut short by a crash is left out
+fn from_last_snapshot(text: &str) -> String {
+    let lines: Vec<(&str, bool)> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<EventType>(line) {
+            Ok(event) => Some((line, event.name == "Snapshot")),
+            Err(err) if err.is_eof() => None,
+            Err(_) => Some((line, false)),
+        })
+        .collect();
+    let start = lines
+        .iter()
+        .rposition(|(_, snapshot)| *snapshot)
+        .unwrap_or(0);
+    let mut kept = String::new();
+    for (line, _) in &lines[start..] {
+        kept += line;
+        kept.push('\n');
+    }
+    kept
+}
+
+fn is_snapshot(logged: &LoggedEvent) -> bool {
+    matches!(logged.event, Event::Snapshot { .. })
+}
+
+fn last_snapshot(events: &[LoggedEvent]) -> usize {
+    events.iter().rposition(is_snapshot).unwrap_or(0)
+}
+
+fn replay_tasks(events: &[LoggedEvent]) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for logged in &events[last_snapshot(events)..] {
+        logged.event.apply(&mut tasks, logged.time);
+    }
+    tasks
+}
+
+// What the log keeps of the tasks. History isn't replayed and urgencies are calculated each run
+fn logged_tasks(tasks: &[Task]) -> BTreeMap<u64, Task> {
+    tasks
+        .iter()
+        .map(|task| {
+            let mut task = task.clone();
+            task.history = Vec::new();
+            task.modified_at = None;
+            task.effective_urgency = 0.0;
+            task.effective_progress = None;
+            task.blocked = false;
+            (task.uid, task)
+        })
+        .collect()
+}
+
+impl TaskManager {
+    // Takes back the changes the last command made and describes them. Only the fields it changed
+    // go back, changes made since by housekeeping stay. The undo is applied and logged like any
+    // other change, so undoing again brings the change back. None when nothing was logged yet
+    pub fn undo_last_change(
+        &mut self,
+        data_file: &Path,
+        config: &Config,
+    ) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        let events = read_events(data_file)?.unwrap_or_default();
+        let by_command =
+            |logged: &LoggedEvent| !logged.housekeeping && logged.event.uid().is_some();
+        let last_saved = match events.iter().rev().find(|logged| by_command(logged)) {
+            Some(logged) => logged.time,
+            None => return Ok(None),
+        };
+        let mut logged_tasks_now = replay_tasks(&events);
+        let now = Utc::now();
+        for event in &self.housekeeping {
+            event.apply(&mut logged_tasks_now, now);
+        }
+        let loaded = self.loaded_tasks.as_deref().unwrap_or_default();
+        if logged_tasks(&logged_tasks_now) != logged_tasks(loaded) {
+            // The save starts the log over from the tasks as they are, so the next change can be
+            // undone again
+            self.events_since_snapshot = SNAPSHOT_EVERY;
+            return Err(Box::new(TaskError::EventLogOutOfDate));
+        }
+        let first = events
+            .iter()
+            .position(|logged| logged.time == last_saved && by_command(logged))
+            .unwrap_or(0);
+        let before = replay_tasks(&events[..first]);
+        let mut after = before.clone();
+        let mut undone = Vec::new();
+        let mut uids = Vec::new();
+        for logged in &events[first..] {
+            if logged.time != last_saved || !by_command(logged) {
+                continue;
+            }
+            undone.push(logged.event.describe(&after, config));
+            logged.event.apply(&mut after, logged.time);
+            if let Some(uid) = logged.event.uid().filter(|uid| !uids.contains(uid)) {
+                uids.push(uid);
+            }
+        }
+        let task = |tasks: &[Task], uid: u64| tasks.iter().find(|task| task.uid == uid).cloned();
+        for uid in uids {
+            self.undo_task(uid, task(&before, uid), task(&after, uid));
+        }
+        Ok(Some(undone))
+    }
+
+    fn undo_task(&mut self, uid: u64, before: Option<Task>, after: Option<Task>) {
+        let current = self.tasks.iter().position(|task| task.uid == uid);
+        match (before, after, current) {
+            (Some(before), Some(after), Some(id)) => {
+                let mut tasks = vec![self.tasks[id].clone()];
+                let now = Utc::now();
+                for event in task_events(&after, &before) {
+                    event.apply(&mut tasks, now);
+                }
+                let mut task = tasks.remove(0);
+                task.history = self.tasks[id].history.clone();
+                task.modified_at = Some(now);
+                self.update_task(id, task);
+            }
+            // Removed, from the trash or the archive it went to if it's still there
+            (Some(before), None, None) => {
+                self.trash.retain(|trashed| trashed.task.uid != uid);
+                self.archive.retain(|archived| archived.task.uid != uid);
+                self.apply(Event::TaskAdded { task: before });
+            }
+            (None, Some(_), Some(_)) => self.apply(Event::TaskRemoved { uid }),
+            _ => {}
+        }
+    }
+}
+
+// The tasks as they were at a time and the events replayed to get there, described
+pub struct Replayed {
+    pub tasks: Vec<Task>,
+    pub events: Vec<String>,
+}
+
+// Replays from the last snapshot taken by until, None without an event log
+pub fn replay(
+    data_file: &Path,
+    until: DateTime<Utc>,
+    config: &Config,
+) -> Result<Option<Replayed>, Box<dyn Error>> {
+    let events = match read_events(data_file)? {
+        Some(events) => events,
+        None => return Ok(None),
+    };
+    let events: Vec<LoggedEvent> = events
+        .into_iter()
+        .take_while(|logged| logged.time <= until)
+        .collect();
+    let start = last_snapshot(&events);
+    let mut tasks = Vec::new();
+    let mut replayed = Vec::new();
+    for logged in &events[start..] {
+        replayed.push(format!(
+            "{}  {}",
+            config.format_datetime(logged.time),
+            logged.event.describe(&tasks, config)
+        ));
+        logged.event.apply(&mut tasks, logged.time);
+    }
+    Ok(Some(Replayed {
+        tasks,
+        events: replayed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changes, events_path, read_events, replay, Event, FieldValue, SNAPSHOT_EVERY};
+    use crate::config::Config;
+    use crate::{Status, TaskManager};
+    use chrono::{Duration, Utc};
+    use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn changes_are_logged_as_events_and_replayed() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Pay rent".to_string());
+        task_manager.add_task("Water plants".to_string());
+        let before = task_manager.tasks.clone();
+        task_manager.set_task_status(0, Status::Active);
+        task_manager.set_urgency(0, 6.0);
+        task_manager.set_task_description(1, "the ferns too".to_string());
+        task_manager.add_task("Book flights".to_string());
+        let events = changes(&before, &task_manager.tasks);
+        assert!(matches!(
+            events.as_slice(),
+            [
+                Event::StatusChanged {
+                    uid: 1,
+                    status: Status::Active,
+                    ..
+                },
+                Event::UrgencyChanged { uid: 1, .. },
+                Event::FieldChanged {
+                    uid: 2,
+                    change: FieldValue::Description(_)
+                },
+                Event::TaskAdded { .. }
+            ]
+        ));
+        // Made through events as they happened, a change without a setter is logged whole on save
+        assert_eq!(task_manager.pending_events.len(), 6);
+        task_manager.tasks[2].external_id = Some("csv:7".to_string());
+
+        let data_dir = std::env::temp_dir().join(format!("tasks-events-{}", std::process::id()));
+        create_dir_all(&data_dir).unwrap();
+        let data_file = data_dir.join("task.json");
+        task_manager.save_to_file(&data_file).unwrap();
+        let config = Config::default();
+        let replayed = replay(&data_file, Utc::now(), &config).unwrap().unwrap();
+        // The empty snapshot starting the log, the events, the whole task changed without one and
+        // the next uid
+        assert_eq!(replayed.events.len(), 9);
+        assert!(replayed.events[7].ends_with("changed Book flights"));
+        assert!(replayed.events[3].ends_with("Pay rent: Active"));
+        assert_eq!(replayed.tasks.len(), 3);
+        assert_eq!(replayed.tasks[0].urgency, 6.0);
+        assert_eq!(replayed.tasks[1].description, "the ferns too");
+
+        let earlier = Utc::now() - Duration::hours(1);
+        let replayed = replay(&data_file, earlier, &config).unwrap().unwrap();
+        assert!(replayed.tasks.is_empty());
+        remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn undo_reverts_the_last_save_and_again_brings_it_back() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-undo-{}", std::process::id()));
+        create_dir_all(&data_dir).unwrap();
+        let data_file = data_dir.join("task.json");
+        let config = Config::default();
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Pay rent".to_string());
+        task_manager.add_task("Water plants".to_string());
+        task_manager.save_to_file(&data_file).unwrap();
+
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.set_task_name(0, "Pay the rent".to_string());
+        task_manager.trash_task(1);
+        task_manager.save_to_file(&data_file).unwrap();
+
+        // A run making only housekeeping changes leaves the last command to undo, and keeps them
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.set_urgency(0, 5.0);
+        task_manager.end_housekeeping();
+        task_manager.save_to_file(&data_file).unwrap();
+
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.set_task_description(0, "by the first".to_string());
+        task_manager.end_housekeeping();
+        let undone = task_manager
+            .undo_last_change(&data_file, &config)
+            .unwrap()
+            .unwrap();
+        assert_eq!(undone.len(), 2);
+        assert_eq!(task_manager.tasks[0].title, "Pay rent");
+        assert_eq!(task_manager.tasks[0].urgency, 5.0);
+        assert_eq!(task_manager.tasks[0].description, "by the first");
+        assert_eq!(task_manager.tasks[1].title, "Water plants");
+        assert!(task_manager.trash.is_empty());
+        // Undoing leaves the history as it was
+        assert_eq!(task_manager.tasks[0].history.len(), 3);
+        task_manager.save_to_file(&data_file).unwrap();
+
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.undo_last_change(&data_file, &config).unwrap();
+        assert_eq!(task_manager.tasks.len(), 1);
+        assert_eq!(task_manager.tasks[0].title, "Pay the rent");
+        task_manager.save_to_file(&data_file).unwrap();
+
+        // Changed behind the log's back, nothing can be undone until the log starts over
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.loaded_tasks = Some(Vec::new());
+        assert!(task_manager.undo_last_change(&data_file, &config).is_err());
+
+        // A log that can't be read is an error, not a log that isn't there yet
+        remove_file(events_path(&data_file)).unwrap();
+        create_dir_all(events_path(&data_file)).unwrap();
+        assert!(task_manager.undo_last_change(&data_file, &config).is_err());
+        assert!(replay(&data_file, Utc::now(), &config).is_err());
+        remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn journaled_saves_are_replayed_on_load() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-journal-{}", std::process::id()));
+        create_dir_all(&data_dir).unwrap();
+        let data_file = data_dir.join("task.json");
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Keep".to_string());
+        task_manager.add_task("Remove".to_string());
+        task_manager.save_to_file(&data_file).unwrap();
+        let written = read_to_string(&data_file).unwrap();
+
+        let mut task_manager = TaskManager::load_from_file(&data_file).unwrap();
+        task_manager.set_task_status(0, Status::Active);
+        task_manager.trash_task(1);
+        task_manager.add_task("New".to_string());
+        task_manager.save_journaled(&data_file, 100).unwrap();
+        assert_eq!(read_to_string(&data_file).unwrap(), written);
+
+        // A last line cut short by a crash is skipped, and ended before the next save appends
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(events_path(&data_file))
+            .unwrap();
+        log.write_all(b"{\"seq\": 99, \"ti").unwrap();
+        let reloaded = TaskManager::load_from_file(&data_file).unwrap();
+        let titles: Vec<&str> = reloaded
+            .tasks
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Keep", "New"]);
+        assert_eq!(reloaded.tasks[0].status, Status::Active);
+        assert_eq!(reloaded.trash.len(), 1);
+        assert_eq!(reloaded.next_uid, task_manager.next_uid);
+
+        let mut reloaded = reloaded;
+        reloaded.set_task_name(1, "Newer".to_string());
+        reloaded.save_journaled(&data_file, 100).unwrap();
+        let reloaded = TaskManager::load_from_file(&data_file).unwrap();
+        assert_eq!(reloaded.tasks[1].title, "Newer");
+        assert_eq!(read_events(&data_file).unwrap().unwrap().len(), 10);
+
+        // Past compact_after the file is written in full and nothing is left to replay
+        let mut reloaded = reloaded;
+        reloaded.set_task_name(1, "Newest".to_string());
+        reloaded.save_journaled(&data_file, 3).unwrap();
+        assert_ne!(read_to_string(&data_file).unwrap(), written);
+        let saved: TaskManager =
+            serde_json::from_str(&read_to_string(&data_file).unwrap()).unwrap();
+        assert_eq!(saved.tasks[1].title, "Newest");
+        remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn a_snapshot_compacts_the_log_to_the_one_before() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-compact-{}", std::process::id()));
+        create_dir_all(&data_dir).unwrap();
+        let data_file = data_dir.join("task.json");
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Count".to_string());
+        task_manager.save_to_file(&data_file).unwrap();
+        for round in 0..2 * SNAPSHOT_EVERY {
+            task_manager.set_task_description(0, round.to_string());
+            task_manager.save_to_file(&data_file).unwrap();
+        }
+        let events = read_events(&data_file).unwrap().unwrap();
+        let snapshots = events
+            .iter()
+            .filter(|logged| matches!(logged.event, Event::Snapshot { .. }))
+            .count();
+        assert_eq!(snapshots, 2);
+        assert!(events.len() < SNAPSHOT_EVERY * 2);
+        assert!(matches!(events[0].event, Event::Snapshot { .. }));
+        assert!(events.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+        let reloaded = TaskManager::load_from_file(&data_file).unwrap();
+        assert_eq!(
+            reloaded.tasks[0].description,
+            (2 * SNAPSHOT_EVERY - 1).to_string()
+        );
+        remove_dir_all(&data_dir).unwrap();
+    }
+}
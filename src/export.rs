@@ -0,0 +1,317 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+
+use crate::dates::to_local;
+use crate::filter::{Field, Filter};
+use crate::{Status, TaskManager};
+
+// Quote a CSV field when it contains a separator, quote or newline
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn ics_datetime(datetime: DateTime<Utc>) -> String {
+    datetime.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Active timestamps like <2024-03-01 Fri 17:00> appear in the org agenda
+fn org_timestamp(datetime: DateTime<Utc>) -> String {
+    to_local(datetime).format("<%Y-%m-%d %a %H:%M>").to_string()
+}
+
+// Org tags only take letters, digits and _@#%, so errands-home is written errands_home
+fn org_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "_@#%".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl TaskManager {
+    // The whole task, without the uids that only mean something in this task file
+    pub fn export_task(&self, id: usize) -> Option<String> {
+        let mut task = self.tasks.get(id)?.clone();
+        task.uid = 0;
+        task.depends_on.clear();
+        serde_json::to_string_pretty(&task).ok()
+    }
+
+    // IDs of tasks matching the filter, in list order
+    pub fn filtered_ids(&self, filter: &Filter) -> Vec<usize> {
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| filter.matches(task))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn export_csv(&self, filter: &Filter, fields: &[Field]) -> String {
+        let mut output = String::new();
+        let header: Vec<&str> = fields.iter().map(|field| field.name()).collect();
+        output.push_str(&header.join(","));
+        output.push('\n');
+        for id in self.filtered_ids(filter) {
+            let row: Vec<String> = fields
+                .iter()
+                .map(|field| csv_escape(&field.value(id, &self.tasks[id])))
+                .collect();
+            output.push_str(&row.join(","));
+            output.push('\n');
+        }
+        output
+    }
+
+    pub fn export_json(&self, filter: &Filter, fields: &[Field]) -> String {
+        let tasks: Vec<Value> = self
+            .filtered_ids(filter)
+            .into_iter()
+            .map(|id| {
+                let task = &self.tasks[id];
+                let mut object = Map::new();
+                for field in fields {
+                    let value = match field {
+                        Field::Id => Value::from(id),
+                        Field::Urgency => Value::from(task.effective_urgency),
+                        Field::Progress => Value::from(task.effective_progress),
+                        _ => Value::from(field.value(id, task)),
+                    };
+                    object.insert(field.name().to_string(), value);
+                }
+                Value::Object(object)
+            })
+            .collect();
+        serde_json::to_string_pretty(&tasks).unwrap_or_default()
+    }
+
+    // iCalendar VTODO entries, fields decide which optional properties are written
+    pub fn export_ics(&self, filter: &Filter, fields: &[Field]) -> String {
+        let stamp = ics_datetime(Utc::now());
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//tasks//tasks//EN".to_string(),
+        ];
+        for id in self.filtered_ids(filter) {
+            let task = &self.tasks[id];
+            let created = task.start_time.map(|start| start.timestamp()).unwrap_or(0);
+            lines.push("BEGIN:VTODO".to_string());
+            // The uid stays with the task when the list is re-sorted, so calendars update the
+            // same entry
+            lines.push(format!("UID:task-{}-{}@tasks", created, task.uid));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            for field in fields {
+                match field {
+                    Field::Title => lines.push(format!("SUMMARY:{}", ics_escape(&task.title))),
+                    Field::Description if !task.description.is_empty() => {
+                        lines.push(format!("DESCRIPTION:{}", ics_escape(&task.description)))
+                    }
+                    Field::Status => lines.push(
+                        match task.status {
+                            Status::Inactive => "STATUS:NEEDS-ACTION",
+                            Status::Active => "STATUS:IN-PROCESS",
+                            Status::Done => "STATUS:COMPLETED",
+                        }
+                        .to_string(),
+                    ),
+                    // iCalendar priorities run from 1 (highest) to 9, 0 is undefined
+                    Field::Urgency => {
+                        let priority = 10.0 - task.effective_urgency.clamp(1.0, 9.0);
+                        lines.push(format!("PRIORITY:{}", priority.round() as u8));
+                    }
+                    Field::Start => {
+                        if let Some(start_time) = task.start_time {
+                            lines.push(format!("DTSTART:{}", ics_datetime(start_time)));
+                        }
+                    }
+                    Field::Due => {
+                        if let Some(due_time) = task.due_time {
+                            lines.push(format!("DUE:{}", ics_datetime(due_time)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            lines.push("END:VTODO".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.join("\r\n") + "\r\n"
+    }
+
+    // A checklist like - [ ] title (due 2024-03-01 17:00), description lines are indented below.
+    // Tasks without a project come first, then a ## heading for each project. Fields decide
+    // whether the due date, description and project headings are written
+    pub fn export_md(&self, filter: &Filter, fields: &[Field]) -> String {
+        let ids = self.filtered_ids(filter);
+        let by_project = fields.contains(&Field::Project);
+        let project_of = |id: usize| self.tasks[id].project.as_ref().filter(|_| by_project);
+        let mut projects: Vec<Option<&String>> = vec![None];
+        for id in ids.iter() {
+            let project = project_of(*id);
+            if !projects.contains(&project) {
+                projects.push(project);
+            }
+        }
+        let mut output = String::new();
+        for project in projects {
+            if let Some(project) = project {
+                if !output.is_empty() {
+                    output.push('\n');
+                }
+                output.push_str(&format!("## {}\n\n", project));
+            }
+            for id in ids.iter() {
+                let task = &self.tasks[*id];
+                if project_of(*id) != project {
+                    continue;
+                }
+                let check = if task.status == Status::Done {
+                    'x'
+                } else {
+                    ' '
+                };
+                output.push_str(&format!("- [{}] {}", check, task.title));
+                if let (true, Some(due_time)) = (fields.contains(&Field::Due), task.due_time) {
+                    output.push_str(&format!(
+                        " (due {})",
+                        to_local(due_time).format("%Y-%m-%d %H:%M")
+                    ));
+                }
+                output.push('\n');
+                if fields.contains(&Field::Description) {
+                    for line in task.description.lines() {
+                        output.push_str(&format!("  {}\n", line));
+                    }
+                }
+            }
+        }
+        output
+    }
+
+    // One org heading per task, fields decide which parts are written
+    pub fn export_org(&self, filter: &Filter, fields: &[Field]) -> String {
+        let mut output = String::new();
+        for id in self.filtered_ids(filter) {
+            let task = &self.tasks[id];
+            let keyword = match task.status {
+                Status::Done => "DONE",
+                _ => "TODO",
+            };
+            let mut heading = format!("* {}", keyword);
+            if fields.contains(&Field::Urgency) {
+                let priority = match task.effective_urgency {
+                    urgency if urgency >= 7.0 => 'A',
+                    urgency if urgency >= 4.0 => 'B',
+                    _ => 'C',
+                };
+                heading.push_str(&format!(" [#{}]", priority));
+            }
+            heading.push(' ');
+            heading.push_str(&task.title);
+            if fields.contains(&Field::Tags) && !task.tags.is_empty() {
+                let tags: Vec<String> = task.tags.iter().map(|tag| org_tag(tag)).collect();
+                heading.push_str(&format!(" :{}:", tags.join(":")));
+            }
+            output.push_str(&heading);
+            output.push('\n');
+
+            let mut planning = Vec::new();
+            if task.status == Status::Done {
+                if let Some(completed) = task.completed_at() {
+                    let closed = to_local(completed).format("[%Y-%m-%d %a %H:%M]");
+                    planning.push(format!("CLOSED: {}", closed));
+                }
+            }
+            if let Some(scheduled) = task.scheduled {
+                planning.push(format!("SCHEDULED: {}", org_timestamp(scheduled)));
+            }
+            if let (true, Some(due_time)) = (fields.contains(&Field::Due), task.due_time) {
+                planning.push(format!("DEADLINE: {}", org_timestamp(due_time)));
+            }
+            if !planning.is_empty() {
+                output.push_str(&format!("  {}\n", planning.join(" ")));
+            }
+
+            output.push_str("  :PROPERTIES:\n");
+            output.push_str(&format!("  :ID: {}\n", task.uid));
+            if fields.contains(&Field::Status) {
+                output.push_str(&format!("  :STATUS: {}\n", task.status.as_str()));
+            }
+            if let (true, Some(start_time)) = (fields.contains(&Field::Start), task.start_time) {
+                output.push_str(&format!("  :START: {}\n", org_timestamp(start_time)));
+            }
+            output.push_str("  :END:\n");
+            if fields.contains(&Field::Description) {
+                for line in task.description.lines() {
+                    output.push_str(&format!("  {}\n", line));
+                }
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::{Field, Filter};
+    use crate::TaskManager;
+    use chrono::{Local, TimeZone, Utc};
+
+    #[test]
+    fn org_headings_carry_tags() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Buy milk".to_string());
+        debug_manager
+            .change_tags(0, &["errands".to_string(), "corner-shop".to_string()])
+            .unwrap();
+        let org = debug_manager.export_org(&Filter::default(), &[Field::Title, Field::Tags]);
+        assert!(org.starts_with("* TODO Buy milk :errands:corner_shop:\n"));
+        let org = debug_manager.export_org(&Filter::default(), &[Field::Title]);
+        assert!(org.starts_with("* TODO Buy milk\n"));
+    }
+
+    #[test]
+    fn ics_uids_follow_the_task_not_its_position() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("first".to_string());
+        debug_manager.add_task("second".to_string());
+        debug_manager.tasks.swap(0, 1);
+        let uid = debug_manager.tasks[0].uid;
+        let ics = debug_manager.export_ics(&Filter::default(), &[Field::Title]);
+        let first_uid = ics.lines().find(|line| line.starts_with("UID:")).unwrap();
+        assert!(first_uid.ends_with(&format!("-{}@tasks", uid)));
+    }
+
+    #[test]
+    fn org_scheduled_comes_from_the_scheduled_time() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Plan trip".to_string());
+        debug_manager.tasks[0].planned_on = Some(Local::now().date_naive());
+        let org = debug_manager.export_org(&Filter::default(), &[Field::Title]);
+        assert!(!org.contains("SCHEDULED"));
+        let scheduled = Local
+            .with_ymd_and_hms(2024, 3, 1, 14, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        debug_manager.set_scheduled(0, Some(scheduled));
+        let org = debug_manager.export_org(&Filter::default(), &[Field::Title]);
+        assert!(org.contains("  SCHEDULED: <2024-03-01 Fri 14:00>\n"));
+    }
+}
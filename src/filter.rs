@@ -0,0 +1,504 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::config::Config;
+use crate::dates::{format_due, format_duration, format_relative, parse_day, to_local};
+use crate::error::TaskError;
+use crate::tags::parse_tag;
+use crate::virtual_tags::VirtualTag;
+use crate::{Status, Task, TaskManager};
+
+// One condition of a filter expression such as status:open or urgency>5
+#[derive(Debug, PartialEq)]
+enum Condition {
+    Status(Status),
+    Open,
+    UrgencyAbove(f32),
+    UrgencyBelow(f32),
+    DueBefore(NaiveDate),
+    DueAfter(NaiveDate),
+    HasDue(bool),
+    Contains(String),
+    // A custom field from config, None for tasks without it
+    Custom(String, Option<String>),
+    // +TAG or -TAG, whether the virtual tag must apply or must not
+    Tag(VirtualTag, bool),
+    // +tag or -tag for the tags set on tasks, lowercase
+    UserTag(String, bool),
+    // Lowercase, None for tasks without a project
+    Project(Option<String>),
+    // Lowercase, None for unassigned tasks
+    Assignee(Option<String>),
+    // Open and unchanged for more than this many days
+    Stale(i64),
+}
+
+// Space separated conditions which must all match
+#[derive(Debug, Default, PartialEq)]
+pub struct Filter {
+    conditions: Vec<Condition>,
+    // From config, for +STALE
+    stale_after_days: i64,
+}
+
+fn parse_filter_date(value: &str, input_date_format: &str) -> Result<NaiveDate, TaskError> {
+    parse_day(value, input_date_format)
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+        .ok_or(TaskError::InvalidFilterValue {
+            field: "date",
+            value: value.to_string(),
+        })
+}
+
+fn parse_urgency(value: &str) -> Result<f32, TaskError> {
+    value.parse().map_err(|_| TaskError::InvalidFilterValue {
+        field: "urgency",
+        value: value.to_string(),
+    })
+}
+
+// Tags written in capitals are taken for virtual tags, so +LATE is a mistake rather than a tag
+fn user_tag(name: &str) -> Option<String> {
+    if name.chars().any(char::is_lowercase) {
+        parse_tag(name).ok()
+    } else {
+        None
+    }
+}
+
+impl Filter {
+    pub fn parse(expression: &str, config: &Config) -> Result<Self, TaskError> {
+        let input_date_format = config.input_date_format.as_str();
+        let mut conditions = Vec::new();
+        for term in expression.split_whitespace() {
+            let condition = if let Some((key, value)) = term.split_once(':') {
+                match (key.to_lowercase().as_str(), value.to_lowercase().as_str()) {
+                    ("status", "open") => Condition::Open,
+                    ("status", "inactive") => Condition::Status(Status::Inactive),
+                    ("status", "active") => Condition::Status(Status::Active),
+                    ("status", "done") => Condition::Status(Status::Done),
+                    ("due", "none") => Condition::HasDue(false),
+                    ("due", "any") => Condition::HasDue(true),
+                    ("title", _) => Condition::Contains(value.to_lowercase()),
+                    ("assignee", "none") => Condition::Assignee(None),
+                    ("assignee", "me") => match config.user_name() {
+                        Some(user) => Condition::Assignee(Some(user.to_lowercase())),
+                        None => {
+                            return Err(TaskError::InvalidFilterValue {
+                                field: "assignee",
+                                value: "me, set user in config".to_string(),
+                            })
+                        }
+                    },
+                    ("assignee", value) => Condition::Assignee(Some(value.to_string())),
+                    ("project", "none") => Condition::Project(None),
+                    ("project", value) => Condition::Project(Some(value.to_string())),
+                    ("stale", days) => match days.parse() {
+                        Ok(days) => Condition::Stale(days),
+                        Err(_) => {
+                            return Err(TaskError::InvalidFilterValue {
+                                field: "stale",
+                                value: days.to_string(),
+                            })
+                        }
+                    },
+                    (_, "none") if config.custom_fields.contains_key(key) => {
+                        Condition::Custom(key.to_string(), None)
+                    }
+                    _ => match config.custom_fields.get(key) {
+                        Some(field) => Condition::Custom(
+                            key.to_string(),
+                            Some(field.check(key, value, config)?.to_lowercase()),
+                        ),
+                        None => return Err(TaskError::UnknownFilterTerm(term.to_string())),
+                    },
+                }
+            } else if let Some((key, value)) = term.split_once('<') {
+                match key {
+                    "urgency" => Condition::UrgencyBelow(parse_urgency(value)?),
+                    "due" => Condition::DueBefore(parse_filter_date(value, input_date_format)?),
+                    _ => return Err(TaskError::UnknownFilterTerm(term.to_string())),
+                }
+            } else if let Some((key, value)) = term.split_once('>') {
+                match key {
+                    "urgency" => Condition::UrgencyAbove(parse_urgency(value)?),
+                    "due" => Condition::DueAfter(parse_filter_date(value, input_date_format)?),
+                    _ => return Err(TaskError::UnknownFilterTerm(term.to_string())),
+                }
+            } else if let Some(tag) = term.strip_prefix('+') {
+                match (VirtualTag::parse(tag), user_tag(tag)) {
+                    (Some(tag), _) => Condition::Tag(tag, true),
+                    (None, Some(tag)) => Condition::UserTag(tag, true),
+                    (None, None) => return Err(TaskError::UnknownFilterTerm(term.to_string())),
+                }
+            } else if let Some(tag) = term.strip_prefix('-').and_then(VirtualTag::parse) {
+                Condition::Tag(tag, false)
+            } else if let Some(tag) = term.strip_prefix('-').and_then(user_tag) {
+                Condition::UserTag(tag, false)
+            } else {
+                Condition::Contains(term.to_lowercase())
+            };
+            conditions.push(condition);
+        }
+        Ok(Filter {
+            conditions,
+            stale_after_days: config.stale_after_days,
+        })
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        let due_date = task.due_time.map(|due_time| to_local(due_time).date());
+        let now = Utc::now();
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Status(status) => task.status == *status,
+            Condition::Open => task.status != Status::Done,
+            Condition::UrgencyAbove(urgency) => task.effective_urgency > *urgency,
+            Condition::UrgencyBelow(urgency) => task.effective_urgency < *urgency,
+            Condition::DueBefore(date) => due_date.is_some_and(|due| due < *date),
+            Condition::DueAfter(date) => due_date.is_some_and(|due| due > *date),
+            Condition::HasDue(has_due) => due_date.is_some() == *has_due,
+            Condition::Contains(text) => {
+                task.title.to_lowercase().contains(text.as_str())
+                    || task.description.to_lowercase().contains(text.as_str())
+            }
+            Condition::Custom(name, value) => {
+                task.custom.get(name).map(|value| value.to_lowercase()) == *value
+            }
+            Condition::Tag(tag, applies) => {
+                tag.applies(task, now, self.stale_after_days) == *applies
+            }
+            Condition::UserTag(tag, applies) => task.tags.contains(tag) == *applies,
+            Condition::Assignee(assignee) => {
+                task.assignee.as_ref().map(|name| name.to_lowercase()) == *assignee
+            }
+            Condition::Project(project) => {
+                task.project.as_ref().map(|name| name.to_lowercase()) == *project
+            }
+            Condition::Stale(days) => task.is_stale(now, *days),
+        })
+    }
+}
+
+// Orders for list --sort, urgency is the order tasks are already stored in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sort {
+    Urgency,
+    Age,
+    Modified,
+    Due,
+}
+
+impl Sort {
+    pub fn parse(name: &str) -> Result<Self, TaskError> {
+        match name.to_lowercase().as_str() {
+            "urgency" => Ok(Sort::Urgency),
+            "age" => Ok(Sort::Age),
+            "modified" => Ok(Sort::Modified),
+            "due" => Ok(Sort::Due),
+            _ => Err(TaskError::UnknownSort(name.to_string())),
+        }
+    }
+}
+
+impl TaskManager {
+    // Oldest first for age, most recent first for modified, soonest first for due
+    pub fn sort_ids(&self, ids: &mut [usize], sort: Sort) {
+        match sort {
+            Sort::Urgency => {}
+            Sort::Age => ids.sort_by_key(|id| self.tasks[*id].created_at),
+            Sort::Modified => ids.sort_by_key(|id| std::cmp::Reverse(self.tasks[*id].modified_at)),
+            Sort::Due => ids.sort_by_key(|id| {
+                let due_time = self.tasks[*id].due_time;
+                (due_time.is_none(), due_time)
+            }),
+        }
+    }
+}
+
+// Columns that list and the exporters can be asked for with --fields
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Id,
+    Title,
+    Description,
+    Status,
+    Urgency,
+    Start,
+    Due,
+    Estimate,
+    Created,
+    Modified,
+    Assignee,
+    Project,
+    Tags,
+    Progress,
+    // A custom field declared in config, by name
+    Custom(String),
+}
+
+pub const ALL_FIELDS: [Field; 14] = [
+    Field::Id,
+    Field::Title,
+    Field::Description,
+    Field::Status,
+    Field::Urgency,
+    Field::Start,
+    Field::Due,
+    Field::Estimate,
+    Field::Created,
+    Field::Modified,
+    Field::Assignee,
+    Field::Project,
+    Field::Tags,
+    Field::Progress,
+];
+
+fn format_datetime(datetime: Option<DateTime<Utc>>) -> String {
+    datetime
+        .map(|datetime| to_local(datetime).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+impl Field {
+    pub fn name(&self) -> &str {
+        match self {
+            Field::Id => "id",
+            Field::Title => "title",
+            Field::Description => "description",
+            Field::Status => "status",
+            Field::Urgency => "urgency",
+            Field::Start => "start",
+            Field::Due => "due",
+            Field::Estimate => "estimate",
+            Field::Created => "created",
+            Field::Modified => "modified",
+            Field::Assignee => "assignee",
+            Field::Project => "project",
+            Field::Tags => "tags",
+            Field::Progress => "progress",
+            Field::Custom(name) => name,
+        }
+    }
+
+    pub fn value(&self, id: usize, task: &Task) -> String {
+        match self {
+            Field::Id => id.to_string(),
+            Field::Title => task.title.clone(),
+            Field::Description => task.description.clone(),
+            Field::Status => task.status.as_str().to_string(),
+            Field::Urgency => format!("{:.2}", task.effective_urgency),
+            Field::Start => format_datetime(task.start_time),
+            Field::Due => format_datetime(task.due_time),
+            Field::Estimate => task.estimate.map(format_duration).unwrap_or_default(),
+            Field::Created => format_datetime(task.created_at),
+            Field::Modified => format_datetime(task.modified_at),
+            Field::Assignee => task.assignee.clone().unwrap_or_default(),
+            Field::Project => task.project.clone().unwrap_or_default(),
+            Field::Tags => task.tags.join(" "),
+            Field::Progress => task
+                .effective_progress
+                .map(|progress| progress.to_string())
+                .unwrap_or_default(),
+            Field::Custom(name) => task.custom.get(name).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+impl Field {
+    // Like value, but dates are written relative to now for reading in the terminal
+    pub fn display_value(&self, id: usize, task: &Task, config: &Config) -> String {
+        let relative = config.relative_times;
+        let now = Utc::now();
+        let datetime = match self {
+            Field::Start => task.start_time,
+            Field::Created => task.created_at,
+            Field::Modified => task.modified_at,
+            Field::Due if relative => {
+                return task
+                    .due_time
+                    .map(|due_time| format_due(due_time, now))
+                    .unwrap_or_default()
+            }
+            Field::Due => task.due_time,
+            _ => return self.value(id, task),
+        };
+        match datetime {
+            Some(datetime) if relative => format_relative(datetime, now),
+            Some(datetime) => config.format_datetime(datetime),
+            None => String::new(),
+        }
+    }
+}
+
+impl Field {
+    pub fn parse(name: &str, config: &Config) -> Result<Self, TaskError> {
+        let name = name.trim();
+        if let Some(field) = ALL_FIELDS
+            .iter()
+            .find(|field| field.name() == name.to_lowercase())
+        {
+            return Ok(field.clone());
+        }
+        if config.custom_fields.contains_key(name) {
+            return Ok(Field::Custom(name.to_string()));
+        }
+        Err(TaskError::UnknownField(name.to_string()))
+    }
+
+    // The built in fields followed by those declared in config
+    pub fn all(config: &Config) -> Vec<Field> {
+        let custom = config.custom_fields.keys().cloned().map(Field::Custom);
+        ALL_FIELDS.iter().cloned().chain(custom).collect()
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches!(
+            self,
+            Field::Start | Field::Due | Field::Created | Field::Modified
+        )
+    }
+
+    // The time behind date fields, None for fields that aren't dates
+    pub fn datetime(&self, task: &Task) -> Option<DateTime<Utc>> {
+        match self {
+            Field::Start => task.start_time,
+            Field::Due => task.due_time,
+            Field::Created => task.created_at,
+            Field::Modified => task.modified_at,
+            _ => None,
+        }
+    }
+}
+
+// Comma separated list of field names, e.g. id,title,due
+pub fn parse_fields(list: &str, config: &Config) -> Result<Vec<Field>, TaskError> {
+    list.split(',')
+        .map(|name| Field::parse(name, config))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_fields, Field, Filter};
+    use crate::config::Config;
+    use crate::custom::CustomField;
+    use crate::error::TaskError;
+    use crate::{Status, Task, TaskManager, UrgencySettings};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn stale_filters() {
+        let mut task = Task::new("Renew lease".to_string());
+        task.modified_at = Some(Utc::now() - Duration::days(20));
+        let config = Config {
+            stale_after_days: 14,
+            ..Config::default()
+        };
+        let matches = |expression: &str, task: &Task| {
+            Filter::parse(expression, &config).unwrap().matches(task)
+        };
+        assert!(matches("+STALE", &task));
+        assert!(matches("stale:7", &task));
+        assert!(!matches("stale:30", &task));
+        assert!(Filter::parse("stale:soon", &config).is_err());
+        task.status = Status::Done;
+        assert!(!matches("+STALE", &task));
+    }
+
+    #[test]
+    fn blocked_until_dependencies_are_done() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Paint walls".to_string());
+        debug_manager.add_task("Buy paint".to_string());
+        debug_manager.set_dependencies(0, &[1], false);
+        debug_manager.derive_from_dependencies();
+        let blocked = Filter::parse("+BLOCKED", &Config::default()).unwrap();
+        assert!(blocked.matches(&debug_manager.tasks[0]));
+        assert!(!blocked.matches(&debug_manager.tasks[1]));
+        debug_manager.set_task_status(1, Status::Done);
+        debug_manager.derive_from_dependencies();
+        assert!(!blocked.matches(&debug_manager.tasks[0]));
+    }
+
+    #[test]
+    fn assignee_filters() {
+        let mut task = Task::new("Review PR".to_string());
+        let config = Config {
+            user: Some("Alice".to_string()),
+            ..Config::default()
+        };
+        assert!(Filter::parse("assignee:none", &config)
+            .unwrap()
+            .matches(&task));
+        task.assignee = Some("alice".to_string());
+        assert!(Filter::parse("assignee:me", &config)
+            .unwrap()
+            .matches(&task));
+        assert!(!Filter::parse("assignee:bob", &config)
+            .unwrap()
+            .matches(&task));
+    }
+
+    #[test]
+    fn tag_and_project_filters() {
+        let mut task = Task::new("Buy stamps".to_string());
+        task.tags = vec!["errands".to_string()];
+        task.project = Some("Home".to_string());
+        let config = Config::default();
+        let matches = |expression: &str| Filter::parse(expression, &config).unwrap().matches(&task);
+        assert!(matches("+errands project:home"));
+        assert!(!matches("-work +DUETODAY"));
+        assert!(!matches("-errands"));
+        assert!(!matches("project:none"));
+        assert!(Filter::parse("+two:words", &config).is_err());
+    }
+
+    #[test]
+    fn filter_expressions() {
+        let mut task = Task::new("Write report".to_string());
+        task.urgency = 6.0;
+        task.calculate_urgency(chrono::Utc::now(), &UrgencySettings::default());
+        let config = Config::default();
+        assert!(Filter::parse("status:open urgency>5 report", &config)
+            .unwrap()
+            .matches(&task));
+        assert!(!Filter::parse("urgency<5", &config).unwrap().matches(&task));
+        assert!(Filter::parse("due:none", &config).unwrap().matches(&task));
+        task.status = Status::Done;
+        assert!(!Filter::parse("status:open", &config)
+            .unwrap()
+            .matches(&task));
+        assert!(Filter::parse("-OVERDUE -active", &config)
+            .unwrap()
+            .matches(&task));
+        assert!(Filter::parse("+done", &config).unwrap().matches(&task));
+        assert!(Filter::parse("+LATE", &config).is_err());
+        assert_eq!(
+            Filter::parse("colour:blue", &config),
+            Err(TaskError::UnknownFilterTerm("colour:blue".to_string()))
+        );
+        assert_eq!(
+            parse_fields("id, due", &config).unwrap(),
+            vec![Field::Id, Field::Due]
+        );
+    }
+
+    #[test]
+    fn custom_fields() {
+        let mut config = Config::default();
+        config
+            .custom_fields
+            .insert("customer".to_string(), CustomField::default());
+        let mut task = Task::new("Invoice".to_string());
+        task.custom
+            .insert("customer".to_string(), "ACME".to_string());
+        assert!(Filter::parse("customer:acme", &config)
+            .unwrap()
+            .matches(&task));
+        assert!(!Filter::parse("customer:none", &config)
+            .unwrap()
+            .matches(&task));
+        let fields = parse_fields("title,customer", &config).unwrap();
+        assert_eq!(fields[1], Field::Custom("customer".to_string()));
+        assert_eq!(fields[1].value(0, &task), "ACME");
+    }
+}
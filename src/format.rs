@@ -0,0 +1,131 @@
+use std::fmt::Write;
+
+use crate::config::Config;
+use crate::dates::to_local;
+use crate::error::TaskError;
+use crate::filter::Field;
+use crate::Task;
+
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Text(String),
+    // A field with an optional spec, a strftime format for dates or an alignment like >4
+    Field(Field, Option<String>),
+}
+
+// A list row template such as "{id} {urgency:>4} {due:%d/%m} {title}", {{ and }} are literal braces
+#[derive(Debug, PartialEq)]
+pub struct RowFormat {
+    pieces: Vec<Piece>,
+}
+
+// Pads to a width with <, > or ^ alignment, e.g. >4
+fn align(value: String, spec: &str) -> Option<String> {
+    let mut chars = spec.chars();
+    let alignment = chars.next()?;
+    let width: usize = chars.as_str().parse().ok()?;
+    let padding = width.saturating_sub(value.chars().count());
+    Some(match alignment {
+        '<' => format!("{}{}", value, " ".repeat(padding)),
+        '>' => format!("{}{}", " ".repeat(padding), value),
+        '^' => format!(
+            "{}{}{}",
+            " ".repeat(padding / 2),
+            value,
+            " ".repeat(padding - padding / 2)
+        ),
+        _ => return None,
+    })
+}
+
+impl RowFormat {
+    pub fn parse(template: &str, config: &Config) -> Result<Self, TaskError> {
+        let invalid = || TaskError::InvalidFormat(template.to_string());
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(invalid()),
+                        }
+                    }
+                    let (name, spec) = match placeholder.split_once(':') {
+                        Some((name, spec)) => (name, Some(spec.to_string())),
+                        None => (placeholder.as_str(), None),
+                    };
+                    let field = Field::parse(name, config)?;
+                    if let (false, Some(spec)) = (field.is_date(), &spec) {
+                        align(String::new(), spec).ok_or_else(invalid)?;
+                    }
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::Field(field, spec));
+                }
+                '}' => return Err(invalid()),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Ok(RowFormat { pieces })
+    }
+
+    pub fn render(&self, id: usize, task: &Task) -> String {
+        let mut row = String::new();
+        for piece in self.pieces.iter() {
+            match piece {
+                Piece::Text(text) => row.push_str(text),
+                Piece::Field(field, None) => row.push_str(&field.value(id, task)),
+                Piece::Field(field, Some(spec)) if field.is_date() => {
+                    if let Some(datetime) = field.datetime(task) {
+                        // chrono reports bad strftime formats while writing, leave those empty
+                        let mut value = String::new();
+                        if write!(value, "{}", to_local(datetime).format(spec)).is_ok() {
+                            row.push_str(&value);
+                        }
+                    }
+                }
+                Piece::Field(field, Some(spec)) => {
+                    let value = field.value(id, task);
+                    row.push_str(&align(value.clone(), spec).unwrap_or(value));
+                }
+            }
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RowFormat;
+    use crate::config::Config;
+    use crate::Task;
+
+    #[test]
+    fn renders_templates() {
+        let mut task = Task::new("Write report".to_string());
+        task.effective_urgency = 6.5;
+        let config = Config::default();
+        let format = RowFormat::parse("{id} {urgency:>6} {{{title}}}", &config).unwrap();
+        assert_eq!(format.render(3, &task), "3   6.50 {Write report}");
+        assert!(RowFormat::parse("{colour}", &config).is_err());
+        assert!(RowFormat::parse("{title", &config).is_err());
+        assert!(RowFormat::parse("{title:?4}", &config).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::archive::ArchivedTask;
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::{Status, Task, TaskManager};
+
+fn done_before(task: &Task, cutoff: DateTime<Utc>) -> bool {
+    task.status == Status::Done
+        && task
+            .completed_at()
+            .is_some_and(|completed| completed < cutoff)
+}
+
+impl TaskManager {
+    // Tasks completed before cutoff are deleted for good, from the list and the archive, or with
+    // archive set moved to the archive instead. Returns the titles of the tasks collected
+    pub fn collect_garbage(
+        &mut self,
+        cutoff: DateTime<Utc>,
+        archive: bool,
+        dry_run: bool,
+    ) -> Vec<String> {
+        let mut collected: Vec<String> = self
+            .tasks
+            .iter()
+            .filter(|task| done_before(task, cutoff))
+            .map(|task| task.title.clone())
+            .collect();
+        if !archive {
+            collected.extend(
+                self.archive
+                    .iter()
+                    .filter(|archived| done_before(&archived.task, cutoff))
+                    .map(|archived| archived.task.title.clone()),
+            );
+        }
+        if dry_run {
+            return collected;
+        }
+        let now = Utc::now();
+        let old = self.remove_tasks_where(|task| done_before(task, cutoff));
+        if archive {
+            self.archive
+                .extend(old.into_iter().map(|task| ArchivedTask {
+                    task,
+                    archived_at: now,
+                }));
+        } else {
+            self.archive
+                .retain(|archived| !done_before(&archived.task, cutoff));
+        }
+        collected
+    }
+
+    // Run on every start when delete_done_after_days is set, like archive_done_after_days
+    pub fn delete_old_done_tasks(&mut self, config: &Config) {
+        if config.delete_done_after_days <= 0 {
+            return;
+        }
+        let cutoff = Utc::now() - Duration::days(config.delete_done_after_days);
+        let deleted = self.collect_garbage(cutoff, false, false);
+        if !deleted.is_empty() {
+            println!(
+                "{}",
+                tr!(
+                    "gc_deleted_old_done",
+                    "Deleted {0} tasks done for over {1} days",
+                    deleted.len(),
+                    config.delete_done_after_days
+                )
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Status, TaskManager};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn old_completed_tasks_are_collected() {
+        let mut task_manager = TaskManager::new();
+        task_manager.log_task("Old".to_string(), Utc::now() - Duration::days(100), None);
+        task_manager.log_task("Recent".to_string(), Utc::now() - Duration::days(3), None);
+        task_manager.add_task("Open".to_string());
+        task_manager.set_task_status(2, Status::Active);
+        let cutoff = Utc::now() - Duration::days(90);
+        assert_eq!(
+            task_manager.collect_garbage(cutoff, false, true),
+            vec!["Old"]
+        );
+        assert_eq!(task_manager.tasks.len(), 3);
+        task_manager.collect_garbage(cutoff, true, false);
+        assert_eq!(task_manager.tasks.len(), 2);
+        assert_eq!(task_manager.archive.len(), 1);
+        assert_eq!(
+            task_manager.collect_garbage(cutoff, false, false),
+            vec!["Old"]
+        );
+        assert!(task_manager.archive.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use serde_json::Value;
+use std::error::Error;
+use std::process::Command;
+
+use crate::i18n::tr;
+use crate::import::{ImportSummary, ImportedTask};
+use crate::tags::tag_from_label;
+use crate::{Status, TaskManager};
+
+// Runs the GitHub CLI, which takes care of authentication
+fn gh(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("gh").args(args).output().map_err(|err| {
+        tr!(
+            "gh_missing",
+            "Unable to run gh, is the GitHub CLI installed? {0}",
+            err
+        )
+    })?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+// Issues as listed by gh issue list --json number,title,body,labels, labels become tags
+pub fn parse_issues(json: &str) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let issues: Vec<Value> = serde_json::from_str(json)?;
+    Ok(issues
+        .iter()
+        .filter_map(|issue| {
+            Some(ImportedTask {
+                external_id: Some(issue["number"].as_u64()?.to_string()),
+                title: issue["title"].as_str()?.to_string(),
+                description: issue["body"].as_str().unwrap_or_default().to_string(),
+                tags: issue["labels"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| tag_from_label(label["name"].as_str()?))
+                    .collect(),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+fn source(repo: &str) -> String {
+    format!("github:{}", repo)
+}
+
+impl TaskManager {
+    // Issue numbers of tasks done here whose issues are still open
+    pub fn issues_to_close(&self, repo: &str, open: &[ImportedTask]) -> Vec<String> {
+        let prefix = format!("{}:", source(repo));
+        self.tasks
+            .iter()
+            .filter(|task| task.status == Status::Done)
+            .filter_map(|task| task.external_id.as_ref()?.strip_prefix(&prefix))
+            .filter(|number| {
+                open.iter()
+                    .any(|issue| issue.external_id.as_deref() == Some(*number))
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    // Closes issues of done tasks, then imports the open issues assigned to the current user
+    pub fn sync_github(&mut self, repo: &str) -> Result<(ImportSummary, usize), Box<dyn Error>> {
+        let listed = gh(&[
+            "issue",
+            "list",
+            "--repo",
+            repo,
+            "--assignee",
+            "@me",
+            "--state",
+            "open",
+            "--limit",
+            "1000",
+            "--json",
+            "number,title,body,labels",
+        ])?;
+        let mut open = parse_issues(&listed)?;
+        let to_close = self.issues_to_close(repo, &open);
+        for number in to_close.iter() {
+            gh(&["issue", "close", number, "--repo", repo])?;
+        }
+        open.retain(|issue| !to_close.contains(issue.external_id.as_ref().unwrap()));
+        let summary = self.import_tasks(&source(repo), open, false);
+        Ok((summary, to_close.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_issues;
+    use crate::{Status, TaskManager};
+
+    #[test]
+    fn done_tasks_close_their_issues() {
+        let issues = parse_issues(
+            r#"[{"number": 12, "title": "Fix login", "body": "Steps...",
+                 "labels": [{"name": "bug"}, {"name": "Good First Issue"}]},
+                {"number": 13, "title": "Add docs", "body": ""}]"#,
+        )
+        .unwrap();
+        let mut debug_manager = TaskManager::new();
+        debug_manager.import_tasks("github:owner/name", issues, false);
+        assert_eq!(
+            debug_manager.tasks[0].external_id.as_deref(),
+            Some("github:owner/name:12")
+        );
+        assert_eq!(debug_manager.tasks[0].tags, vec!["bug", "good-first-issue"]);
+        debug_manager.set_task_status(0, Status::Done);
+        let issues = parse_issues(r#"[{"number": 12, "title": "Fix login"}]"#).unwrap();
+        assert_eq!(
+            debug_manager.issues_to_close("owner/name", &issues),
+            vec!["12"]
+        );
+        assert!(debug_manager
+            .issues_to_close("other/repo", &issues)
+            .is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, Datelike, Duration, Local, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::dates::{format_duration, from_local};
+use crate::i18n::{self, tr};
+use crate::{Status, Task, TaskManager};
+
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+// Targets for the current week, weeks run monday to sunday in local time
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyGoals {
+    // Number of tasks to mark done
+    pub done: Option<u32>,
+    // Time spent with tasks active
+    #[serde(default, with = "crate::dates::serde_seconds")]
+    pub active_time: Option<Duration>,
+    // Time spent with tasks carrying a tag active, e.g. 6h on +thesis
+    #[serde(
+        default,
+        with = "crate::dates::serde_seconds_map",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub tag_time: BTreeMap<String, Duration>,
+}
+
+impl WeeklyGoals {
+    pub fn is_empty(&self) -> bool {
+        self.done.is_none() && self.active_time.is_none() && self.tag_time.is_empty()
+    }
+}
+
+// Start of the current week
+fn week_start() -> DateTime<Utc> {
+    let today = Local::now().date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    from_local(monday.and_hms_opt(0, 0, 0).unwrap())
+}
+
+// e.g. [##########----------] 5/10
+pub fn progress_bar(progress: f64, target: f64) -> String {
+    let filled = if target > 0.0 {
+        ((progress / target).min(1.0) * PROGRESS_BAR_WIDTH as f64) as usize
+    } else {
+        PROGRESS_BAR_WIDTH
+    };
+    format!(
+        "[{}{}]",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled)
+    )
+}
+
+impl Task {
+    // Time the task spent active since a point in time, worked out from its status history
+    fn active_time_since(&self, since: DateTime<Utc>) -> Duration {
+        let mut total = Duration::zero();
+        let mut active_from = None;
+        for change in self
+            .history
+            .iter()
+            .filter(|change| change.field == "status")
+        {
+            if change.new_value == Status::Active.as_str() {
+                active_from = Some(change.time);
+            } else if let Some(from) = active_from.take() {
+                if change.time > since {
+                    total += change.time - from.max(since);
+                }
+            }
+        }
+        if let Some(from) = active_from {
+            total += Utc::now() - from.max(since);
+        }
+        total
+    }
+}
+
+impl TaskManager {
+    pub fn set_done_goal(&mut self, count: Option<u32>) {
+        self.goals.done = count;
+    }
+
+    pub fn set_active_time_goal(&mut self, active_time: Option<Duration>) {
+        self.goals.active_time = active_time;
+    }
+
+    pub fn set_tag_time_goal(&mut self, tag: String, active_time: Option<Duration>) {
+        match active_time {
+            Some(active_time) => self.goals.tag_time.insert(tag, active_time),
+            None => self.goals.tag_time.remove(&tag),
+        };
+    }
+
+    pub fn clear_goals(&mut self) {
+        self.goals = WeeklyGoals::default();
+    }
+
+    fn done_this_week(&self) -> u32 {
+        let since = week_start();
+        let archived = self.archive.iter().map(|archived| &archived.task);
+        self.tasks
+            .iter()
+            .chain(archived)
+            .filter(|task| task.status == Status::Done)
+            .filter(|task| {
+                task.completed_at()
+                    .is_some_and(|completed| completed >= since)
+            })
+            .count() as u32
+    }
+
+    // Only counting tasks with the tag when one is given
+    fn active_time_this_week(&self, tag: Option<&str>) -> Duration {
+        let since = week_start();
+        let mut total = Duration::zero();
+        for task in self.tasks.iter() {
+            if tag.is_none_or(|tag| task.tags.iter().any(|other| other == tag)) {
+                total += task.active_time_since(since);
+            }
+        }
+        total
+    }
+
+    // One line per goal, empty when no goals are set
+    pub fn goal_progress(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(target) = self.goals.done {
+            let done = self.done_this_week();
+            lines.push(tr!(
+                "goal_tasks_progress",
+                "{0} {1}/{2} tasks done",
+                progress_bar(done as f64, target as f64),
+                done,
+                target
+            ));
+        }
+        if let Some(target) = self.goals.active_time {
+            let active_time = self.active_time_this_week(None);
+            lines.push(tr!(
+                "goal_active_progress",
+                "{0} {1}/{2} active",
+                progress_bar(
+                    active_time.num_minutes() as f64,
+                    target.num_minutes() as f64
+                ),
+                format_duration(active_time),
+                format_duration(target)
+            ));
+        }
+        for (tag, target) in self.goals.tag_time.iter() {
+            let active_time = self.active_time_this_week(Some(tag));
+            lines.push(tr!(
+                "goal_tag_progress",
+                "{0} {1}/{2} on +{3}",
+                progress_bar(
+                    active_time.num_minutes() as f64,
+                    target.num_minutes() as f64
+                ),
+                format_duration(active_time),
+                format_duration(*target),
+                tag
+            ));
+        }
+        lines
+    }
+
+    pub fn show_goals(&self) {
+        if self.goals.is_empty() {
+            println!(
+                "{}",
+                i18n::text("no_weekly_goals", "There are currently no weekly goals")
+            );
+        }
+        for line in self.goal_progress() {
+            println!("{}", line);
+        }
+    }
+
+    // Goals still unmet once the week is ending, from friday on
+    pub fn unmet_goals(&self) -> Vec<String> {
+        let weekday = Local::now().date_naive().weekday();
+        if weekday.num_days_from_monday() < Weekday::Fri.num_days_from_monday() {
+            return Vec::new();
+        }
+        let mut unmet = Vec::new();
+        if let Some(target) = self.goals.done {
+            let done = self.done_this_week();
+            if done < target {
+                unmet.push(tr!(
+                    "goal_tasks_unmet",
+                    "{0} more tasks to complete",
+                    target - done
+                ));
+            }
+        }
+        if let Some(target) = self.goals.active_time {
+            let active_time = self.active_time_this_week(None);
+            if active_time < target {
+                unmet.push(tr!(
+                    "goal_active_unmet",
+                    "{0} more active time",
+                    format_duration(target - active_time)
+                ));
+            }
+        }
+        for (tag, target) in self.goals.tag_time.iter() {
+            let active_time = self.active_time_this_week(Some(tag));
+            if active_time < *target {
+                unmet.push(tr!(
+                    "goal_tag_unmet",
+                    "{0} more on +{1}",
+                    format_duration(*target - active_time),
+                    tag
+                ));
+            }
+        }
+        unmet
+    }
+}
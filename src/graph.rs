@@ -0,0 +1,65 @@
+use crate::filter::Filter;
+use crate::{Status, TaskManager};
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl TaskManager {
+    // A Graphviz digraph with an edge from each dependency to the task waiting on it,
+    // render with e.g. task graph --format dot | dot -Tsvg > tasks.svg
+    pub fn export_dot(&self, filter: &Filter) -> String {
+        let ids = self.filtered_ids(filter);
+        let mut lines = vec![
+            "digraph tasks {".to_string(),
+            "  rankdir=LR;".to_string(),
+            "  node [shape=box, style=\"rounded,filled\"];".to_string(),
+        ];
+        for &id in ids.iter() {
+            let task = &self.tasks[id];
+            let colour = match task.status {
+                Status::Inactive => "white",
+                Status::Active => "palegreen",
+                Status::Done => "lightgrey",
+            };
+            lines.push(format!(
+                "  t{} [label=\"-{}- {}\", fillcolor={}];",
+                task.uid,
+                id,
+                dot_escape(&task.title),
+                colour
+            ));
+        }
+        for &id in ids.iter() {
+            let task = &self.tasks[id];
+            for (dependency_id, dependency) in self.dependencies(task) {
+                if ids.contains(&dependency_id) {
+                    lines.push(format!("  t{} -> t{};", dependency.uid, task.uid));
+                }
+            }
+        }
+        lines.push("}".to_string());
+        lines.join("\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::Filter;
+    use crate::TaskManager;
+
+    #[test]
+    fn edges_run_from_dependency_to_task() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Book \"van\"".to_string());
+        debug_manager.add_task("Move house".to_string());
+        debug_manager.set_dependencies(1, &[0, 1], false);
+        let dot = debug_manager.export_dot(&Filter::default());
+        assert!(dot.starts_with("digraph tasks {\n"));
+        assert!(dot.contains("  t1 [label=\"-0- Book \\\"van\\\"\", fillcolor=white];"));
+        assert!(dot.contains("  t1 -> t2;"));
+        assert!(!dot.contains("t2 -> t2"));
+        debug_manager.set_dependencies(0, &[1], false);
+        assert!(debug_manager.tasks[0].depends_on.is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::Config;
+use crate::dates::{format_duration, from_local, to_local};
+use crate::error::TaskError;
+use crate::i18n;
+use crate::theme::Element;
+use crate::{Task, TaskManager};
+
+// One recorded edit of a task field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+    #[serde(deserialize_with = "crate::dates::serde_utc::deserialize")]
+    pub time: DateTime<Utc>,
+}
+
+// How times and durations are written into the history
+pub fn history_datetime(datetime: Option<DateTime<Utc>>) -> String {
+    match datetime {
+        Some(datetime) => to_local(datetime).format("%H:%M, %d/%m/%Y").to_string(),
+        None => "none".to_string(),
+    }
+}
+
+// Reads back a value written by history_datetime, "none" gives None
+pub fn parse_history_datetime(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%H:%M, %d/%m/%Y")
+        .ok()
+        .map(from_local)
+}
+
+pub fn history_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format_duration(duration),
+        None => "none".to_string(),
+    }
+}
+
+impl Task {
+    // Unchanged values are not recorded, anything else also bumps modified_at
+    pub fn record_change(&mut self, field: &str, old_value: String, new_value: String) {
+        if old_value != new_value {
+            let now = Utc::now();
+            self.history.push(Change {
+                field: field.to_string(),
+                old_value,
+                new_value,
+                time: now,
+            });
+            self.modified_at = Some(now);
+        }
+    }
+}
+
+impl TaskManager {
+    pub fn show_history(&self, id: usize, config: &Config) {
+        if self.verify_id(id) {
+            let task = &self.tasks[id];
+            println!(" -{}- {}", id, task.title);
+            if task.history.is_empty() {
+                println!(
+                    "{}",
+                    i18n::text("no_changes_recorded", "  No changes recorded")
+                );
+            }
+            for change in task.history.iter() {
+                println!(
+                    "  {}  {}: {} -> {}",
+                    config.format_datetime(change.time),
+                    change.field,
+                    change.old_value,
+                    change.new_value
+                );
+            }
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // Before and after of every change made since the history had changes_before entries
+    pub fn show_changes_since(
+        &self,
+        id: usize,
+        changes_before: usize,
+        json: bool,
+        config: &Config,
+    ) {
+        if !self.verify_id(id) {
+            return;
+        }
+        let changes = &self.tasks[id].history[changes_before..];
+        if json {
+            let patch: Vec<_> = changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "id": id,
+                        "field": change.field,
+                        "old": change.old_value,
+                        "new": change.new_value,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&patch).unwrap_or_default()
+            );
+            return;
+        }
+        if changes.is_empty() {
+            println!("{}", i18n::text("nothing_changed", "Nothing changed"));
+        }
+        let theme = config.theme();
+        for change in changes {
+            println!(
+                "  {}: {} -> {}",
+                change.field,
+                theme.paint(Element::Removed, &change.old_value),
+                theme.paint(Element::Added, &change.new_value)
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeSet;
+
+// e.g. 20241225 from DTSTART;VALUE=DATE:20241225 or DTSTART:20241225T000000Z
+fn ics_date(value: &str) -> Option<NaiveDate> {
+    let digits = value.get(..8)?;
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(
+        digits[..4].parse().ok()?,
+        digits[4..6].parse().ok()?,
+        digits[6..].parse().ok()?,
+    )
+}
+
+// Every day covered by a VEVENT, DTEND is exclusive and missing means a single day
+pub fn parse_ics_holidays(text: &str) -> BTreeSet<NaiveDate> {
+    let mut holidays = BTreeSet::new();
+    let mut start = None;
+    let mut end = None;
+    for line in text.lines() {
+        let line = line.trim_end();
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name.split(';').next().unwrap_or(name), value),
+            None => continue,
+        };
+        match (name, value) {
+            ("BEGIN", "VEVENT") => {
+                start = None;
+                end = None;
+            }
+            ("DTSTART", value) => start = ics_date(value),
+            ("DTEND", value) => end = ics_date(value),
+            ("END", "VEVENT") => {
+                if let Some(start) = start {
+                    let end = end
+                        .filter(|end| *end > start)
+                        .unwrap_or(start + Duration::days(1));
+                    let mut day = start;
+                    while day < end {
+                        holidays.insert(day);
+                        day += Duration::days(1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    holidays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ics_holidays;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn reads_all_day_events() {
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Christmas Day\r\n\
+            DTSTART;VALUE=DATE:20241225\r\n\
+            DTEND;VALUE=DATE:20241227\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20250101T000000Z\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let holidays: Vec<NaiveDate> = parse_ics_holidays(calendar).into_iter().collect();
+        let expected: Vec<NaiveDate> = [(2024, 12, 25), (2024, 12, 26), (2025, 1, 1)]
+            .iter()
+            .map(|(y, m, d)| NaiveDate::from_ymd_opt(*y, *m, *d).unwrap())
+            .collect();
+        assert_eq!(holidays, expected);
+    }
+}
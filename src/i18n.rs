@@ -0,0 +1,164 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::sync::RwLock;
+
+use crate::config::config_path;
+use crate::error::TaskError;
+
+// Locales that need no file, any other is read from locales/<name>.json in the config directory
+pub const BUILT_IN_LOCALES: [&str; 2] = ["en-eu", "en-us"];
+
+// A locale file, e.g. {"date_format": "%d.%m.%Y", "messages": {"invalid_id": "Ungültige ID: {0}"}}.
+// Messages are looked up by the keys of TaskError and the first argument of every tr! and text
+// call such as created_task, {0} {1} are replaced with the values the english message shows in
+// the same order
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Locale {
+    pub date_format: String,
+    pub time_format: String,
+    pub messages: BTreeMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            date_format: "%d/%m/%Y".to_string(),
+            time_format: "%H:%M".to_string(),
+            messages: BTreeMap::new(),
+        }
+    }
+}
+
+impl Locale {
+    pub fn load(name: &str) -> Result<Self, TaskError> {
+        match name {
+            "en-eu" => return Ok(Locale::default()),
+            "en-us" => {
+                return Ok(Locale {
+                    date_format: "%m/%d/%Y".to_string(),
+                    time_format: "%I:%M %p".to_string(),
+                    ..Locale::default()
+                })
+            }
+            _ => {}
+        }
+        let unknown = || TaskError::UnknownLocale(name.to_string());
+        // Names become file names, so nothing that could leave the directory
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_'))
+        {
+            return Err(unknown());
+        }
+        let text = config_path(&format!("locales/{}.json", name))
+            .and_then(|path| read_to_string(path).ok())
+            .ok_or_else(unknown)?;
+        serde_json::from_str(&text).map_err(|_| unknown())
+    }
+}
+
+static MESSAGES: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+// Set when the config is loaded, before anything is printed, and again whenever it is reloaded
+pub fn set_messages(messages: BTreeMap<String, String>) {
+    if let Ok(mut current) = MESSAGES.write() {
+        *current = messages;
+    }
+}
+
+// The messages in use, to put back when a reloaded config is rejected
+pub fn messages() -> BTreeMap<String, String> {
+    MESSAGES
+        .read()
+        .map(|messages| messages.clone())
+        .unwrap_or_default()
+}
+
+// Replaces {0} {1} ... in one pass, so a value that itself contains {1} is kept as it is
+fn fill(template: &str, args: &[String]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let arg = after
+            .find('}')
+            .and_then(|end| Some((end, args.get(after[..end].parse::<usize>().ok()?)?)));
+        match arg {
+            Some((end, arg)) => {
+                text.push_str(arg);
+                rest = &after[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+// The message in the configured language, None to use the english one
+pub fn translate(key: &str, args: &[String]) -> Option<String> {
+    let messages = MESSAGES.read().ok()?;
+    Some(fill(messages.get(key)?, args))
+}
+
+pub fn text(key: &str, english: &str) -> String {
+    message(key, english, &[])
+}
+
+// The message in the configured language or else the english template, with {0} {1} ... filled in
+pub fn message(key: &str, english: &str, args: &[String]) -> String {
+    let messages = MESSAGES.read();
+    let template = messages
+        .as_ref()
+        .ok()
+        .and_then(|messages| messages.get(key))
+        .map_or(english, String::as_str);
+    fill(template, args)
+}
+
+// message with any Display values, e.g. tr!("created_task", "Created task {0}", id)
+macro_rules! tr {
+    ($key:expr, $english:expr $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::message($key, $english, &[$($arg.to_string()),*])
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::{fill, Locale};
+    use crate::error::TaskError;
+
+    #[test]
+    fn locales_set_dates_and_fill_messages() {
+        assert_eq!(Locale::load("en-us").unwrap().date_format, "%m/%d/%Y");
+        assert_eq!(Locale::load("en-eu").unwrap(), Locale::default());
+        assert_eq!(
+            Locale::load("../secrets"),
+            Err(TaskError::UnknownLocale("../secrets".to_string()))
+        );
+        assert_eq!(
+            fill(
+                "Keine Aufgabe {0} von {1}",
+                &["7".to_string(), "3".to_string()]
+            ),
+            "Keine Aufgabe 7 von 3"
+        );
+        assert_eq!(
+            fill("{0} und {1}", &["{1}".to_string(), "3".to_string()]),
+            "{1} und 3"
+        );
+        assert_eq!(fill("{x} {5} {0", &["7".to_string()]), "{x} {5} {0");
+        assert_eq!(
+            TaskError::InvalidLink { index: 7, count: 3 }.args(),
+            vec!["7", "3"]
+        );
+    }
+}
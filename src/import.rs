@@ -0,0 +1,687 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde_json::Value;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use crate::dates::from_local;
+use crate::tags::tag_from_label;
+use crate::{Status, Task, TaskManager};
+
+// A task read from another tool, before it is merged into the task list
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportedTask {
+    pub external_id: Option<String>,
+    pub title: String,
+    pub description: String,
+    pub urgency: Option<f32>,
+    pub due_time: Option<DateTime<Utc>>,
+    pub done: bool,
+    // Valid tags, see parse_tag, added to those the task already has
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+}
+
+// Splits CSV text into records, handling quoted fields with commas, quotes and newlines
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+// Due dates from other tools come in a few shapes, dates alone are due at 17:00 local time
+pub fn parse_import_date(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%d/%m/%Y %H:%M",
+    ] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(input, format) {
+            return Some(from_local(datetime));
+        }
+    }
+    // Unknown zone suffixes are dropped, the date and time are kept as written
+    if input.len() >= 19 {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(&input[..19], "%Y-%m-%dT%H:%M:%S") {
+            return Some(from_local(datetime));
+        }
+    }
+    for format in ["%Y-%m-%d", "%d/%m/%Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(input, format) {
+            return date.and_hms_opt(17, 0, 0).map(from_local);
+        }
+    }
+    None
+}
+
+// Generic CSV with a header row naming the columns id, title, description, urgency, due and status
+pub fn read_csv(filename: &PathBuf) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let records = parse_csv(&read_to_string(filename)?);
+    let mut records = records.into_iter();
+    let header: Vec<String> = match records.next() {
+        Some(header) => header
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let title_column = column("title")
+        .or_else(|| column("name"))
+        .ok_or("CSV needs a title column")?;
+    let (id_column, description_column) = (column("id"), column("description"));
+    let (urgency_column, due_column, status_column) =
+        (column("urgency"), column("due"), column("status"));
+
+    let mut tasks = Vec::new();
+    for record in records {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let title = match field(Some(title_column)) {
+            Some(title) => title,
+            None => continue,
+        };
+        tasks.push(ImportedTask {
+            external_id: field(id_column),
+            title,
+            description: field(description_column).unwrap_or_default(),
+            urgency: field(urgency_column).and_then(|urgency| urgency.parse().ok()),
+            due_time: field(due_column).and_then(|due| parse_import_date(&due)),
+            done: field(status_column)
+                .map(|status| status.eq_ignore_ascii_case("done"))
+                .unwrap_or(false),
+            ..Default::default()
+        });
+    }
+    Ok(tasks)
+}
+
+// Markdown checklists as written by export --format md, other lines are ignored. Items start at
+// the beginning of a line, anything indented below one is its description, even - [ ] steps.
+// A heading sets the project of the items after it
+pub fn parse_md(text: &str) -> Vec<ImportedTask> {
+    let mut tasks: Vec<ImportedTask> = Vec::new();
+    let mut project = None;
+    for line in text.lines() {
+        if let Some(heading) = line.strip_prefix('#') {
+            let heading = heading.trim_start_matches('#');
+            if heading.starts_with(' ') && !heading.trim().is_empty() {
+                project = Some(heading.trim().to_string());
+                continue;
+            }
+        }
+        let item = ["- [ ] ", "* [ ] "]
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))
+            .map(|title| (false, title))
+            .or_else(|| {
+                ["- [x] ", "- [X] ", "* [x] ", "* [X] "]
+                    .iter()
+                    .find_map(|prefix| line.strip_prefix(prefix))
+                    .map(|title| (true, title))
+            });
+        let (done, mut title) = match item {
+            Some((done, title)) => (done, title.trim()),
+            None => {
+                // Indented lines below an item are its description
+                if let (Some(task), Some(text)) = (tasks.last_mut(), line.strip_prefix("  ")) {
+                    if !task.description.is_empty() {
+                        task.description.push('\n');
+                    }
+                    task.description.push_str(text);
+                }
+                continue;
+            }
+        };
+        let mut due_time = None;
+        if let Some(start) = title.rfind(" (due ") {
+            if let Some(due) = title[start + 6..]
+                .strip_suffix(')')
+                .and_then(parse_import_date)
+            {
+                due_time = Some(due);
+                title = &title[..start];
+            }
+        }
+        tasks.push(ImportedTask {
+            title: title.to_string(),
+            due_time,
+            done,
+            project: project.clone(),
+            ..Default::default()
+        });
+    }
+    tasks
+}
+
+pub fn read_md(filename: &PathBuf) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    Ok(parse_md(&read_to_string(filename)?))
+}
+
+// Cards of an exported Trello board, archived cards are skipped and lists limits which lists
+// are imported, by name. The list of a card becomes its project and its labels tags
+pub fn parse_trello(text: &str, lists: &[String]) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let board: Value = serde_json::from_str(text)?;
+    let list_name = |id: &Value| {
+        board["lists"]
+            .as_array()
+            .and_then(|all| all.iter().find(|list| &list["id"] == id))
+            .and_then(|list| list["name"].as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let cards = board["cards"]
+        .as_array()
+        .ok_or("Trello JSON needs a cards array")?;
+    Ok(cards
+        .iter()
+        .filter(|card| !card["closed"].as_bool().unwrap_or(false))
+        .filter(|card| {
+            lists.is_empty()
+                || lists
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&list_name(&card["idList"])))
+        })
+        .filter_map(|card| {
+            Some(ImportedTask {
+                external_id: card["id"].as_str().map(str::to_string),
+                title: card["name"].as_str()?.to_string(),
+                description: card["desc"].as_str().unwrap_or_default().to_string(),
+                due_time: card["due"].as_str().and_then(parse_import_date),
+                done: card["dueComplete"].as_bool().unwrap_or(false),
+                tags: card["labels"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| tag_from_label(label["name"].as_str()?))
+                    .collect(),
+                project: Some(list_name(&card["idList"])).filter(|name| !name.trim().is_empty()),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+pub fn read_trello(
+    filename: &PathBuf,
+    lists: &[String],
+) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    parse_trello(&read_to_string(filename)?, lists)
+}
+
+// Microsoft To Do tasks as returned by Graph, either the response with a value array or the
+// array alone
+pub fn parse_mstodo(text: &str) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let data: Value = serde_json::from_str(text)?;
+    let tasks = data
+        .as_array()
+        .or_else(|| data["value"].as_array())
+        .ok_or("Microsoft To Do JSON needs a value array")?;
+    Ok(tasks
+        .iter()
+        .filter_map(|task| {
+            let due = &task["dueDateTime"];
+            let due_time =
+                due["dateTime"]
+                    .as_str()
+                    .and_then(|datetime| match due["timeZone"].as_str() {
+                        Some("UTC") => parse_import_date(&format!("{}Z", datetime)),
+                        _ => parse_import_date(datetime),
+                    });
+            Some(ImportedTask {
+                external_id: task["id"].as_str().map(str::to_string),
+                title: task["title"].as_str()?.to_string(),
+                description: task["body"]["content"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                urgency: match task["importance"].as_str() {
+                    Some("high") => Some(8.0),
+                    Some("normal") => Some(5.0),
+                    Some("low") => Some(2.0),
+                    _ => None,
+                },
+                due_time,
+                done: task["status"].as_str() == Some("completed"),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+pub fn read_mstodo(filename: &PathBuf) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    parse_mstodo(&read_to_string(filename)?)
+}
+
+// Google Takeout's Tasks.json, task lists holding tasks, each list becomes a project. Subtasks
+// point at their parent and are imported as their own tasks titled "parent > subtask", as tasks
+// can't be nested here
+pub fn parse_google_tasks(text: &str) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let data: Value = serde_json::from_str(text)?;
+    let lists = data["items"]
+        .as_array()
+        .ok_or("Google Tasks JSON needs an items array of task lists")?;
+    let mut tasks = Vec::new();
+    for list in lists {
+        let items = match list["items"].as_array() {
+            Some(items) => items,
+            None => continue,
+        };
+        let project = list["title"]
+            .as_str()
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        let title_of = |id: &Value| {
+            items
+                .iter()
+                .find(|item| &item["id"] == id)
+                .and_then(|item| item["title"].as_str())
+        };
+        for item in items {
+            if item["deleted"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            let title = match item["title"].as_str() {
+                Some(title) if !title.trim().is_empty() => title.trim(),
+                _ => continue,
+            };
+            let title = match title_of(&item["parent"]) {
+                Some(parent) => format!("{} > {}", parent.trim(), title),
+                None => title.to_string(),
+            };
+            tasks.push(ImportedTask {
+                external_id: item["id"].as_str().map(str::to_string),
+                title,
+                description: item["notes"].as_str().unwrap_or_default().to_string(),
+                due_time: item["due"].as_str().and_then(parse_import_date),
+                done: item["status"].as_str() == Some("completed"),
+                project: project.clone(),
+                ..Default::default()
+            });
+        }
+    }
+    Ok(tasks)
+}
+
+pub fn read_google_tasks(filename: &PathBuf) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    parse_google_tasks(&read_to_string(filename)?)
+}
+
+// Jira priorities on the 0 to 10 urgency scale
+fn jira_urgency(priority: &str) -> Option<f32> {
+    match priority.trim().to_lowercase().as_str() {
+        "highest" | "blocker" => Some(9.0),
+        "high" | "critical" => Some(7.0),
+        "medium" | "major" => Some(5.0),
+        "low" | "minor" => Some(3.0),
+        "lowest" | "trivial" => Some(1.0),
+        _ => None,
+    }
+}
+
+fn jira_done(status: &str) -> bool {
+    ["done", "closed", "resolved"].contains(&status.trim().to_lowercase().as_str())
+}
+
+// Jira CSV exports write dates like 01/Mar/24 5:00 PM
+fn parse_jira_date(input: &str) -> Option<DateTime<Utc>> {
+    let input = input.trim();
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(input, "%d/%b/%y %I:%M %p") {
+        return Some(from_local(datetime));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%d/%b/%y") {
+        return date.and_hms_opt(17, 0, 0).map(from_local);
+    }
+    parse_import_date(input)
+}
+
+// Jira issue export, either the CSV export or the JSON of a search with issues and their fields
+pub fn read_jira(filename: &PathBuf) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let text = read_to_string(filename)?;
+    if text.trim_start().starts_with('{') {
+        return parse_jira_json(&text);
+    }
+    let mut records = parse_csv(&text).into_iter();
+    let header: Vec<String> = match records.next() {
+        Some(header) => header
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let summary_column = column("summary").ok_or("Jira CSV needs a Summary column")?;
+    let (key_column, description_column) = (column("issue key"), column("description"));
+    let (priority_column, due_column, status_column) =
+        (column("priority"), column("due date"), column("status"));
+
+    let mut tasks = Vec::new();
+    for record in records {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| record.get(index))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let title = match field(Some(summary_column)) {
+            Some(title) => title,
+            None => continue,
+        };
+        tasks.push(ImportedTask {
+            external_id: field(key_column),
+            title,
+            description: field(description_column).unwrap_or_default(),
+            urgency: field(priority_column).and_then(|priority| jira_urgency(&priority)),
+            due_time: field(due_column).and_then(|due| parse_jira_date(&due)),
+            done: field(status_column).is_some_and(|status| jira_done(&status)),
+            ..Default::default()
+        });
+    }
+    Ok(tasks)
+}
+
+pub fn parse_jira_json(text: &str) -> Result<Vec<ImportedTask>, Box<dyn Error>> {
+    let data: Value = serde_json::from_str(text)?;
+    let issues = data["issues"]
+        .as_array()
+        .ok_or("Jira JSON needs an issues array")?;
+    Ok(issues
+        .iter()
+        .filter_map(|issue| {
+            let fields = &issue["fields"];
+            Some(ImportedTask {
+                external_id: issue["key"].as_str().map(str::to_string),
+                title: fields["summary"].as_str()?.to_string(),
+                description: fields["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                urgency: fields["priority"]["name"].as_str().and_then(jira_urgency),
+                due_time: fields["duedate"].as_str().and_then(parse_jira_date),
+                done: fields["status"]["name"].as_str().is_some_and(jira_done),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+// One task as printed by export <id>, or an array of them
+pub fn parse_task_json(text: &str) -> Result<Vec<Task>, Box<dyn Error>> {
+    match serde_json::from_str(text)? {
+        Value::Array(tasks) => Ok(tasks
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<Task>, _>>()?),
+        task => Ok(vec![serde_json::from_value(task)?]),
+    }
+}
+
+impl TaskManager {
+    // Tasks handed over from another task file are added as new ones, returning their IDs
+    pub fn add_tasks(&mut self, tasks: Vec<Task>) -> Vec<usize> {
+        tasks
+            .into_iter()
+            .map(|mut task| {
+                task.depends_on.clear();
+                self.push_task(task);
+                self.tasks.len() - 1
+            })
+            .collect()
+    }
+
+    // Tasks seen before from the same source are updated in place unless force_new is set
+    pub fn import_tasks(
+        &mut self,
+        source: &str,
+        imported: Vec<ImportedTask>,
+        force_new: bool,
+    ) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        for imported_task in imported {
+            let external_id = imported_task
+                .external_id
+                .as_ref()
+                .map(|id| format!("{}:{}", source, id));
+            let existing = match &external_id {
+                Some(external_id) if !force_new => self
+                    .tasks
+                    .iter()
+                    .position(|task| task.external_id.as_ref() == Some(external_id)),
+                _ => None,
+            };
+            let mut task = match existing {
+                Some(id) => self.tasks[id].clone(),
+                None => Task::new(imported_task.title.clone()),
+            };
+            task.modified_at = Some(Utc::now());
+            task.title = imported_task.title;
+            task.description = imported_task.description;
+            task.external_id = external_id;
+            if let Some(urgency) = imported_task.urgency {
+                task.urgency = urgency;
+            }
+            if imported_task.due_time.is_some() {
+                task.due_time = imported_task.due_time;
+            }
+            if imported_task.done {
+                task.status = Status::Done;
+                task.urgency = 0.0;
+            }
+            for tag in imported_task.tags {
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+            if imported_task.project.is_some() {
+                task.project = imported_task.project;
+            }
+            match existing {
+                Some(id) => {
+                    summary.updated += 1;
+                    self.update_task(id, task);
+                }
+                None => {
+                    summary.added += 1;
+                    self.push_task(task);
+                }
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_csv, parse_google_tasks, parse_jira_json, parse_md, parse_mstodo, parse_task_json,
+        parse_trello, ImportedTask,
+    };
+    use crate::config::Config;
+    use crate::filter::{Field, Filter};
+    use crate::TaskManager;
+
+    #[test]
+    fn csv_quoting() {
+        let records = parse_csv("id,title\n1,\"Hello, \"\"world\"\"\"\n2,\"two\nlines\"\n");
+        assert_eq!(records[1], vec!["1", "Hello, \"world\""]);
+        assert_eq!(records[2], vec!["2", "two\nlines"]);
+    }
+
+    #[test]
+    fn reimport_updates_existing() {
+        let mut debug_manager = TaskManager::new();
+        let imported = || {
+            vec![ImportedTask {
+                external_id: Some("7".to_string()),
+                title: "from csv".to_string(),
+                ..Default::default()
+            }]
+        };
+        debug_manager.import_tasks("csv", imported(), false);
+        let summary = debug_manager.import_tasks("csv", imported(), false);
+        assert_eq!((summary.added, summary.updated), (0, 1));
+        assert_eq!(debug_manager.tasks.len(), 1);
+        debug_manager.import_tasks("csv", imported(), true);
+        assert_eq!(debug_manager.tasks.len(), 2);
+    }
+
+    #[test]
+    fn jira_json_fields() {
+        let tasks = parse_jira_json(
+            r#"{"issues": [{"key": "ABC-1", "fields": {"summary": "Fix login",
+                "description": null, "priority": {"name": "High"}, "duedate": "2024-03-01",
+                "status": {"name": "Done"}}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tasks[0].external_id.as_deref(), Some("ABC-1"));
+        assert_eq!(tasks[0].urgency, Some(7.0));
+        assert!(tasks[0].due_time.is_some());
+        assert!(tasks[0].done);
+    }
+
+    #[test]
+    fn markdown_round_trip() {
+        let fields = Field::all(&Config::default());
+        let checklist =
+            "- [ ] Write report (due 2024-03-01 17:00)\n  two\n  lines\n- [x] Send (draft) email\n";
+        let mut debug_manager = TaskManager::new();
+        debug_manager.import_tasks("md", parse_md(checklist), false);
+        assert_eq!(debug_manager.tasks[0].description, "two\nlines");
+        assert_eq!(debug_manager.tasks[1].title, "Send (draft) email");
+        assert_eq!(
+            debug_manager.export_md(&Filter::default(), &fields),
+            checklist
+        );
+
+        let checklist = "- [ ] Inbox\n\n## thesis\n\n- [ ] Chapter two\n  - [ ] outline\n  - [x] sources\n\n## home\n\n- [x] Fix tap\n";
+        let mut debug_manager = TaskManager::new();
+        debug_manager.import_tasks("md", parse_md(checklist), false);
+        assert_eq!(debug_manager.tasks.len(), 3);
+        assert_eq!(debug_manager.tasks[0].project, None);
+        assert_eq!(debug_manager.tasks[1].project.as_deref(), Some("thesis"));
+        assert_eq!(
+            debug_manager.tasks[1].description,
+            "- [ ] outline\n- [x] sources"
+        );
+        assert_eq!(debug_manager.tasks[2].project.as_deref(), Some("home"));
+        assert_eq!(
+            debug_manager.export_md(&Filter::default(), &fields),
+            checklist
+        );
+        assert_eq!(
+            debug_manager.export_md(&Filter::default(), &[Field::Title]),
+            "- [ ] Inbox\n- [ ] Chapter two\n- [x] Fix tap\n"
+        );
+    }
+
+    #[test]
+    fn trello_cards_from_chosen_lists() {
+        let board = r#"{"lists": [{"id": "l1", "name": "Doing"}, {"id": "l2", "name": "Later"}],
+            "cards": [
+                {"id": "c1", "name": "Ship it", "desc": "", "idList": "l1", "closed": false,
+                 "due": "2024-03-01T17:00:00.000Z", "dueComplete": true,
+                 "labels": [{"name": "Urgent", "color": "red"}, {"name": "", "color": "blue"}]},
+                {"id": "c2", "name": "Someday", "idList": "l2", "closed": false},
+                {"id": "c3", "name": "Old", "idList": "l1", "closed": true}]}"#;
+        assert_eq!(parse_trello(board, &[]).unwrap().len(), 2);
+        let cards = parse_trello(board, &["doing".to_string()]).unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].external_id.as_deref(), Some("c1"));
+        assert!(cards[0].due_time.is_some() && cards[0].done);
+        assert_eq!(cards[0].project.as_deref(), Some("Doing"));
+        assert_eq!(cards[0].tags, vec!["urgent"]);
+    }
+
+    #[test]
+    fn mstodo_fields() {
+        let tasks = parse_mstodo(
+            r#"{"value": [{"id": "AAMk", "title": "Pay rent", "importance": "high",
+                "status": "completed", "body": {"content": "", "contentType": "text"},
+                "dueDateTime": {"dateTime": "2024-03-01T00:00:00.0000000", "timeZone": "UTC"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tasks[0].urgency, Some(8.0));
+        assert!(tasks[0].done);
+        assert_eq!(
+            tasks[0].due_time.unwrap().to_rfc3339(),
+            "2024-03-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn google_tasks_flatten_subtasks() {
+        let tasks = parse_google_tasks(
+            r#"{"kind": "tasks#taskLists", "items": [{"title": "My Tasks", "items": [
+                {"id": "a", "title": "Move house", "status": "needsAction"},
+                {"id": "b", "title": "Book van", "parent": "a", "status": "completed",
+                 "notes": "Saturday", "due": "2024-03-01T00:00:00.000Z"},
+                {"id": "c", "title": "Gone", "deleted": true}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].title, "Move house > Book van");
+        assert_eq!(tasks[1].description, "Saturday");
+        assert!(tasks[1].done && tasks[1].due_time.is_some());
+        assert_eq!(tasks[1].project.as_deref(), Some("My Tasks"));
+    }
+
+    #[test]
+    fn exported_task_is_added_back() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Review budget".to_string());
+        task_manager.tasks[0].description = "Q3 numbers".to_string();
+        let exported = task_manager.export_task(0).unwrap();
+        let tasks = parse_task_json(&format!("[{}, {}]", exported, exported)).unwrap();
+        assert_eq!(task_manager.add_tasks(tasks), vec![1, 2]);
+        assert_eq!(task_manager.tasks[2].description, "Q3 numbers");
+        assert_ne!(task_manager.tasks[1].uid, task_manager.tasks[2].uid);
+        assert!(parse_task_json("{\"title\": \"Missing fields\"}").is_err());
+    }
+}
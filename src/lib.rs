@@ -0,0 +1,3973 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, Utc};
+use dirs::data_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{stdin, stdout, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use term_size::dimensions;
+
+mod aliases;
+mod archive;
+mod attachments;
+mod board;
+mod burndown;
+mod business_hours;
+mod checklist;
+mod checkpoint;
+mod config;
+mod custom;
+mod dates;
+mod dependencies;
+mod doctor;
+mod dry_run;
+mod duplicates;
+mod error;
+mod events;
+mod export;
+mod filter;
+mod format;
+mod gc;
+mod github;
+mod goals;
+mod graph;
+mod history;
+mod holidays;
+mod i18n;
+mod import;
+mod links;
+mod live;
+mod log;
+mod man;
+mod merge;
+mod migrate;
+mod notes;
+mod notify;
+mod ooo;
+mod pager;
+mod pick;
+mod plan;
+mod plugins;
+mod progress;
+mod recur;
+mod reminders;
+mod remote;
+mod repair;
+mod replace;
+mod reports;
+mod review;
+mod rpc;
+mod rules;
+mod scheduled;
+mod script;
+mod search;
+mod select;
+mod server;
+mod snapshot;
+mod status;
+mod suggest;
+mod sync;
+mod tags;
+mod templates;
+mod text;
+mod theme;
+mod timeline;
+mod transaction;
+mod trash;
+mod view;
+mod virtual_tags;
+mod watch;
+use aliases::expand_aliases;
+use archive::{parse_until, ArchivedTask};
+use business_hours::WorkWeek;
+use checklist::ChecklistItem;
+use checkpoint::{list_snapshots, BEFORE_RESTORE};
+use config::{Config, ConfigWatch};
+use custom::parse_assignment;
+use dates::{format_days, format_duration, from_local, parse_day, parse_duration, to_local};
+use duplicates::Duplicate;
+use error::TaskError;
+use events::{parse_replay_time, Event, FieldValue};
+use filter::{parse_fields, Field, Filter};
+use format::RowFormat;
+use goals::WeeklyGoals;
+use history::{history_datetime, history_duration, Change};
+use i18n::tr;
+use import::parse_task_json;
+use log::parse_completed_at;
+use merge::Prefer;
+use migrate::{backup_before_migrating, is_current, migrate, SCHEMA_VERSION};
+use ooo::OutOfOffice;
+use pick::prompt;
+use remote::Remote;
+use replace::Replacement;
+use reports::{find_report, GroupBy};
+use scheduled::parse_scheduled;
+use templates::{Template, TemplateStore};
+use text::{display_width, pad, truncate};
+use theme::{urgency_bar, Element, HIGH_URGENCY, URGENCY_LEGEND};
+use transaction::Transaction;
+use trash::TrashedTask;
+use watch::WatchedFile;
+// CONSTS
+//
+// Urgencies
+const URGENCY_MULTIPLIER: f32 = 0.5;
+const DEFAULT_URGENCY: f32 = 3.0;
+const MINIMUM_URGENCY: f32 = 0.0;
+const MAXIMUM_URGENCY: f32 = 10.0;
+
+// How urgency climbs, from config on each run and again whenever it is reloaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencySettings {
+    // Only working time counts, see urgency_business_hours
+    pub work_week: Option<WorkWeek>,
+    // Minimum urgency gained each day by tasks without a due date
+    pub per_day: f32,
+    // Minimum urgency a task reaches at its due time, rising evenly from its start
+    pub at_due: f32,
+    // Urgency new tasks start with
+    pub default: f32,
+}
+
+impl Default for UrgencySettings {
+    fn default() -> Self {
+        UrgencySettings {
+            work_week: None,
+            per_day: URGENCY_MULTIPLIER,
+            at_due: MAXIMUM_URGENCY,
+            default: DEFAULT_URGENCY,
+        }
+    }
+}
+
+const DEFAULT_TERMINAL_WIDTH: usize = 95;
+// Longer projects and tags are cut off in list
+const MAX_PROJECT_WIDTH: usize = 12;
+const MAX_TAGS_WIDTH: usize = 16;
+
+// --- Arg parsing struct and enums -------
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "Taskmanager",
+    about = "Another RUST task manager",
+    after_help = man::EXAMPLES
+)]
+struct Opt {
+    #[structopt(
+        short = "q",
+        long = "quiet",
+        global = true,
+        help = "Print only what scripts need, e.g. the new ID on add"
+    )]
+    quiet: bool,
+    #[structopt(
+        short = "v",
+        long = "verbose",
+        global = true,
+        conflicts_with = "quiet",
+        help = "Show the full task after every change"
+    )]
+    verbose: bool,
+    #[structopt(
+        long = "dry-run",
+        global = true,
+        help = "Show what the command would change without saving it"
+    )]
+    dry_run: bool,
+    #[structopt(
+        long = "plain",
+        global = true,
+        help = "Labeled lines without boxes, colors, padding or cut off text, for screen readers"
+    )]
+    plain: bool,
+    #[structopt(flatten)]
+    selection: Selection,
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+// Filters given before the command, e.g. tasks --tag overdue done, pick the tasks that start,
+// stop, done and remove act on when no ID is given
+#[derive(Debug, Default, StructOpt)]
+pub struct Selection {
+    #[structopt(long = "filter", help = "Act on tasks matching a filter expression")]
+    filter: Option<String>,
+    #[structopt(
+        long = "tag",
+        allow_hyphen_values = true,
+        number_of_values = 1,
+        help = "Act on tasks with (+) or without (-) a tag, e.g. OVERDUE"
+    )]
+    tag: Vec<String>,
+    #[structopt(long = "due-before", help = "Act on tasks due before a day")]
+    due_before: Option<String>,
+}
+#[derive(Debug, StructOpt)]
+enum Command {
+    #[structopt(name = "add", alias = "a", about = "Add a new task")]
+    Add {
+        #[structopt(name = "name", required_unless = "json", help = "Name of the task")]
+        name: Option<String>,
+        #[structopt(
+            long = "json",
+            conflicts_with = "name",
+            help = "Read one task or an array of tasks as exported with export <id> from stdin"
+        )]
+        json: bool,
+        #[structopt(
+            short = "d",
+            long = "description",
+            help = "Description of task, - reads it from stdin"
+        )]
+        description: Option<String>,
+        #[structopt(short = "u", long = "urgency", help = "Urgency of task")]
+        urgency: Option<f32>,
+        #[structopt(short = "D", long = "due-time", help = "Due time of task")]
+        due_time: Option<String>,
+        #[structopt(
+            short = "e",
+            long = "estimate",
+            help = "Estimated effort, e.g. 2h or 1h30m"
+        )]
+        estimate: Option<String>,
+        #[structopt(
+            long = "soft-due",
+            help = "Allow the due date to be moved automatically"
+        )]
+        soft_due: bool,
+        #[structopt(
+            long = "remind",
+            help = "Remind this long before the due time, e.g. 1d or 2h, can be repeated"
+        )]
+        remind: Vec<String>,
+        #[structopt(
+            short = "t",
+            long = "template",
+            help = "Create the task from a saved template"
+        )]
+        template: Option<String>,
+        #[structopt(
+            long = "watch-file",
+            parse(from_os_str),
+            help = "Log activity on the task when this file changes"
+        )]
+        watch_file: Option<PathBuf>,
+        #[structopt(long = "pin-urgency", help = "Never raise the urgency automatically")]
+        pin_urgency: bool,
+        #[structopt(
+            long = "link",
+            help = "A URL for the task, e.g. its ticket or PR, can be repeated"
+        )]
+        link: Vec<String>,
+        #[structopt(
+            long = "recur",
+            help = "Repeat the task, e.g. \"FREQ=MONTHLY;BYDAY=1MO\", weekly or \"first monday of month\""
+        )]
+        recur: Option<String>,
+        #[structopt(
+            long = "scheduled",
+            help = "When to start working on the task, a day with an optional time, e.g. \"monday 14:00\""
+        )]
+        scheduled: Option<String>,
+        #[structopt(
+            long = "until",
+            help = "Last day the task is worth doing, after it the task is archived"
+        )]
+        until: Option<String>,
+        #[structopt(long = "assignee", help = "Who the task is for in a shared list")]
+        assignee: Option<String>,
+        #[structopt(
+            long = "tag",
+            number_of_values = 1,
+            help = "A tag for the task, e.g. errands, can be repeated"
+        )]
+        tag: Vec<String>,
+        #[structopt(long = "project", help = "Project the task belongs to")]
+        project: Option<String>,
+        #[structopt(
+            long = "force",
+            help = "Add the task even when an open task has a very similar title"
+        )]
+        force: bool,
+    },
+    #[structopt(name = "log", about = "Add a task that is already done")]
+    Log {
+        #[structopt(name = "name", help = "Name of the task")]
+        name: String,
+        #[structopt(
+            short = "d",
+            long = "description",
+            help = "Description of task, - reads it from stdin"
+        )]
+        description: Option<String>,
+        #[structopt(
+            long = "at",
+            help = "When it was done, a day with an optional time, e.g. \"yesterday 16:00\", now if not given"
+        )]
+        at: Option<String>,
+        #[structopt(
+            long = "spent",
+            help = "Time spent on it before it was done, counted as active time, e.g. 1h30m"
+        )]
+        spent: Option<String>,
+    },
+    #[structopt(name = "view", alias = "v", about = "View task by ID")]
+    View {
+        #[structopt(name = "id", help = "Index of task")]
+        id: usize,
+        #[structopt(short = "s", long = "short", help = "Show the task on a single line")]
+        short: bool,
+    },
+    #[structopt(name = "list", alias = "ls", about = "List all the tasks")]
+    List(ListOptions),
+    #[structopt(
+        name = "report",
+        about = "List tasks with a report from config, options given here add to it"
+    )]
+    Report {
+        #[structopt(name = "name", help = "Name of the report, list is the default one")]
+        name: String,
+        #[structopt(flatten)]
+        options: ListOptions,
+    },
+    #[structopt(
+        name = "watch",
+        about = "Keep the list on screen, redrawn on an interval or when tasks change"
+    )]
+    Watch {
+        #[structopt(
+            short = "n",
+            long = "interval",
+            default_value = "5",
+            help = "Seconds between redraws"
+        )]
+        interval: u64,
+        #[structopt(flatten)]
+        options: ListOptions,
+    },
+    #[structopt(name = "export", about = "Export tasks to another format")]
+    Export {
+        #[structopt(
+            name = "id",
+            help = "Print this one task as JSON to hand over, read back with add --json"
+        )]
+        id: Option<usize>,
+        #[structopt(long = "format", possible_values = &["csv", "json", "ics", "org", "md"], required_unless = "id", help = "Output format")]
+        format: Option<String>,
+        #[structopt(short = "f", long = "filter", help = "Only export matching tasks")]
+        filter: Option<String>,
+        #[structopt(long = "fields", help = "Comma separated fields to export")]
+        fields: Option<String>,
+        #[structopt(
+            short = "o",
+            long = "output",
+            parse(from_os_str),
+            help = "Write to a file instead of stdout"
+        )]
+        output: Option<PathBuf>,
+    },
+    #[structopt(name = "search", about = "Search task titles and descriptions")]
+    Search {
+        #[structopt(name = "query", required = true, help = "Words to search for")]
+        query: Vec<String>,
+        #[structopt(long = "include-trash", help = "Also search removed tasks")]
+        include_trash: bool,
+        #[structopt(long = "include-archived", help = "Also search archived tasks")]
+        include_archived: bool,
+    },
+    #[structopt(
+        name = "replace",
+        about = "Replace text in the titles and descriptions of matching tasks"
+    )]
+    Replace {
+        #[structopt(short = "f", long = "filter", help = "Only change matching tasks")]
+        filter: Option<String>,
+        #[structopt(long = "find", required_unless = "undo", help = "Text to find")]
+        find: Option<String>,
+        #[structopt(
+            long = "replace",
+            default_value = "",
+            help = "Text to put in its place"
+        )]
+        replace: String,
+        #[structopt(short = "y", long = "yes", help = "Don't ask for confirmation")]
+        yes: bool,
+        #[structopt(long = "undo", conflicts_with = "find", help = "Undo the last replace")]
+        undo: bool,
+    },
+    #[structopt(name = "next", about = "Suggest what to work on next")]
+    Next {
+        #[structopt(name = "n", default_value = "3", help = "Number of tasks to suggest")]
+        count: usize,
+    },
+    #[structopt(name = "count", about = "Print the number of matching tasks")]
+    Count {
+        #[structopt(
+            name = "filter",
+            help = "Only count matching tasks, e.g. \"status:open\""
+        )]
+        filter: Option<String>,
+    },
+    #[structopt(name = "edit", alias = "e", about = "Edit a tasks values by ID")]
+    Edit {
+        #[structopt(name = "id", about = "ID of task")]
+        id: usize,
+        #[structopt(short = "n", long = "name", help = "Name of the task")]
+        name: Option<String>,
+        #[structopt(
+            short = "d",
+            long = "description",
+            help = "Description of task, - reads it from stdin"
+        )]
+        description: Option<String>,
+        #[structopt(short = "u", long = "urgency", help = "Urgency of task")]
+        urgency: Option<f32>,
+        #[structopt(short = "D", long = "due-time", help = "Due time of task")]
+        due_time: Option<String>,
+        #[structopt(
+            short = "e",
+            long = "estimate",
+            help = "Estimated effort, e.g. 2h or 1h30m"
+        )]
+        estimate: Option<String>,
+        #[structopt(
+            long = "soft-due",
+            help = "Allow the due date to be moved automatically"
+        )]
+        soft_due: bool,
+        #[structopt(
+            long = "hard-due",
+            help = "Never move the due date automatically",
+            conflicts_with = "soft-due"
+        )]
+        hard_due: bool,
+        #[structopt(
+            long = "remind",
+            help = "Remind this long before the due time, e.g. 1d or 2h, can be repeated"
+        )]
+        remind: Vec<String>,
+        #[structopt(
+            long = "clear-reminders",
+            help = "Remove all reminders before adding any new ones"
+        )]
+        clear_reminders: bool,
+        #[structopt(
+            long = "watch-file",
+            parse(from_os_str),
+            help = "Log activity on the task when this file changes"
+        )]
+        watch_file: Option<PathBuf>,
+        #[structopt(
+            long = "unwatch",
+            help = "Stop watching the linked file",
+            conflicts_with = "watch-file"
+        )]
+        unwatch: bool,
+        #[structopt(long = "pin-urgency", help = "Never raise the urgency automatically")]
+        pin_urgency: bool,
+        #[structopt(
+            long = "unpin-urgency",
+            help = "Let the urgency rise with age and due date again",
+            conflicts_with = "pin-urgency"
+        )]
+        unpin_urgency: bool,
+        #[structopt(long = "json", help = "Print the changes as a JSON patch")]
+        json: bool,
+        #[structopt(
+            long = "link",
+            help = "A URL for the task, e.g. its ticket or PR, can be repeated"
+        )]
+        link: Vec<String>,
+        #[structopt(
+            long = "clear-links",
+            help = "Remove all links before adding any new ones"
+        )]
+        clear_links: bool,
+        #[structopt(
+            long = "set",
+            help = "Set a custom field from config, e.g. customer=ACME, empty to remove it"
+        )]
+        set: Vec<String>,
+        #[structopt(
+            long = "depends-on",
+            help = "ID of a task this one waits on, can be repeated"
+        )]
+        depends_on: Vec<usize>,
+        #[structopt(
+            long = "clear-depends",
+            help = "Remove all dependencies before adding any new ones"
+        )]
+        clear_depends: bool,
+        #[structopt(
+            long = "recur",
+            help = "Repeat the task, e.g. \"FREQ=MONTHLY;BYDAY=1MO\", weekly or \"first monday of month\""
+        )]
+        recur: Option<String>,
+        #[structopt(
+            long = "no-recur",
+            help = "Stop repeating the task",
+            conflicts_with = "recur"
+        )]
+        no_recur: bool,
+        #[structopt(
+            long = "scheduled",
+            help = "When to start working on the task, a day with an optional time, e.g. \"monday 14:00\""
+        )]
+        scheduled: Option<String>,
+        #[structopt(
+            long = "unschedule",
+            help = "Remove the scheduled time",
+            conflicts_with = "scheduled"
+        )]
+        unschedule: bool,
+        #[structopt(
+            long = "until",
+            help = "Last day the task is worth doing, after it the task is archived"
+        )]
+        until: Option<String>,
+        #[structopt(
+            long = "no-until",
+            help = "Keep the task however long it takes",
+            conflicts_with = "until"
+        )]
+        no_until: bool,
+        #[structopt(long = "assignee", help = "Who the task is for in a shared list")]
+        assignee: Option<String>,
+        #[structopt(
+            long = "unassign",
+            help = "Remove the assignee",
+            conflicts_with = "assignee"
+        )]
+        unassign: bool,
+        #[structopt(
+            long = "tag",
+            allow_hyphen_values = true,
+            number_of_values = 1,
+            help = "Add a tag, or remove it with -tag, can be repeated"
+        )]
+        tag: Vec<String>,
+        #[structopt(long = "project", help = "Project the task belongs to")]
+        project: Option<String>,
+        #[structopt(
+            long = "no-project",
+            help = "Take the task out of its project",
+            conflicts_with = "project"
+        )]
+        no_project: bool,
+        #[structopt(long = "progress", help = "How far along the task is, in percent")]
+        progress: Option<u8>,
+        #[structopt(
+            long = "no-progress",
+            help = "Remove the progress, so it is taken from the tasks this one waits on",
+            conflicts_with = "progress"
+        )]
+        no_progress: bool,
+    },
+    #[structopt(name = "open", about = "Open a link or attachment of the task")]
+    Open {
+        id: usize,
+        #[structopt(
+            short = "n",
+            long = "link",
+            default_value = "0",
+            help = "Which link to open, counting from 0"
+        )]
+        link: usize,
+        #[structopt(
+            short = "a",
+            long = "attachment",
+            help = "Open this attachment instead of a link, counting from 0"
+        )]
+        attachment: Option<usize>,
+    },
+    #[structopt(
+        name = "notes",
+        about = "Open the task's markdown notes in $EDITOR, made the first time"
+    )]
+    Notes { id: usize },
+    #[structopt(name = "attach", about = "Attach a file to a task")]
+    Attach {
+        id: usize,
+        #[structopt(name = "path", parse(from_os_str), help = "File to attach")]
+        path: PathBuf,
+        #[structopt(
+            long = "copy",
+            help = "Keep a copy of the file with the task data instead of referring to it"
+        )]
+        copy: bool,
+    },
+    #[structopt(name = "start", about = "Set a task to active by ID")]
+    Start { id: Option<usize> },
+    #[structopt(name = "stop", about = "Set a task to inactive by ID")]
+    Stop { id: Option<usize> },
+    #[structopt(name = "done", about = "Set a task to Complete by ID")]
+    Done { id: Option<usize> },
+    #[structopt(
+        name = "remove",
+        alias = "rm",
+        about = "Move a task to the trash by ID"
+    )]
+    Remove {
+        id: Option<usize>,
+        #[structopt(short = "f", long = "force", help = "Don't ask for confirmation")]
+        force: bool,
+    },
+    #[structopt(name = "check", about = "Manage the checklist inside a task")]
+    Check {
+        #[structopt(subcommand)]
+        command: CheckCommand,
+    },
+    #[structopt(name = "trash", about = "Manage removed tasks")]
+    Trash {
+        #[structopt(subcommand)]
+        command: TrashCommand,
+    },
+    #[structopt(
+        name = "gc",
+        about = "Delete or archive tasks completed a while ago, from the list and the archive"
+    )]
+    Gc {
+        #[structopt(
+            long = "older-than",
+            help = "How long ago the tasks were completed, e.g. 90d or 12w"
+        )]
+        older_than: String,
+        #[structopt(
+            long = "archive",
+            help = "Move them to the archive instead of deleting"
+        )]
+        archive: bool,
+    },
+    #[structopt(
+        name = "snapshot",
+        about = "Save a full copy of the tasks and roll back to it"
+    )]
+    Snapshot {
+        #[structopt(subcommand)]
+        command: SnapshotCommand,
+    },
+    #[structopt(name = "archive", about = "Manage archived tasks")]
+    Archive {
+        #[structopt(subcommand)]
+        command: ArchiveCommand,
+    },
+    #[structopt(name = "plan", about = "Plan a task for a specific day by ID")]
+    Plan {
+        id: usize,
+        #[structopt(
+            long = "on",
+            help = "Day to work on it, e.g. friday or d/m/y",
+            required_unless = "clear"
+        )]
+        on: Option<String>,
+        #[structopt(long = "clear", help = "Remove the plan")]
+        clear: bool,
+    },
+    #[structopt(
+        name = "pick",
+        about = "Find a task by fuzzy search and view, complete, start or edit it"
+    )]
+    Pick {
+        #[structopt(
+            name = "action",
+            default_value = "view",
+            possible_values = &["view", "done", "start", "edit"],
+            help = "What to do with the picked task, edit opens $EDITOR"
+        )]
+        action: String,
+    },
+    #[structopt(name = "today", about = "Show tasks planned and due today")]
+    Today,
+    #[structopt(
+        name = "week",
+        about = "Show tasks planned, scheduled and due over the next seven days"
+    )]
+    Week,
+    #[structopt(
+        name = "rules",
+        about = "List the automation rules from the config directory and what they match"
+    )]
+    Rules,
+    #[structopt(name = "history", about = "Show the change log of a task by ID")]
+    History { id: usize },
+    #[structopt(name = "clone", about = "Copy a task by ID")]
+    Clone {
+        #[structopt(name = "id", help = "ID of task to copy")]
+        id: usize,
+        #[structopt(short = "n", long = "name", help = "Name of the new task")]
+        name: Option<String>,
+    },
+    #[structopt(
+        name = "current",
+        about = "Show active tasks and how long they have run"
+    )]
+    Current,
+    #[structopt(
+        name = "workload",
+        about = "Compare open estimates with available time"
+    )]
+    Workload {
+        #[structopt(
+            long = "until",
+            default_value = "friday",
+            help = "Last day to plan for"
+        )]
+        until: String,
+    },
+    #[structopt(
+        name = "notify",
+        about = "Send notifications for overdue and due tasks"
+    )]
+    Notify {
+        #[structopt(long = "digest", help = "Group notifications into a single summary")]
+        digest: bool,
+    },
+    #[structopt(name = "ooo", about = "Manage out of office periods")]
+    Ooo {
+        #[structopt(subcommand)]
+        command: OooCommand,
+    },
+    #[structopt(
+        name = "doctor",
+        about = "Show where data is stored and check it for problems"
+    )]
+    Doctor,
+    #[structopt(
+        name = "status",
+        about = "Show the active task and what is due, quick enough for a shell prompt"
+    )]
+    Status {
+        #[structopt(long = "short", help = "One line for a prompt or tmux status line")]
+        short: bool,
+    },
+    #[structopt(
+        name = "repair",
+        about = "Salvage what can be read from a damaged data file, keeping the original"
+    )]
+    Repair,
+    #[structopt(name = "goal", about = "Set and check weekly goals")]
+    Goal {
+        #[structopt(subcommand)]
+        command: GoalCommand,
+    },
+    #[structopt(name = "import", about = "Import tasks from another tool")]
+    Import {
+        #[structopt(name = "file", parse(from_os_str), help = "File to import")]
+        file: PathBuf,
+        #[structopt(long = "from", default_value = "csv", possible_values = &["csv", "jira", "md", "trello", "mstodo", "google-tasks"], help = "Format of the file")]
+        from: String,
+        #[structopt(
+            long = "force-new",
+            help = "Always create new tasks, even if imported before"
+        )]
+        force_new: bool,
+        #[structopt(
+            long = "merge",
+            help = "Merge another task data file, e.g. a copy synced from another machine"
+        )]
+        merge: bool,
+        #[structopt(
+            long = "prefer",
+            default_value = "newer",
+            possible_values = &["newer", "local", "remote"],
+            help = "Which side wins when a task changed in both files"
+        )]
+        prefer: String,
+        #[structopt(
+            long = "list",
+            help = "Only import Trello cards from this list, can be repeated"
+        )]
+        list: Vec<String>,
+    },
+    #[structopt(name = "serve", about = "Serve the tasks as a JSON API on localhost")]
+    Serve {
+        #[structopt(
+            short = "p",
+            long = "port",
+            default_value = "8080",
+            help = "Port to listen on"
+        )]
+        port: u16,
+    },
+    #[structopt(
+        name = "sync-server",
+        about = "Serve the tasks for other devices to sync with, see sync remote"
+    )]
+    SyncServer {
+        #[structopt(
+            short = "p",
+            long = "port",
+            default_value = "8081",
+            help = "Port to listen on"
+        )]
+        port: u16,
+        #[structopt(
+            long = "token",
+            help = "Token clients must send, sync_token in config by default"
+        )]
+        token: Option<String>,
+    },
+    #[structopt(
+        name = "rpc",
+        about = "Speak JSON-RPC on stdin and stdout, for editor plugins"
+    )]
+    Rpc,
+    #[structopt(
+        name = "graph",
+        about = "Draw the tasks and their dependencies as a graph"
+    )]
+    Graph {
+        #[structopt(long = "format", default_value = "dot", possible_values = &["dot"], help = "Output format")]
+        format: String,
+        #[structopt(short = "f", long = "filter", help = "Only draw matching tasks")]
+        filter: Option<String>,
+    },
+    #[structopt(
+        name = "board",
+        about = "Show the tasks in lanes by status, side by side"
+    )]
+    Board {
+        #[structopt(
+            long = "by",
+            help = "Lanes by due-week, project, tag or a custom field instead of status"
+        )]
+        by: Option<String>,
+        #[structopt(short = "f", long = "filter", help = "Only show matching tasks")]
+        filter: Option<String>,
+        #[structopt(
+            short = "w",
+            long = "width",
+            help = "Lay out for this many columns instead of the terminal width"
+        )]
+        width: Option<usize>,
+    },
+    #[structopt(
+        name = "timeline",
+        about = "Draw tasks as bars from their start or scheduled day to their due date"
+    )]
+    Timeline {
+        #[structopt(long = "from", default_value = "today", help = "First day to draw")]
+        from: String,
+        #[structopt(
+            long = "to",
+            help = "Last day to draw, two weeks after from if not given"
+        )]
+        to: Option<String>,
+        #[structopt(short = "f", long = "filter", help = "Only draw matching tasks")]
+        filter: Option<String>,
+        #[structopt(
+            short = "w",
+            long = "width",
+            help = "Lay out for this many columns instead of the terminal width"
+        )]
+        width: Option<usize>,
+    },
+    #[structopt(
+        name = "replay",
+        about = "Show the tasks as they were at a time, rebuilt from the event log"
+    )]
+    Replay {
+        #[structopt(
+            long = "until",
+            help = "Day, or day and time like \"monday 14:00\", now by default"
+        )]
+        until: Option<String>,
+        #[structopt(
+            long = "events",
+            help = "List the events replayed instead of the tasks"
+        )]
+        events: bool,
+    },
+    #[structopt(
+        name = "undo",
+        about = "Undo the changes the last command made, run again to bring them back"
+    )]
+    Undo,
+    #[structopt(
+        name = "burndown",
+        about = "Chart how many tasks were open on each day of the last weeks"
+    )]
+    Burndown {
+        #[structopt(
+            long = "weeks",
+            default_value = "4",
+            help = "How many weeks back to chart, up to 104"
+        )]
+        weeks: u32,
+        #[structopt(
+            long = "estimate",
+            help = "Chart the hours open tasks are estimated to take instead of how many there are"
+        )]
+        estimate: bool,
+    },
+    #[structopt(
+        name = "review",
+        about = "Go through stale tasks one at a time to keep, snooze, reprioritize or delete them"
+    )]
+    Review {
+        #[structopt(
+            long = "days",
+            help = "Tasks unchanged for longer than this are stale, stale_after_days in config by default"
+        )]
+        days: Option<i64>,
+    },
+    #[structopt(name = "sync", about = "Sync tasks with other services")]
+    Sync {
+        #[structopt(subcommand)]
+        command: SyncCommand,
+    },
+    #[structopt(name = "template", about = "Manage task templates")]
+    Template {
+        #[structopt(subcommand)]
+        command: TemplateCommand,
+    },
+    #[structopt(name = "gen-man", about = "Print a man page for tasks, for packaging")]
+    GenMan {
+        #[structopt(
+            short = "o",
+            long = "output",
+            parse(from_os_str),
+            help = "Write it to a file, e.g. tasks.1"
+        )]
+        output: Option<PathBuf>,
+    },
+    // Any other name runs the task-<name> plugin from PATH
+    #[structopt(external_subcommand)]
+    External(Vec<String>),
+}
+#[derive(Debug, StructOpt)]
+enum TrashCommand {
+    #[structopt(name = "list", about = "List removed tasks")]
+    List,
+    #[structopt(name = "restore", about = "Restore a removed task by trash ID")]
+    Restore { id: usize },
+}
+#[derive(Debug, StructOpt)]
+enum CheckCommand {
+    #[structopt(name = "add", about = "Add an item to the end of the checklist")]
+    Add { id: usize, text: String },
+    #[structopt(name = "done", about = "Check off an item, counting from 0")]
+    Done { id: usize, item: usize },
+    #[structopt(name = "undo", about = "Uncheck an item, counting from 0")]
+    Undo { id: usize, item: usize },
+    #[structopt(
+        name = "remove",
+        alias = "rm",
+        about = "Remove an item, counting from 0"
+    )]
+    Remove { id: usize, item: usize },
+}
+#[derive(Debug, StructOpt)]
+enum SnapshotCommand {
+    #[structopt(name = "create", about = "Save the tasks as they are now")]
+    Create {
+        #[structopt(
+            name = "label",
+            help = "Name to restore it by, the current time if not given"
+        )]
+        label: Option<String>,
+    },
+    #[structopt(
+        name = "restore",
+        about = "Replace the tasks with a snapshot, saving the current ones as before-restore"
+    )]
+    Restore { label: String },
+    #[structopt(name = "list", about = "List saved snapshots")]
+    List,
+}
+#[derive(Debug, StructOpt)]
+enum ArchiveCommand {
+    #[structopt(name = "list", about = "List archived tasks")]
+    List,
+    #[structopt(name = "restore", about = "Restore an archived task by archive ID")]
+    Restore { id: usize },
+}
+#[derive(Debug, StructOpt)]
+enum OooCommand {
+    #[structopt(name = "start", about = "Start an out of office period")]
+    Start {
+        #[structopt(name = "date", help = "First day away")]
+        date: String,
+        #[structopt(long = "until", help = "Last day away")]
+        until: String,
+    },
+    #[structopt(name = "end", about = "End the current out of office period early")]
+    End,
+    #[structopt(name = "status", about = "Show the current out of office period")]
+    Status,
+}
+#[derive(Debug, StructOpt)]
+enum GoalCommand {
+    #[structopt(name = "done", about = "Complete a number of tasks each week")]
+    Done {
+        #[structopt(name = "count", help = "Tasks to complete")]
+        count: u32,
+    },
+    #[structopt(
+        name = "time",
+        about = "Spend an amount of time on active tasks each week"
+    )]
+    Time {
+        #[structopt(name = "time", help = "Time to spend, e.g. 6h or 1h30m")]
+        time: String,
+        #[structopt(
+            name = "tag",
+            help = "Only count time on tasks with this tag, e.g. +thesis"
+        )]
+        tag: Option<String>,
+    },
+    #[structopt(name = "clear", about = "Remove all weekly goals")]
+    Clear,
+    #[structopt(name = "status", about = "Show progress towards this week's goals")]
+    Status,
+}
+#[derive(Debug, StructOpt)]
+enum SyncCommand {
+    #[structopt(
+        name = "github",
+        about = "Import open issues assigned to you with labels as tags, and close issues of done tasks, using gh"
+    )]
+    Github {
+        #[structopt(long = "repo", help = "Repository, e.g. owner/name")]
+        repo: String,
+    },
+    #[structopt(
+        name = "remote",
+        about = "Sync with a task sync-server, the newer change to a task wins"
+    )]
+    Remote {
+        #[structopt(help = "Address of the server, e.g. http://192.168.1.20:8081")]
+        url: String,
+        #[structopt(
+            long = "token",
+            help = "Token of the server, sync_token in config by default"
+        )]
+        token: Option<String>,
+    },
+}
+#[derive(Debug, StructOpt)]
+enum TemplateCommand {
+    #[structopt(name = "save", about = "Save a task as a named template")]
+    Save {
+        #[structopt(name = "name", help = "Name of the template")]
+        name: String,
+        #[structopt(name = "id", help = "ID of task to copy")]
+        id: usize,
+    },
+    #[structopt(name = "list", about = "List saved templates")]
+    List,
+    #[structopt(name = "remove", about = "Remove a template by name")]
+    Remove {
+        #[structopt(name = "name", help = "Name of the template")]
+        name: String,
+    },
+}
+
+// Options of list, also taken by report to narrow or change a saved report
+#[derive(Debug, Clone, Default, StructOpt)]
+pub struct ListOptions {
+    #[structopt(
+        short = "f",
+        long = "filter",
+        help = "Only list matching tasks, e.g. \"status:open due<friday urgency>5\""
+    )]
+    filter: Option<String>,
+    #[structopt(
+        short = "t",
+        long = "tag",
+        allow_hyphen_values = true,
+        number_of_values = 1,
+        help = "Only list tasks with (+) or without (-) a tag, e.g. errands, or a virtual one: OVERDUE, DUETODAY, ACTIVE, DONE, PLANNED, PINNED, STALE, WAITING or BLOCKED"
+    )]
+    tag: Vec<String>,
+    #[structopt(
+        long = "mine",
+        help = "Only list tasks assigned to you, see user in config"
+    )]
+    mine: bool,
+    #[structopt(
+        long = "stale",
+        help = "Only list open tasks unchanged for this many days, stale_after_days in config if not given"
+    )]
+    stale: Option<Option<i64>>,
+    #[structopt(
+        long = "assignee",
+        help = "Only list tasks assigned to this person, or none for unassigned tasks"
+    )]
+    assignee: Option<String>,
+    #[structopt(
+        long = "project",
+        help = "Only list tasks in this project, or none for tasks without one"
+    )]
+    project: Option<String>,
+    #[structopt(long = "fields", help = "Comma separated columns, e.g. id,title,due")]
+    fields: Option<String>,
+    #[structopt(
+        long = "format",
+        help = "Row template or a preset from config, e.g. \"{id} {urgency:>4} {due:%d/%m} {title}\"",
+        conflicts_with = "fields"
+    )]
+    format: Option<String>,
+    #[structopt(
+        short = "s",
+        long = "sort",
+        possible_values = &["urgency", "age", "modified", "due"],
+        help = "Order of the listed tasks, urgency unless the report sets one"
+    )]
+    sort: Option<String>,
+    #[structopt(
+        short = "w",
+        long = "width",
+        help = "Lay out for this many columns instead of the terminal width"
+    )]
+    width: Option<usize>,
+    #[structopt(long = "due", help = "Always show the due column")]
+    due: bool,
+    #[structopt(
+        long = "no-due",
+        help = "Never show the due column",
+        conflicts_with = "due"
+    )]
+    no_due: bool,
+    #[structopt(long = "tags", help = "Always show the project and tags columns")]
+    tags: bool,
+    #[structopt(
+        long = "no-tags",
+        help = "Never show the project and tags columns",
+        conflicts_with = "tags"
+    )]
+    no_tags: bool,
+    #[structopt(short = "l", long = "limit", help = "List at most this many tasks")]
+    limit: Option<usize>,
+    #[structopt(
+        long = "offset",
+        default_value = "0",
+        help = "Skip this many tasks first"
+    )]
+    offset: usize,
+    #[structopt(
+        long = "at",
+        help = "List the tasks as they were at the end of a past day, read only"
+    )]
+    at: Option<String>,
+    #[structopt(
+        short = "g",
+        long = "group-by",
+        help = "Split the list into sections by status, due-week, project, tag or a custom field"
+    )]
+    group_by: Option<String>,
+    #[structopt(
+        long = "raw",
+        help = "Tab separated values without header, padding or truncation, for scripts",
+        conflicts_with = "format"
+    )]
+    raw: bool,
+    #[structopt(
+        long = "bars",
+        help = "Draw a bar next to each urgency, with a legend below"
+    )]
+    bars: bool,
+}
+
+impl Command {
+    // Commands that change tasks, these are followed by a one line summary
+    fn mutates(&self) -> bool {
+        match self {
+            Command::Edit { json, .. } => !json,
+            Command::Pick { action } => action != "view",
+            Command::Snapshot { command } => matches!(command, SnapshotCommand::Restore { .. }),
+            Command::Trash { command } => matches!(command, TrashCommand::Restore { .. }),
+            Command::Archive { command } => matches!(command, ArchiveCommand::Restore { .. }),
+            Command::Add { .. }
+            | Command::Log { .. }
+            | Command::Start { .. }
+            | Command::Stop { .. }
+            | Command::Done { .. }
+            | Command::Remove { .. }
+            | Command::Plan { .. }
+            | Command::Clone { .. }
+            | Command::Replace { .. }
+            | Command::Gc { .. }
+            | Command::Sync { .. }
+            | Command::Attach { .. }
+            | Command::Check { .. }
+            | Command::Review { .. }
+            | Command::Notes { .. }
+            | Command::Import { .. } => true,
+            _ => false,
+        }
+    }
+
+    // Commands that only show the tasks, these go without the remote copy rather than wait on the
+    // network for it
+    fn reads_only(&self) -> bool {
+        matches!(
+            self,
+            Command::View { .. }
+                | Command::List(_)
+                | Command::Report { .. }
+                | Command::Export { .. }
+                | Command::Search { .. }
+                | Command::Next { .. }
+                | Command::Count { .. }
+                | Command::Today
+                | Command::Week
+                | Command::History { .. }
+                | Command::Current
+                | Command::Workload { .. }
+                | Command::Graph { .. }
+                | Command::Board { .. }
+                | Command::Timeline { .. }
+                | Command::Burndown { .. }
+        )
+    }
+
+    // Commands that write outside the task data or reach other programs, which a dry run
+    // can't hold back
+    fn writes_elsewhere(&self) -> bool {
+        match self {
+            Command::Attach { copy, .. } => *copy,
+            Command::Snapshot { command } => !matches!(command, SnapshotCommand::List),
+            Command::Template { command } => !matches!(command, TemplateCommand::List),
+            Command::Sync { command } => matches!(command, SyncCommand::Remote { .. }),
+            Command::Notes { .. }
+            | Command::Notify { .. }
+            | Command::Serve { .. }
+            | Command::SyncServer { .. }
+            | Command::Rpc
+            | Command::Repair => true,
+            _ => false,
+        }
+    }
+}
+
+// ------------Structs and Enums ---------------
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    title: String,
+    description: String,
+    status: Status,
+    // Urgency as set by the user, only ever changed by edits
+    urgency: f32,
+    // Base urgency raised by age or due time, recalculated on every run and never saved
+    #[serde(skip)]
+    effective_urgency: f32,
+    // Times are UTC, naive ones in older data files are read as local time
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    due_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    active_since: Option<DateTime<Utc>>,
+    #[serde(default, with = "dates::serde_seconds")]
+    estimate: Option<Duration>,
+    // Soft due dates may be moved automatically, e.g. when out of office
+    #[serde(default)]
+    soft_due: bool,
+    // Source and ID of the task in the tool it was imported from, e.g. csv:42
+    #[serde(default)]
+    external_id: Option<String>,
+    #[serde(default)]
+    history: Vec<Change>,
+    // Older data files only have start_time, see migrate
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    modified_at: Option<DateTime<Utc>>,
+    // Day the task is planned to be worked on, independent of the due date
+    #[serde(default)]
+    planned_on: Option<NaiveDate>,
+    // Days the plan has been carried forward unfinished
+    #[serde(default)]
+    rollovers: u32,
+    #[serde(default)]
+    watch_file: Option<WatchedFile>,
+    // Pinned urgencies are never raised above the base urgency
+    #[serde(default)]
+    urgency_pinned: bool,
+    // Stays the same when the list is sorted, unlike the IDs shown in lists, 0 until assigned
+    #[serde(default)]
+    uid: u64,
+    #[serde(default)]
+    links: Vec<String>,
+    #[serde(default)]
+    attachments: Vec<PathBuf>,
+    // Values of the custom fields declared in config, by field name
+    #[serde(default)]
+    custom: BTreeMap<String, String>,
+    // Uids of the tasks this one waits on
+    #[serde(default)]
+    depends_on: Vec<u64>,
+    // RRULE for repeating tasks, the next occurrence is added when this one is done
+    #[serde(default)]
+    recur: Option<String>,
+    // When work on the task is meant to start, until then its urgency isn't raised
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    scheduled: Option<DateTime<Utc>>,
+    // Open tasks are archived once this passes, e.g. buying tickets before a sale ends
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    until: Option<DateTime<Utc>>,
+    // Who the task is for when several people share the task file
+    #[serde(default)]
+    assignee: Option<String>,
+    // Lowercase labels such as errands, see tags.rs. Uppercase tags like OVERDUE are virtual
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    project: Option<String>,
+    // Percent done as set by the user
+    #[serde(default)]
+    progress: Option<u8>,
+    // Progress as set, or taken from the tasks this one waits on, recalculated on every run
+    #[serde(skip)]
+    effective_progress: Option<u8>,
+    // Some task this one depends on is still open, recalculated on every run
+    #[serde(skip)]
+    blocked: bool,
+    #[serde(default)]
+    checklist: Vec<ChecklistItem>,
+    // Markdown file for notes too long for the description, see notes
+    #[serde(default)]
+    notes: Option<PathBuf>,
+    // How long before the due time notify reminds of the task, longest first
+    #[serde(default, with = "dates::serde_seconds_list")]
+    reminders: Vec<Duration>,
+    // When notify last sent a reminder, each reminder goes off once
+    #[serde(default, deserialize_with = "dates::serde_utc::deserialize_option")]
+    reminded: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TaskManager {
+    // Schema of the data file, see migrate
+    #[serde(default)]
+    version: u32,
+    // Given to the next new task as its uid
+    #[serde(default)]
+    next_uid: u64,
+    tasks: Vec<Task>,
+    #[serde(default)]
+    out_of_office: Option<OutOfOffice>,
+    #[serde(default)]
+    trash: Vec<TrashedTask>,
+    // Done tasks moved out of the list by archive_done_after_days
+    #[serde(default)]
+    archive: Vec<ArchivedTask>,
+    #[serde(default)]
+    goals: WeeklyGoals,
+    #[serde(default)]
+    last_replace: Option<Replacement>,
+    // From config on each run, see UrgencySettings
+    #[serde(skip)]
+    urgency: UrgencySettings,
+    // Events logged after the last snapshot in events.jsonl, so saving never reads the log
+    #[serde(default)]
+    events_since_snapshot: usize,
+    // seq of the last event logged when the file was written and the length of the log then,
+    // anything logged after that is replayed on load
+    #[serde(default)]
+    logged_events: u64,
+    #[serde(default)]
+    event_log_length: u64,
+    // Events logged since the file was written, see save_journaled
+    #[serde(skip)]
+    unsaved_events: usize,
+    // The typed events applied since the tasks were read, appended to events.jsonl on save
+    #[serde(skip)]
+    pending_events: Vec<Event>,
+    // What the changes made on every run came to, see end_housekeeping
+    #[serde(skip)]
+    housekeeping: Vec<Event>,
+    // The tasks as read, changes not made through an event are logged as the difference to them
+    #[serde(skip)]
+    loaded_tasks: Option<Vec<Task>>,
+    // Everything else as read, changes to it are logged whole
+    #[serde(skip)]
+    loaded_state: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    Inactive,
+    Active,
+    Done,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Inactive => "Inactive",
+            Status::Active => "Active",
+            Status::Done => "Done",
+        }
+    }
+}
+// ------------- Implimentations ----------------
+impl Task {
+    fn new(title: String) -> Self {
+        let now = Utc::now();
+        Task {
+            title,
+            description: String::new(),
+            status: Status::Inactive,
+            urgency: DEFAULT_URGENCY,
+            effective_urgency: DEFAULT_URGENCY,
+            start_time: Some(now),
+            due_time: None,
+            active_since: None,
+            estimate: None,
+            soft_due: false,
+            external_id: None,
+            history: Vec::new(),
+            created_at: Some(now),
+            modified_at: Some(now),
+            planned_on: None,
+            rollovers: 0,
+            watch_file: None,
+            urgency_pinned: false,
+            uid: 0,
+            links: Vec::new(),
+            attachments: Vec::new(),
+            custom: BTreeMap::new(),
+            depends_on: Vec::new(),
+            recur: None,
+            scheduled: None,
+            until: None,
+            assignee: None,
+            tags: Vec::new(),
+            project: None,
+            progress: None,
+            effective_progress: None,
+            blocked: false,
+            checklist: Vec::new(),
+            notes: None,
+            reminders: Vec::new(),
+            reminded: None,
+        }
+    }
+}
+
+impl Task {
+    fn calculate_urgency(&mut self, now: DateTime<Utc>, settings: &UrgencySettings) {
+        self.effective_urgency = self.urgency;
+        // Tasks waiting for their scheduled time keep their base urgency
+        if self.status == Status::Done || self.urgency_pinned || self.is_waiting(now) {
+            return;
+        }
+        let start_time = match self.start_time {
+            Some(start_time) => start_time,
+            None => return,
+        };
+        let minimum_urgency = match self.due_time {
+            Some(due_time) => {
+                // Calculate ratio from start to due-time and set minimum urgency
+                let business_ratio = settings
+                    .work_week
+                    .as_ref()
+                    .and_then(|week| week.due_ratio(start_time, due_time, now));
+                let difference_difference_ratio: f32 = match business_ratio {
+                    Some(ratio) => ratio,
+                    None => {
+                        let total_time_difference = due_time - start_time;
+                        let time_difference_since_start_time = now - start_time;
+                        time_difference_since_start_time.num_seconds() as f32
+                            / total_time_difference.num_seconds() as f32
+                    }
+                };
+                // Intentially by design to let overdue projects go above urgency 10
+                difference_difference_ratio * settings.at_due
+            }
+            None => {
+                // Calculate Days since task to find a minimum urgency, or since it was scheduled
+                let since = self
+                    .scheduled
+                    .map_or(start_time, |scheduled| scheduled.max(start_time));
+                let days_difference = (now - since).num_days();
+                let minimum_urgency: f32 = days_difference as f32 * settings.per_day;
+                minimum_urgency.min(MAXIMUM_URGENCY)
+            }
+        };
+        if minimum_urgency > self.effective_urgency {
+            self.effective_urgency = minimum_urgency;
+        }
+    }
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        TaskManager::new()
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        TaskManager {
+            version: SCHEMA_VERSION,
+            next_uid: 1,
+            tasks: Vec::new(),
+            out_of_office: None,
+            trash: Vec::new(),
+            archive: Vec::new(),
+            goals: WeeklyGoals::default(),
+            last_replace: None,
+            urgency: UrgencySettings::default(),
+            events_since_snapshot: 0,
+            logged_events: 0,
+            event_log_length: 0,
+            unsaved_events: 0,
+            pending_events: Vec::new(),
+            housekeeping: Vec::new(),
+            loaded_tasks: None,
+            loaded_state: None,
+        }
+    }
+
+    // The data file alone, save_to_file and save_journaled log the changes first
+    fn write_file(&mut self, filename: &PathBuf) -> Result<(), Box<dyn Error>> {
+        // Compact and buffered, writing large files pretty printed one piece at a time is slow.
+        // export gives the readable version
+        let partial = filename.with_extension("json.partial");
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        // Moved into place whole, so the servers reading it before every request never see
+        // half a file
+        std::fs::rename(&partial, filename)?;
+        // Everything logged is in the file now
+        self.unsaved_events = 0;
+        self.save_status(filename)?;
+        Ok(())
+    }
+
+    // The servers keep running while other commands change the file, so they read it again before
+    // every request rather than saving over those changes with what they read at the start
+    fn reload(&mut self, filename: &PathBuf, config: &Config) -> Result<(), Box<dyn Error>> {
+        let reloaded = match TaskManager::load_from_file(filename) {
+            Ok(reloaded) => reloaded,
+            Err(err)
+                if err
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound) =>
+            {
+                TaskManager::new()
+            }
+            Err(err) => return Err(err),
+        };
+        *self = reloaded;
+        self.urgency = config.urgency_settings();
+        self.calculate_urgencies();
+        self.derive_from_dependencies();
+        self.sort_by_urgencies();
+        Ok(())
+    }
+
+    pub fn load_from_file(filename: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(filename)?;
+        // Up to date files are read straight into tasks, going through a Value first takes twice
+        // as long with large files
+        let mut task_manager: TaskManager = if is_current(&text) {
+            serde_json::from_str(&text)?
+        } else {
+            let mut data: serde_json::Value = serde_json::from_str(&text)?;
+            backup_before_migrating(filename, &data)?;
+            migrate(&mut data)?;
+            serde_json::from_value(data)?
+        };
+        task_manager.replay_unsaved(filename)?;
+        task_manager.loaded_tasks = Some(task_manager.tasks.clone());
+        task_manager.loaded_state = Some(task_manager.state());
+        Ok(task_manager)
+    }
+
+    // Urgencies are frozen at the start of an out of office period
+    fn urgency_time(&self) -> DateTime<Utc> {
+        match &self.out_of_office {
+            Some(out_of_office) if self.out_of_office_active() => {
+                from_local(out_of_office.start.and_hms_opt(0, 0, 0).unwrap())
+            }
+            _ => Utc::now(),
+        }
+    }
+
+    pub fn calculate_urgencies(&mut self) {
+        let now = self.urgency_time();
+        for task in self.tasks.iter_mut() {
+            task.calculate_urgency(now, &self.urgency);
+        }
+    }
+
+    // Where a task added this run will be once the list is sorted on the next run
+    fn sorted_id(&mut self, id: usize) -> usize {
+        let now = self.urgency_time();
+        self.tasks[id].calculate_urgency(now, &self.urgency);
+        let key = self.tasks[id].effective_urgency.to_bits();
+        self.tasks
+            .iter()
+            .enumerate()
+            .filter(|(index, task)| {
+                let other = task.effective_urgency.to_bits();
+                other > key || (other == key && *index < id)
+            })
+            .count()
+    }
+
+    pub fn sort_by_urgencies(&mut self) {
+        self.tasks
+            .sort_by_key(|s| std::cmp::Reverse(s.effective_urgency.to_bits()));
+    }
+
+    // Every new task goes through here to get its uid
+    fn push_task(&mut self, mut task: Task) {
+        task.uid = self.next_uid;
+        self.next_uid += 1;
+        self.apply(Event::TaskAdded { task });
+    }
+
+    pub fn add_task(&mut self, title: String) {
+        let mut task = Task::new(title);
+        task.urgency = self.urgency.default;
+        self.push_task(task);
+    }
+
+    // Copies everything but status and timestamps, which start fresh like a new task
+    fn clone_task(&mut self, id: usize, new_name: Option<String>) {
+        if self.verify_id(id) {
+            let original = &self.tasks[id];
+            let mut new_task = Task::new(new_name.unwrap_or_else(|| original.title.clone()));
+            new_task.description = original.description.clone();
+            new_task.urgency = original.urgency;
+            new_task.estimate = original.estimate;
+            new_task.soft_due = original.soft_due;
+            new_task.reminders = original.reminders.clone();
+            new_task.links = original.links.clone();
+            new_task.attachments = original.attachments.clone();
+            new_task.custom = original.custom.clone();
+            new_task.depends_on = original.depends_on.clone();
+            new_task.assignee = original.assignee.clone();
+            new_task.tags = original.tags.clone();
+            new_task.project = original.project.clone();
+            // The same steps, none of them done yet
+            new_task.checklist = original
+                .checklist
+                .iter()
+                .map(|item| ChecklistItem {
+                    done: false,
+                    ..item.clone()
+                })
+                .collect();
+            self.push_task(new_task);
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn verify_id(&self, id: usize) -> bool {
+        if id < self.tasks.len() {
+            return true;
+        }
+        false
+    }
+    // ----- Task Setters -----
+    fn set_task_name(&mut self, id: usize, new_name: String) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change("title", task.title.clone(), new_name.clone());
+            let uid = task.uid;
+            self.apply(Event::TitleChanged {
+                uid,
+                title: new_name,
+            });
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+    pub fn set_task_description(&mut self, id: usize, new_description: String) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "description",
+                task.description.clone(),
+                new_description.clone(),
+            );
+            self.set_field(id, FieldValue::Description(new_description));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+    pub fn set_task_status(&mut self, id: usize, new_status: Status) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            // Track the current continuous interval, restarting an active task keeps its interval
+            let active_since = if new_status != Status::Active {
+                None
+            } else if task.status != Status::Active || task.active_since.is_none() {
+                Some(Utc::now())
+            } else {
+                task.active_since
+            };
+            let completed = new_status == Status::Done && task.status != Status::Done;
+            task.record_change(
+                "status",
+                task.status.as_str().to_string(),
+                new_status.as_str().to_string(),
+            );
+            let uid = task.uid;
+            self.apply(Event::StatusChanged {
+                uid,
+                status: new_status,
+                active_since,
+            });
+            if completed {
+                self.create_next_occurrence(id);
+            }
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn set_urgency(&mut self, id: usize, new_urgency: f32) {
+        if self.verify_id(id) {
+            if (MINIMUM_URGENCY..=MAXIMUM_URGENCY).contains(&new_urgency) {
+                let task = &mut self.tasks[id];
+                task.record_change(
+                    "urgency",
+                    format!("{:.2}", task.urgency),
+                    format!("{:.2}", new_urgency),
+                );
+                let uid = task.uid;
+                self.apply(Event::UrgencyChanged {
+                    uid,
+                    urgency: new_urgency,
+                });
+                let now = self.urgency_time();
+                self.tasks[id].calculate_urgency(now, &self.urgency);
+            } else {
+                eprintln!("{}", TaskError::InvalidUrgency(new_urgency));
+            }
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn set_partial_due_date(
+        &mut self,
+        id: usize,
+        date_str: &str,
+        input_format: &str,
+    ) -> Result<(), TaskError> {
+        let datetime_string = format!("{} 17:00:00", date_str);
+        let datetime_str: &str = &datetime_string;
+        let datetime_format = format!("{} %H:%M:%S", input_format);
+        match NaiveDateTime::parse_from_str(datetime_str, &datetime_format) {
+            Ok(date) => {
+                self.set_due_date(id, from_local(date));
+                Ok(())
+            }
+            Err(_) => Err(TaskError::InvalidDate {
+                input: date_str.to_string(),
+                expected: input_format.to_string(),
+            }),
+        }
+    }
+    fn set_due_date(&mut self, id: usize, new_due_date: DateTime<Utc>) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "due",
+                history_datetime(task.due_time),
+                history_datetime(Some(new_due_date)),
+            );
+            let uid = task.uid;
+            self.apply(Event::DueChanged {
+                uid,
+                due: Some(new_due_date),
+            });
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn apply_template(&mut self, id: usize, template: &Template) {
+        if self.verify_id(id) {
+            let uid = self.tasks[id].uid;
+            self.set_field(id, FieldValue::Description(template.description.clone()));
+            self.apply(Event::UrgencyChanged {
+                uid,
+                urgency: template.urgency,
+            });
+            if let (Some(start_time), Some(offset)) =
+                (self.tasks[id].start_time, template.due_offset())
+            {
+                self.apply(Event::DueChanged {
+                    uid,
+                    due: Some(start_time + offset),
+                });
+            }
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn set_estimate(&mut self, id: usize, estimate_str: &str) -> Result<(), TaskError> {
+        if !self.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        let estimate = parse_duration(estimate_str)
+            .ok_or_else(|| TaskError::InvalidEstimate(estimate_str.to_string()))?;
+        let task = &mut self.tasks[id];
+        task.record_change(
+            "estimate",
+            history_duration(task.estimate),
+            history_duration(Some(estimate)),
+        );
+        self.set_field(id, FieldValue::Estimate(Some(estimate.num_seconds())));
+        Ok(())
+    }
+
+    fn set_soft_due(&mut self, id: usize, soft_due: bool) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change("soft due", task.soft_due.to_string(), soft_due.to_string());
+            self.set_field(id, FieldValue::SoftDue(soft_due));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn set_urgency_pinned(&mut self, id: usize, pinned: bool) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "urgency pinned",
+                task.urgency_pinned.to_string(),
+                pinned.to_string(),
+            );
+            self.set_field(id, FieldValue::UrgencyPinned(pinned));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    fn set_assignee(&mut self, id: usize, assignee: Option<String>) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "assignee",
+                task.assignee.clone().unwrap_or_else(|| "none".to_string()),
+                assignee.clone().unwrap_or_else(|| "none".to_string()),
+            );
+            self.set_field(id, FieldValue::Assignee(assignee));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // -------------------------
+    // Narrow terminals drop the description column first, then status, see the config breakpoints.
+    // The due, project and tags columns show when there is room for them, unless forced on or off
+    // with show_due and show_tags
+    fn list_tasks(
+        &self,
+        ids: &[usize],
+        width: Option<usize>,
+        show_due: Option<bool>,
+        show_tags: Option<bool>,
+        bars: bool,
+        config: &Config,
+    ) -> String {
+        let mut lines = Vec::new();
+        if self.tasks.is_empty() {
+            lines.push(i18n::text("no_tasks", "There are currently no tasks :)"));
+        } else {
+            let term_width = match width.or_else(|| dimensions().map(|(w, _)| w)) {
+                Some(w) => w,
+                None => {
+                    println!(
+                        "{}",
+                        tr!(
+                            "terminal_width_unknown",
+                            "Unable to determine terminal width using default width {0}",
+                            DEFAULT_TERMINAL_WIDTH
+                        )
+                    );
+                    DEFAULT_TERMINAL_WIDTH
+                }
+            };
+            let show_description = term_width >= config.hide_description_below;
+            let show_status = term_width >= config.hide_status_below;
+            let show_due = show_due.unwrap_or(term_width >= config.show_due_from);
+            // Widest date in the configured format, plus the overdue marker
+            let due_width = config
+                .format_date(NaiveDate::from_ymd_opt(2000, 12, 31).unwrap())
+                .chars()
+                .count()
+                + 1;
+            let mut description_length = if show_status {
+                term_width.saturating_sub(32)
+            } else {
+                term_width.saturating_sub(21)
+            };
+            if show_due {
+                description_length = description_length.saturating_sub(due_width + 3);
+            }
+            let listed = || ids.iter().map(|&index| &self.tasks[index]);
+            let show_tags = show_tags.unwrap_or(
+                term_width >= config.show_tags_from
+                    && listed().any(|task| task.project.is_some() || !task.tags.is_empty()),
+            );
+            // As wide as the longest value up to a limit, at least as wide as the header
+            let column_width = |widest: Option<usize>, header: &str, limit: usize| {
+                widest.unwrap_or(0).min(limit).max(header.len())
+            };
+            let project_width = column_width(
+                listed()
+                    .filter_map(|task| task.project.as_ref())
+                    .map(|project| display_width(project))
+                    .max(),
+                "PROJECT",
+                MAX_PROJECT_WIDTH,
+            );
+            let tags_width = column_width(
+                listed()
+                    .map(|task| display_width(&task.tags.join(" ")))
+                    .max(),
+                "TAGS",
+                MAX_TAGS_WIDTH,
+            );
+            if show_tags {
+                description_length =
+                    description_length.saturating_sub(project_width + tags_width + 6);
+            }
+            if bars {
+                description_length = description_length.saturating_sub(2);
+            }
+
+            let mut header = if bars { "ID | URG   " } else { "ID | URG " }.to_string();
+            if show_description {
+                header += &format!("| {:width$} ", "DESCRIPTION", width = description_length);
+            }
+            if show_due {
+                header += &format!("| {:due_width$} ", "DUE");
+            }
+            if show_tags {
+                header += &format!("| {:project_width$} | {:tags_width$} ", "PROJECT", "TAGS");
+            }
+            if show_status {
+                header += "| STATUS ";
+            }
+            let theme = config.theme();
+            lines.push(theme.paint(Element::Header, &header));
+
+            let now = Utc::now();
+            for &index in ids {
+                let task = &self.tasks[index];
+                let mut urgency = format!("{:^3}", task.effective_urgency);
+                if bars {
+                    urgency = format!("{} {}", urgency, urgency_bar(task.effective_urgency));
+                }
+                if task.effective_urgency >= HIGH_URGENCY {
+                    urgency = theme.paint(Element::UrgencyHigh, &urgency);
+                }
+                let mut row = format!("{:^3}| {} ", index, urgency);
+                if show_description {
+                    let badges = task.badges(now, config.stale_after_days);
+                    let title_width = description_length.saturating_sub(badges.chars().count());
+                    let title_cut = format!("{}{}", truncate(&task.title, title_width), badges);
+                    row += &format!("| {} ", pad(&title_cut, description_length));
+                }
+                if show_due {
+                    let due = match task.due_time {
+                        Some(due_time) => config.format_date(to_local(due_time).date()),
+                        None => String::new(),
+                    };
+                    let overdue = task.status != Status::Done
+                        && task.due_time.is_some_and(|due_time| due_time < now);
+                    if overdue {
+                        let due = format!("{:due_width$}", due + "!");
+                        row += &format!("| {} ", theme.paint(Element::Overdue, &due));
+                    } else {
+                        row += &format!("| {:due_width$} ", due);
+                    }
+                }
+                if show_tags {
+                    let project = task.project.as_deref().unwrap_or_default();
+                    row += &format!(
+                        "| {} ",
+                        pad(truncate(project, project_width), project_width)
+                    );
+                    let tags = task.tags.join(" ");
+                    row += &format!("| {} ", pad(truncate(&tags, tags_width), tags_width));
+                }
+                if show_status {
+                    let status = format!("{:.8}", task.status.as_str());
+                    let status = match task.status {
+                        Status::Active => theme.paint(Element::Active, &status),
+                        Status::Done => theme.paint(Element::Done, &status),
+                        Status::Inactive => status,
+                    };
+                    row += &format!("| {}", status);
+                }
+                lines.push(row.trim_end().to_string());
+            }
+            if bars {
+                lines.push(URGENCY_LEGEND.to_string());
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+    // Plain columns chosen with --fields
+    fn list_fields(&self, ids: &[usize], fields: &[Field], config: &Config) -> String {
+        let header: Vec<String> = fields
+            .iter()
+            .map(|field| field.name().to_uppercase())
+            .collect();
+        let mut lines = vec![header.join(" | ")];
+        for &index in ids {
+            let row: Vec<String> = fields
+                .iter()
+                .map(|field| field.display_value(index, &self.tasks[index], config))
+                .collect();
+            lines.push(row.join(" | "));
+        }
+        lines.join("\n") + "\n"
+    }
+    // Tab separated machine readable values, tabs and newlines inside values become spaces
+    fn list_raw(&self, ids: &[usize], fields: &[Field]) -> String {
+        ids.iter()
+            .map(|&index| {
+                let row: Vec<String> = fields
+                    .iter()
+                    .map(|field| {
+                        field
+                            .value(index, &self.tasks[index])
+                            .replace(['\t', '\n', '\r'], " ")
+                    })
+                    .collect();
+                row.join("\t") + "\n"
+            })
+            .collect()
+    }
+    // One labeled line per task for screen readers, e.g.
+    // ID: 4. Title: Pay rent. Urgency: 5. Due: 01/03/2025, overdue. Status: Inactive.
+    fn list_plain(&self, ids: &[usize], config: &Config) -> String {
+        if self.tasks.is_empty() {
+            return i18n::text("no_tasks", "There are currently no tasks :)") + "\n";
+        }
+        let now = Utc::now();
+        ids.iter()
+            .map(|&index| {
+                let task = &self.tasks[index];
+                let mut fields = vec![
+                    format!("ID: {}", index),
+                    tr!("detail_title", "Title: {0}", task.title),
+                    tr!(
+                        "detail_urgency",
+                        "Urgency: {0}",
+                        format!("{:.0}", task.effective_urgency)
+                    ),
+                ];
+                if let Some(due_time) = task.due_time {
+                    let overdue = task.status != Status::Done && due_time < now;
+                    fields.push(tr!(
+                        "detail_due",
+                        "Due: {0}{1}",
+                        config.format_date(to_local(due_time).date()),
+                        if overdue { ", overdue" } else { "" }
+                    ));
+                }
+                fields.push(tr!("detail_status", "Status: {0}", task.status.as_str()));
+                fields.join(". ") + ".\n"
+            })
+            .collect()
+    }
+    // One line per task from a --format template, without a header
+    fn list_formatted(&self, ids: &[usize], format: &RowFormat) -> String {
+        ids.iter()
+            .map(|&index| format.render(index, &self.tasks[index]) + "\n")
+            .collect()
+    }
+    // ---
+    // Tasks are already sorted by urgency, so the first ones ready to work on are the suggestions
+    fn show_next(&self, count: usize) {
+        let now = Utc::now();
+        let mut suggested = 0;
+        for (index, task) in self.tasks.iter().enumerate() {
+            if suggested == count {
+                break;
+            }
+            // Waiting and blocked tasks can't be worked on yet
+            if task.status == Status::Done || task.is_waiting(now) || task.blocked {
+                continue;
+            }
+            suggested += 1;
+            let mut reasons = Vec::new();
+            if let Some(due_time) = task.due_time {
+                if due_time < now {
+                    reasons.push(tr!(
+                        "overdue_by",
+                        "overdue by {0}",
+                        format_days(now - due_time)
+                    ));
+                } else {
+                    reasons.push(tr!("due_in", "due in {0}", format_days(due_time - now)));
+                }
+            }
+            if let Some(start_time) = task.start_time {
+                reasons.push(tr!("row_age", "age {0}", format_days(now - start_time)));
+            }
+            reasons.push(tr!(
+                "row_urgency",
+                "urgency {0}",
+                format!("{:.1}", task.effective_urgency)
+            ));
+            if task.status == Status::Active {
+                reasons.push(i18n::text("already_active", "already active"));
+            }
+            println!(" -{}- {} ({})", index, task.title, reasons.join(", "));
+        }
+        if suggested == 0 && self.tasks.iter().any(|task| task.status != Status::Done) {
+            println!(
+                "{}",
+                i18n::text(
+                    "nothing_ready",
+                    "Every open task is waiting or blocked by another task"
+                )
+            );
+        } else if suggested == 0 {
+            println!(
+                "{}",
+                i18n::text("no_tasks", "There are currently no tasks :)")
+            );
+        }
+    }
+    // e.g. "3 active, 2 overdue, 14 total"
+    fn summary(&self) -> String {
+        let now = Utc::now();
+        let active = self
+            .tasks
+            .iter()
+            .filter(|task| task.status == Status::Active)
+            .count();
+        let overdue = self
+            .tasks
+            .iter()
+            .filter(|task| {
+                task.status != Status::Done && task.due_time.is_some_and(|due| due < now)
+            })
+            .count();
+        tr!(
+            "prompt_summary",
+            "{0} active, {1} overdue, {2} total",
+            active,
+            overdue,
+            self.tasks.len()
+        )
+    }
+    // ---
+    fn show_current(&self, config: &Config) {
+        let now = Utc::now();
+        let mut any_active = false;
+        for (index, task) in self.tasks.iter().enumerate() {
+            if let Some(active_since) = task.active_since {
+                any_active = true;
+                let elapsed = now - active_since;
+                let over_limit = config.break_reminder_minutes > 0
+                    && !self.out_of_office_active()
+                    && elapsed.num_minutes() >= config.break_reminder_minutes;
+                if over_limit {
+                    let theme = config.theme();
+                    println!(
+                        " -{}- {} --- {}",
+                        index,
+                        task.title,
+                        theme.paint(Element::Warning, &format_duration(elapsed))
+                    );
+                    let reminder = tr!(
+                        "break_reminder",
+                        "Active for over {0} minutes, time for a break",
+                        config.break_reminder_minutes
+                    );
+                    println!("  {}", theme.paint(Element::Warning, &reminder));
+                } else {
+                    println!(
+                        " -{}- {} --- {}",
+                        index,
+                        task.title,
+                        format_duration(elapsed)
+                    );
+                }
+            }
+        }
+        if !any_active {
+            println!(
+                "{}",
+                i18n::text("no_active_tasks", "There are currently no active tasks")
+            );
+        }
+    }
+    // ---
+    // ---
+    fn show_workload(&self, until: NaiveDate, config: &Config) {
+        let today = Local::now().date_naive();
+        let mut committed_minutes: i64 = 0;
+        let mut counted_tasks = 0;
+        let mut unestimated_tasks = 0;
+        for task in self.tasks.iter() {
+            if task.status == Status::Done {
+                continue;
+            }
+            // Tasks without a due date are not committed to this period
+            let due_in_period = match task.due_time {
+                Some(due_time) => to_local(due_time).date() <= until,
+                None => false,
+            };
+            if !due_in_period {
+                continue;
+            }
+            match task.estimate {
+                Some(estimate) => {
+                    committed_minutes += estimate.num_minutes();
+                    counted_tasks += 1;
+                }
+                None => unestimated_tasks += 1,
+            }
+        }
+
+        // Available time counts today and every work day up to and including until
+        let work_days = config.work_days();
+        let holidays = config.holidays();
+        let mut working_days = 0;
+        let mut day = today;
+        while day <= until {
+            if work_days.contains(&day.weekday()) && !holidays.contains(&day) {
+                working_days += 1;
+            }
+            day += Duration::days(1);
+        }
+        let committed = Duration::minutes(committed_minutes);
+        let available =
+            Duration::minutes((working_days as f32 * config.work_hours_per_day * 60.0) as i64);
+
+        let workload = tr!(
+            "workload_summary",
+            "Workload until {0}: {1} estimated across {2} tasks, {3} available over {4} working days",
+            config.format_date(until),
+            format_duration(committed),
+            counted_tasks,
+            format_duration(available),
+            working_days
+        );
+        println!("{}", workload);
+        if committed > available {
+            let overcommitted = tr!(
+                "workload_overcommitted",
+                "Overcommitted by {0}",
+                format_duration(committed - available)
+            );
+            println!("{}", config.theme().paint(Element::Warning, &overcommitted));
+        } else {
+            println!(
+                "{}",
+                tr!(
+                    "workload_spare",
+                    "{0} to spare",
+                    format_duration(available - committed)
+                )
+            );
+        }
+        if unestimated_tasks > 0 {
+            println!(
+                "{}",
+                tr!(
+                    "workload_unestimated",
+                    "{0} tasks due in this period have no estimate",
+                    unestimated_tasks
+                )
+            );
+        }
+    }
+}
+
+// Parse an optional --filter, errors are reported and give None
+fn parse_filter(expression: Option<String>, config: &Config) -> Option<Filter> {
+    match Filter::parse(expression.as_deref().unwrap_or_default(), config) {
+        Ok(filter) => Some(filter),
+        Err(err) => {
+            eprintln!("{}", err);
+            None
+        }
+    }
+}
+
+// Ask a yes/no question on the terminal, anything but yes is a no
+fn confirm(question: &str) -> bool {
+    print!("{} [y/N] ", question);
+    let _ = stdout().flush();
+    let mut answer = String::new();
+    if stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+// Checked before a change so a transaction can stop on it, set_urgency only reports it
+fn urgency_in_range(urgency: f32) -> Result<f32, TaskError> {
+    if (MINIMUM_URGENCY..=MAXIMUM_URGENCY).contains(&urgency) {
+        Ok(urgency)
+    } else {
+        Err(TaskError::InvalidUrgency(urgency))
+    }
+}
+
+// A description of - is read from stdin, keeping its newlines but not the final one
+fn read_description(description: String) -> Result<String, Box<dyn Error>> {
+    if description != "-" {
+        return Ok(description);
+    }
+    let mut piped = String::new();
+    stdin().read_to_string(&mut piped)?;
+    Ok(piped.trim_end_matches(['\n', '\r']).to_string())
+}
+
+// ------------------------
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let mut app_data_dir = match data_dir() {
+        Some(dir) => dir,
+        None => {
+            eprint!(
+                "{}",
+                i18n::text("data_dir_unknown", "Failed to determine Data Directory")
+            );
+            return Ok(());
+        }
+    };
+    app_data_dir.push("task");
+    app_data_dir.push("task.json");
+    let mut config = Config::load();
+    let argv = expand_aliases(std::env::args().collect(), &config.aliases);
+    let opt = Opt::from_iter(&argv);
+    config.plain |= opt.plain;
+    if opt.dry_run && opt.command.writes_elsewhere() {
+        eprintln!("{}", TaskError::DryRunUnsupported);
+        return Ok(());
+    }
+    if let Command::Repair = opt.command {
+        return repair::repair(&app_data_dir);
+    }
+    if let Command::GenMan { output } = &opt.command {
+        return man::generate_man_page(output.as_deref());
+    }
+    if let Command::Status { short } = opt.command {
+        return status::print_status(&app_data_dir, short);
+    }
+    // Reads the data file on every redraw, so it is never loaded and saved here
+    if let Command::Watch { interval, options } = opt.command {
+        let watch = ConfigWatch::new(opt.plain);
+        return live::watch_list(&app_data_dir, interval.max(1), options, config, watch);
+    }
+    // Plugins read and save the data file themselves
+    if let Command::External(args) = &opt.command {
+        return plugins::run_plugin(args, &argv, &app_data_dir, &opt, &config);
+    }
+    let mut task_manager = match TaskManager::load_from_file(&app_data_dir) {
+        Ok(contents) => contents,
+        Err(err) => {
+            let missing = err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == std::io::ErrorKind::NotFound);
+            if missing || matches!(opt.command, Command::Doctor) {
+                TaskManager::new()
+            } else {
+                // Saving now would replace the user's data, so stop before anything changes
+                match err.downcast_ref::<TaskError>() {
+                    Some(err @ TaskError::NewerDataVersion(_)) => eprintln!("{}", err),
+                    _ => eprintln!(
+                        "{}",
+                        TaskError::UnreadableData {
+                            path: app_data_dir.display().to_string(),
+                            reason: err.to_string(),
+                        }
+                    ),
+                }
+                return Ok(());
+            }
+        }
+    };
+
+    // Changes made on other devices since the last run come in before anything else
+    let remote = Remote::from_config(&config);
+    let pull = !opt.dry_run && !opt.command.reads_only();
+    if let Some(remote) = remote.as_ref().filter(|_| pull) {
+        if let Err(err) = task_manager.pull_remote(remote, &app_data_dir) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "remote_unreachable",
+                    "Unable to reach {0}, using the local tasks: {1}",
+                    remote.url(),
+                    err
+                )
+            );
+        }
+    }
+
+    task_manager.urgency = config.urgency_settings();
+    task_manager.finish_out_of_office(false, &config);
+    task_manager.drop_reminders_while_away();
+    task_manager.purge_trash();
+    task_manager.archive_done_tasks(&config);
+    task_manager.archive_expired_tasks();
+    task_manager.delete_old_done_tasks(&config);
+    task_manager.roll_forward_plans();
+    task_manager.check_watched_files();
+    task_manager.calculate_urgencies();
+    task_manager.derive_from_dependencies();
+    if let Some(script) = script::load_script() {
+        task_manager.apply_script(&script, &config);
+    }
+    let rules = rules::load_rules(&config);
+    task_manager.apply_rules(&rules, &config);
+    task_manager.sort_by_urgencies();
+    task_manager.end_housekeeping();
+
+    // The command runs as usual on the tasks in memory, only the save at the end is skipped
+    let before_command = if opt.dry_run {
+        serde_json::to_value(&task_manager)?
+    } else {
+        serde_json::Value::Null
+    };
+    let show_summary = opt.command.mutates() && !opt.quiet && !opt.dry_run;
+    let show_changes = opt.command.mutates() && opt.dry_run;
+
+    match opt.command {
+        Command::Add { json: true, .. } => {
+            let mut text = String::new();
+            stdin().read_to_string(&mut text)?;
+            match parse_task_json(&text) {
+                Ok(tasks) => {
+                    for id in task_manager.add_tasks(tasks) {
+                        if opt.quiet {
+                            println!("{}", task_manager.sorted_id(id));
+                        } else {
+                            println!(
+                                "{}",
+                                tr!(
+                                    "created_task",
+                                    "Created task {0}",
+                                    task_manager.sorted_id(id)
+                                )
+                            );
+                        }
+                    }
+                }
+                Err(err) => eprintln!("{}", TaskError::InvalidRequest(err.to_string())),
+            }
+        }
+        Command::Add {
+            name,
+            description,
+            urgency,
+            due_time,
+            estimate,
+            soft_due,
+            remind,
+            template,
+            watch_file,
+            pin_urgency,
+            link,
+            recur,
+            scheduled,
+            until,
+            assignee,
+            tag,
+            project,
+            force,
+            ..
+        } => {
+            let name = name.unwrap_or_default();
+            // Quick captures often repeat a task already in the list
+            let add = match task_manager.find_duplicate(&name) {
+                Some(existing) if !force && !opt.dry_run => {
+                    let question = tr!(
+                        "similar_question",
+                        "Similar to open task -{0}- {1}. [a]dd anyway, [n]ote it on that task or [c]ancel?",
+                        existing,
+                        task_manager.tasks[existing].title
+                    );
+                    match Duplicate::parse(prompt(&question).as_deref()) {
+                        Duplicate::Add => true,
+                        Duplicate::Note => {
+                            task_manager.note_duplicate(existing, &name);
+                            println!("{}", tr!("similar_noted", "Noted on task {0}", existing));
+                            false
+                        }
+                        Duplicate::Cancel => {
+                            println!(
+                                "{}",
+                                i18n::text(
+                                    "similar_not_added",
+                                    "Not added, use --force to add it anyway"
+                                )
+                            );
+                            false
+                        }
+                    }
+                }
+                _ => true,
+            };
+            if add {
+                let description = description.map(read_description).transpose()?;
+                let mut transaction = Transaction::add(&mut task_manager, name);
+                if let Some(template_name) = template {
+                    transaction.step(&mut task_manager, "template", |task_manager, id| {
+                        let store = TemplateStore::default_path()
+                            .and_then(|path| TemplateStore::load_from_file(&path).ok())
+                            .unwrap_or_default();
+                        let template = store
+                            .templates
+                            .get(&template_name)
+                            .ok_or_else(|| TaskError::UnknownTemplate(template_name.clone()))?;
+                        task_manager.apply_template(id, template);
+                        Ok(())
+                    });
+                }
+                if let Some(description) = description {
+                    transaction.step(&mut task_manager, "description", |task_manager, id| {
+                        task_manager.set_task_description(id, description);
+                        Ok(())
+                    });
+                }
+                if let Some(urgency) = urgency {
+                    transaction.step(&mut task_manager, "urgency", |task_manager, id| {
+                        task_manager.set_urgency(id, urgency_in_range(urgency)?);
+                        Ok(())
+                    });
+                }
+                if let Some(due_time) = due_time {
+                    transaction.step(&mut task_manager, "due", |task_manager, id| {
+                        task_manager.set_partial_due_date(id, &due_time, &config.input_date_format)
+                    });
+                }
+                if let Some(estimate) = estimate {
+                    transaction.step(&mut task_manager, "estimate", |task_manager, id| {
+                        task_manager.set_estimate(id, &estimate)
+                    });
+                }
+                if soft_due {
+                    transaction.step(&mut task_manager, "soft due", |task_manager, id| {
+                        task_manager.set_soft_due(id, true);
+                        Ok(())
+                    });
+                }
+                if !remind.is_empty() {
+                    transaction.step(&mut task_manager, "reminders", |task_manager, id| {
+                        task_manager.set_reminders(id, &remind, false)
+                    });
+                }
+                if watch_file.is_some() {
+                    transaction.step(&mut task_manager, "watch file", |task_manager, id| {
+                        task_manager.set_watch_file(id, watch_file);
+                        Ok(())
+                    });
+                }
+                if pin_urgency {
+                    transaction.step(&mut task_manager, "pinned urgency", |task_manager, id| {
+                        task_manager.set_urgency_pinned(id, true);
+                        Ok(())
+                    });
+                }
+                if !link.is_empty() {
+                    transaction.step(&mut task_manager, "links", |task_manager, id| {
+                        task_manager.set_links(id, link, false);
+                        Ok(())
+                    });
+                }
+                if let Some(recur) = recur {
+                    transaction.step(&mut task_manager, "recurrence", |task_manager, id| {
+                        task_manager.set_recurrence(id, Some(&recur))
+                    });
+                }
+                if let Some(scheduled) = scheduled {
+                    transaction.step(&mut task_manager, "scheduled", |task_manager, id| {
+                        let scheduled = parse_scheduled(&scheduled, &config.input_date_format)?;
+                        task_manager.set_scheduled(id, Some(scheduled));
+                        Ok(())
+                    });
+                }
+                if let Some(until) = until {
+                    transaction.step(&mut task_manager, "until", |task_manager, id| {
+                        let until = parse_until(&until, &config.input_date_format)?;
+                        task_manager.set_until(id, Some(until));
+                        Ok(())
+                    });
+                }
+                if assignee.is_some() {
+                    transaction.step(&mut task_manager, "assignee", |task_manager, id| {
+                        task_manager.set_assignee(id, assignee);
+                        Ok(())
+                    });
+                }
+                if !tag.is_empty() {
+                    transaction.step(&mut task_manager, "tags", |task_manager, id| {
+                        task_manager.change_tags(id, &tag)
+                    });
+                }
+                if project.is_some() {
+                    transaction.step(&mut task_manager, "project", |task_manager, id| {
+                        task_manager.set_project(id, project);
+                        Ok(())
+                    });
+                }
+                let id = transaction.id;
+                match transaction.commit(&mut task_manager) {
+                    Ok(()) if opt.verbose => task_manager.show_task(id, &config),
+                    Ok(()) if opt.quiet => println!("{}", task_manager.sorted_id(id)),
+                    Ok(()) => println!(
+                        "{}",
+                        tr!(
+                            "created_task",
+                            "Created task {0}",
+                            task_manager.sorted_id(id)
+                        )
+                    ),
+                    Err(rolled_back) => eprintln!("{}", rolled_back),
+                }
+            }
+        }
+        Command::Log {
+            name,
+            description,
+            at,
+            spent,
+        } => {
+            let now = Utc::now();
+            let completed_at =
+                match at.map(|at| parse_completed_at(&at, &config.input_date_format, now)) {
+                    Some(Ok(at)) => at,
+                    Some(Err(err)) => {
+                        eprintln!("{}", err);
+                        return Ok(());
+                    }
+                    None => now,
+                };
+            let spent = match spent {
+                Some(spent) => match parse_duration(&spent) {
+                    Some(spent) => Some(spent),
+                    None => {
+                        eprintln!("{}", TaskError::InvalidEstimate(spent));
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+            let id = task_manager.log_task(name, completed_at, spent);
+            if let Some(description) = description {
+                let description = read_description(description)?;
+                task_manager.set_field(id, FieldValue::Description(description));
+            }
+            if opt.quiet {
+                println!("{}", task_manager.sorted_id(id));
+            } else {
+                println!(
+                    "{}",
+                    tr!("logged_task", "Logged task {0}", task_manager.sorted_id(id))
+                );
+            }
+        }
+        Command::View { id, short } => {
+            if short {
+                task_manager.show_task_short(id);
+            } else {
+                task_manager.show_task(id, &config);
+            }
+        }
+        Command::List(options) => {
+            let report = find_report("list", &config).unwrap_or_default();
+            task_manager.run_report(&report, options, &config, true);
+        }
+        Command::Report { name, options } => match find_report(&name, &config) {
+            Some(report) => task_manager.run_report(&report, options, &config, true),
+            None => eprintln!("{}", TaskError::UnknownReport(name)),
+        },
+        Command::Export { id: Some(id), .. } => match task_manager.export_task(id) {
+            Some(exported) => println!("{}", exported),
+            None => eprintln!("{}", TaskError::InvalidId(id)),
+        },
+        Command::Export {
+            format,
+            filter,
+            fields,
+            output,
+            ..
+        } => {
+            let filter = match parse_filter(filter, &config) {
+                Some(filter) => filter,
+                None => return Ok(()),
+            };
+            let fields = match fields.map(|fields| parse_fields(&fields, &config)) {
+                Some(Ok(fields)) => fields,
+                Some(Err(err)) => {
+                    eprintln!("{}", err);
+                    return Ok(());
+                }
+                None => Field::all(&config),
+            };
+            let exported = match format.unwrap_or_default().as_str() {
+                "csv" => task_manager.export_csv(&filter, &fields),
+                "json" => task_manager.export_json(&filter, &fields),
+                "ics" => task_manager.export_ics(&filter, &fields),
+                "org" => task_manager.export_org(&filter, &fields),
+                "md" => task_manager.export_md(&filter, &fields),
+                _ => unreachable!(),
+            };
+            match output {
+                Some(path) => std::fs::write(path, exported)?,
+                None => print!("{}", exported),
+            }
+        }
+        Command::Search {
+            query,
+            include_trash,
+            include_archived,
+        } => {
+            let query = query.join(" ");
+            let matches = task_manager.search(&query, &config);
+            let trash_matches = if include_trash {
+                task_manager.search_trash(&query, &config)
+            } else {
+                Vec::new()
+            };
+            let archive_matches = if include_archived {
+                task_manager.search_archive(&query, &config)
+            } else {
+                Vec::new()
+            };
+            if matches.is_empty() && trash_matches.is_empty() && archive_matches.is_empty() {
+                println!("{}", i18n::text("no_tasks_matched", "No tasks matched"));
+            }
+            for id in matches {
+                println!(" -{}- {}", id, task_manager.tasks[id].title);
+            }
+            for archive_id in archive_matches {
+                let archived = &task_manager.archive[archive_id];
+                println!(
+                    "{}",
+                    tr!(
+                        "search_archived",
+                        " -{0}- {1} --- archive, archived {2}",
+                        archive_id,
+                        archived.task.title,
+                        config.format_datetime(archived.archived_at)
+                    )
+                );
+            }
+            for trash_id in trash_matches {
+                let trashed = &task_manager.trash[trash_id];
+                println!(
+                    "{}",
+                    tr!(
+                        "search_trashed",
+                        " -{0}- {1} --- trash, deleted {2}",
+                        trash_id,
+                        trashed.task.title,
+                        config.format_datetime(trashed.deleted_at)
+                    )
+                );
+            }
+        }
+        Command::Next { count } => {
+            task_manager.show_next(count);
+        }
+        Command::Replace {
+            filter,
+            find,
+            replace,
+            yes,
+            undo,
+        } => {
+            if undo {
+                println!(
+                    "{}",
+                    tr!(
+                        "undid_replacements",
+                        "Undid {0} replacements",
+                        task_manager.undo_replace()
+                    )
+                );
+            } else {
+                let filter = match parse_filter(filter, &config) {
+                    Some(filter) => filter,
+                    None => return Ok(()),
+                };
+                let find = find.unwrap_or_default();
+                if find.is_empty() {
+                    eprintln!("{}", TaskError::EmptyFind);
+                    return Ok(());
+                }
+                let ids = task_manager.filtered_ids(&filter);
+                let changes = task_manager.replacements(&ids, &find, &replace);
+                task_manager.show_replacements(&changes, &config);
+                if !changes.is_empty()
+                    && (yes
+                        || opt.dry_run
+                        || confirm(&i18n::text("confirm_replace", "Apply these changes?")))
+                {
+                    task_manager.apply_replacements(changes, find, replace);
+                }
+            }
+        }
+        Command::Count { filter } => {
+            let filter = match parse_filter(filter, &config) {
+                Some(filter) => filter,
+                None => return Ok(()),
+            };
+            println!("{}", task_manager.filtered_ids(&filter).len());
+        }
+        Command::Edit {
+            id,
+            name,
+            description,
+            urgency,
+            due_time,
+            estimate,
+            soft_due,
+            hard_due,
+            remind,
+            clear_reminders,
+            watch_file,
+            unwatch,
+            pin_urgency,
+            unpin_urgency,
+            json,
+            link,
+            clear_links,
+            set,
+            depends_on,
+            clear_depends,
+            recur,
+            no_recur,
+            scheduled,
+            unschedule,
+            until,
+            no_until,
+            assignee,
+            unassign,
+            tag,
+            project,
+            no_project,
+            progress,
+            no_progress,
+        } => {
+            let description = description.map(read_description).transpose()?;
+            let mut transaction = match Transaction::edit(&task_manager, id) {
+                Ok(transaction) => transaction,
+                Err(err) if json => {
+                    println!("{}", err.to_json());
+                    return Ok(());
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return Ok(());
+                }
+            };
+            let changes_before = task_manager.tasks[id].history.len();
+            if let Some(name) = name {
+                transaction.step(&mut task_manager, "title", |task_manager, id| {
+                    task_manager.set_task_name(id, name);
+                    Ok(())
+                });
+            }
+            if let Some(description) = description {
+                transaction.step(&mut task_manager, "description", |task_manager, id| {
+                    task_manager.set_task_description(id, description);
+                    Ok(())
+                });
+            }
+            if let Some(urgency) = urgency {
+                transaction.step(&mut task_manager, "urgency", |task_manager, id| {
+                    task_manager.set_urgency(id, urgency_in_range(urgency)?);
+                    Ok(())
+                });
+            }
+            if let Some(due_time) = due_time {
+                transaction.step(&mut task_manager, "due", |task_manager, id| {
+                    task_manager.set_partial_due_date(id, &due_time, &config.input_date_format)
+                });
+            }
+            if let Some(estimate) = estimate {
+                transaction.step(&mut task_manager, "estimate", |task_manager, id| {
+                    task_manager.set_estimate(id, &estimate)
+                });
+            }
+            if soft_due || hard_due {
+                transaction.step(&mut task_manager, "soft due", |task_manager, id| {
+                    task_manager.set_soft_due(id, soft_due);
+                    Ok(())
+                });
+            }
+            if !remind.is_empty() || clear_reminders {
+                transaction.step(&mut task_manager, "reminders", |task_manager, id| {
+                    task_manager.set_reminders(id, &remind, clear_reminders)
+                });
+            }
+            if watch_file.is_some() || unwatch {
+                transaction.step(&mut task_manager, "watch file", |task_manager, id| {
+                    task_manager.set_watch_file(id, watch_file);
+                    Ok(())
+                });
+            }
+            if pin_urgency || unpin_urgency {
+                transaction.step(&mut task_manager, "pinned urgency", |task_manager, id| {
+                    task_manager.set_urgency_pinned(id, pin_urgency);
+                    Ok(())
+                });
+            }
+            if !link.is_empty() || clear_links {
+                transaction.step(&mut task_manager, "links", |task_manager, id| {
+                    task_manager.set_links(id, link, clear_links);
+                    Ok(())
+                });
+            }
+            if !set.is_empty() {
+                transaction.step(&mut task_manager, "custom fields", |task_manager, id| {
+                    for assignment in &set {
+                        parse_assignment(assignment, &config)?;
+                    }
+                    task_manager.set_custom_fields(id, &set, &config);
+                    Ok(())
+                });
+            }
+            if !depends_on.is_empty() || clear_depends {
+                transaction.step(&mut task_manager, "dependencies", |task_manager, id| {
+                    let invalid = depends_on.iter().find(|&&dependency| {
+                        !task_manager.verify_id(dependency) || dependency == id
+                    });
+                    if let Some(&dependency) = invalid {
+                        return Err(TaskError::InvalidId(dependency));
+                    }
+                    task_manager.set_dependencies(id, &depends_on, clear_depends);
+                    Ok(())
+                });
+            }
+            if recur.is_some() || no_recur {
+                transaction.step(&mut task_manager, "recurrence", |task_manager, id| {
+                    task_manager.set_recurrence(id, recur.as_deref())
+                });
+            }
+            if let Some(scheduled) = scheduled {
+                transaction.step(&mut task_manager, "scheduled", |task_manager, id| {
+                    let scheduled = parse_scheduled(&scheduled, &config.input_date_format)?;
+                    task_manager.set_scheduled(id, Some(scheduled));
+                    Ok(())
+                });
+            } else if unschedule {
+                transaction.step(&mut task_manager, "scheduled", |task_manager, id| {
+                    task_manager.set_scheduled(id, None);
+                    Ok(())
+                });
+            }
+            if let Some(until) = until {
+                transaction.step(&mut task_manager, "until", |task_manager, id| {
+                    let until = parse_until(&until, &config.input_date_format)?;
+                    task_manager.set_until(id, Some(until));
+                    Ok(())
+                });
+            } else if no_until {
+                transaction.step(&mut task_manager, "until", |task_manager, id| {
+                    task_manager.set_until(id, None);
+                    Ok(())
+                });
+            }
+            if assignee.is_some() || unassign {
+                transaction.step(&mut task_manager, "assignee", |task_manager, id| {
+                    task_manager.set_assignee(id, assignee);
+                    Ok(())
+                });
+            }
+            if !tag.is_empty() {
+                transaction.step(&mut task_manager, "tags", |task_manager, id| {
+                    task_manager.change_tags(id, &tag)
+                });
+            }
+            if project.is_some() || no_project {
+                transaction.step(&mut task_manager, "project", |task_manager, id| {
+                    task_manager.set_project(id, project);
+                    Ok(())
+                });
+            }
+            if progress.is_some() || no_progress {
+                transaction.step(&mut task_manager, "progress", |task_manager, id| {
+                    task_manager.set_progress(id, progress)?;
+                    task_manager.derive_from_dependencies();
+                    Ok(())
+                });
+            }
+            match transaction.commit(&mut task_manager) {
+                Ok(()) => {
+                    if json || !opt.quiet {
+                        task_manager.show_changes_since(id, changes_before, json, &config);
+                    }
+                    if opt.verbose {
+                        task_manager.show_task(id, &config);
+                    }
+                }
+                Err(rolled_back) if json => println!("{}", rolled_back.error.to_json()),
+                Err(rolled_back) => eprintln!("{}", rolled_back),
+            }
+        }
+        Command::Open {
+            id,
+            link,
+            attachment,
+        } => match attachment {
+            Some(attachment) => task_manager.open_attachment(id, attachment),
+            None => task_manager.open_link(id, link),
+        },
+        Command::Board { by, filter, width } => {
+            let group_by = match by.map(|name| GroupBy::parse(&name, &config)) {
+                Some(Ok(group_by)) => Some(group_by),
+                Some(Err(err)) => {
+                    eprintln!("{}", err);
+                    return Ok(());
+                }
+                None => None,
+            };
+            if let Some(filter) = parse_filter(filter, &config) {
+                print!(
+                    "{}",
+                    task_manager.board(&filter, group_by.as_ref(), width, &config)
+                );
+            }
+        }
+        Command::Timeline {
+            from,
+            to,
+            filter,
+            width,
+        } => {
+            let from_day = match parse_day(&from, &config.input_date_format) {
+                Some(day) => day,
+                None => {
+                    eprintln!("{}", TaskError::InvalidDay(from));
+                    return Ok(());
+                }
+            };
+            let to_day = match to {
+                Some(to) => match parse_day(&to, &config.input_date_format) {
+                    Some(day) => day,
+                    None => {
+                        eprintln!("{}", TaskError::InvalidDay(to));
+                        return Ok(());
+                    }
+                },
+                None => from_day + Duration::days(13),
+            };
+            if let Some(filter) = parse_filter(filter, &config) {
+                let today = Local::now().date_naive();
+                print!(
+                    "{}",
+                    task_manager.timeline(
+                        &filter,
+                        from_day,
+                        to_day.max(from_day),
+                        width,
+                        today,
+                        &config
+                    )
+                );
+            }
+        }
+        Command::Replay { until, events } => {
+            let until =
+                match until.map(|until| parse_replay_time(&until, &config.input_date_format)) {
+                    Some(Ok(until)) => until,
+                    Some(Err(err)) => {
+                        eprintln!("{}", err);
+                        return Ok(());
+                    }
+                    None => Utc::now(),
+                };
+            match events::replay(&app_data_dir, until, &config)? {
+                Some(replayed) if events => {
+                    for line in replayed.events {
+                        println!("{}", line);
+                    }
+                }
+                Some(replayed) => {
+                    let mut past = TaskManager::new();
+                    past.tasks = replayed.tasks;
+                    past.calculate_urgencies();
+                    past.sort_by_urgencies();
+                    let report = find_report("list", &config).unwrap_or_default();
+                    past.run_report(&report, ListOptions::default(), &config, true);
+                }
+                None => println!(
+                    "{}",
+                    i18n::text(
+                        "no_event_log_yet",
+                        "No event log yet, it starts with the next change"
+                    )
+                ),
+            }
+        }
+        Command::Undo => match task_manager.undo_last_change(&app_data_dir, &config) {
+            Ok(Some(undone)) => {
+                for change in undone {
+                    println!("{}", tr!("undid_change", "Undid {0}", change));
+                }
+            }
+            Ok(None) => println!("{}", i18n::text("nothing_to_undo", "Nothing to undo")),
+            Err(err) => eprintln!("{}", err),
+        },
+        Command::Burndown { weeks, estimate } => {
+            if weeks == 0 {
+                eprintln!("{}", TaskError::InvalidWeeks(weeks));
+            } else {
+                let today = Local::now().date_naive();
+                print!("{}", task_manager.burndown(weeks, estimate, today, &config));
+            }
+        }
+        Command::Review { days } => {
+            task_manager.review(days.unwrap_or(config.stale_after_days), &config);
+        }
+        Command::Graph { format, filter } => {
+            if let Some(filter) = parse_filter(filter, &config) {
+                let graph = match format.as_str() {
+                    "dot" => task_manager.export_dot(&filter),
+                    _ => unreachable!(),
+                };
+                print!("{}", graph);
+            }
+        }
+        Command::Notes { id } => {
+            if let Some(data_dir) = app_data_dir.parent() {
+                task_manager.edit_notes(id, data_dir)?;
+            }
+        }
+        Command::Attach { id, path, copy } => {
+            let data_dir = app_data_dir.parent().map(Path::to_path_buf);
+            let copy_to = if copy { data_dir.as_deref() } else { None };
+            task_manager.attach_file(id, &path, copy_to);
+        }
+        Command::Start { id } => {
+            for id in task_manager.select(id, &opt.selection, &config) {
+                task_manager.set_task_status(id, Status::Active);
+                if opt.verbose {
+                    task_manager.show_task(id, &config);
+                }
+            }
+        }
+        Command::Stop { id } => {
+            for id in task_manager.select(id, &opt.selection, &config) {
+                task_manager.set_task_status(id, Status::Inactive);
+                if opt.verbose {
+                    task_manager.show_task(id, &config);
+                }
+            }
+        }
+        Command::Done { id } => {
+            for id in task_manager.select(id, &opt.selection, &config) {
+                task_manager.set_task_status(id, Status::Done);
+                task_manager.set_urgency(id, 0.0);
+                if opt.verbose {
+                    task_manager.show_task(id, &config);
+                }
+            }
+        }
+        Command::Remove { id, force } => {
+            let ids = task_manager.select(id, &opt.selection, &config);
+            let question = match ids.as_slice() {
+                [] => None,
+                [id] => Some(tr!(
+                    "confirm_remove",
+                    "Remove \"{0}\"?",
+                    task_manager.tasks[*id].title
+                )),
+                ids => Some(tr!("confirm_remove_tasks", "Remove {0} tasks?", ids.len())),
+            };
+            if question.is_some_and(|question| force || opt.dry_run || confirm(&question)) {
+                // Later IDs first, each removal moves the tasks after it up
+                for id in ids.into_iter().rev() {
+                    task_manager.trash_task(id);
+                }
+            }
+        }
+        Command::Check { command } => match command {
+            CheckCommand::Add { id, text } => task_manager.add_checklist_item(id, text),
+            CheckCommand::Done { id, item } => task_manager.set_checklist_item_done(id, item, true),
+            CheckCommand::Undo { id, item } => {
+                task_manager.set_checklist_item_done(id, item, false)
+            }
+            CheckCommand::Remove { id, item } => task_manager.remove_checklist_item(id, item),
+        },
+        Command::Trash { command } => match command {
+            TrashCommand::List => task_manager.list_trash(&config),
+            TrashCommand::Restore { id } => task_manager.restore_task(id),
+        },
+        Command::Gc {
+            older_than,
+            archive,
+        } => match parse_duration(&older_than) {
+            Some(older_than) => {
+                let cutoff = Utc::now() - older_than;
+                let collected = task_manager.collect_garbage(cutoff, archive, opt.dry_run);
+                let verb = match (opt.dry_run, archive) {
+                    (true, true) => i18n::text("gc_would_archive", "Would archive"),
+                    (true, false) => i18n::text("gc_would_delete", "Would delete"),
+                    (false, true) => i18n::text("gc_archived", "Archived"),
+                    (false, false) => i18n::text("gc_deleted", "Deleted"),
+                };
+                if opt.dry_run {
+                    for title in collected.iter() {
+                        println!(" {}", title);
+                    }
+                }
+                println!(
+                    "{}",
+                    tr!(
+                        "gc_summary",
+                        "{0} {1} completed tasks",
+                        verb,
+                        collected.len()
+                    )
+                );
+            }
+            None => eprintln!("{}", TaskError::InvalidDuration(older_than)),
+        },
+        Command::Snapshot { command } => match command {
+            SnapshotCommand::Create { label } => {
+                match task_manager.create_snapshot(&app_data_dir, label) {
+                    Ok(label) => println!("{}", tr!("snapshot_saved", "Saved snapshot {0}", label)),
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+            SnapshotCommand::Restore { label } => {
+                match TaskManager::load_snapshot(&app_data_dir, &label) {
+                    Ok(mut restored) => {
+                        task_manager
+                            .create_snapshot(&app_data_dir, Some(BEFORE_RESTORE.to_string()))?;
+                        restored.continue_log(task_manager);
+                        task_manager = restored;
+                        println!(
+                            "{}",
+                            tr!(
+                                "snapshot_restored",
+                                "Restored snapshot {0}, the tasks before it were saved as {1}",
+                                label,
+                                BEFORE_RESTORE
+                            )
+                        );
+                    }
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+            SnapshotCommand::List => list_snapshots(&app_data_dir, &config),
+        },
+        Command::Archive { command } => match command {
+            ArchiveCommand::List => task_manager.list_archive(&config),
+            ArchiveCommand::Restore { id } => task_manager.restore_archived(id),
+        },
+        Command::Plan { id, on, clear } => {
+            if clear {
+                task_manager.set_planned_on(id, None);
+            } else if let Some(on) = on {
+                match parse_day(&on, &config.input_date_format) {
+                    Some(day) => task_manager.set_planned_on(id, Some(day)),
+                    None => eprintln!("{}", TaskError::InvalidDay(on)),
+                }
+            }
+            if opt.verbose {
+                task_manager.show_task(id, &config);
+            }
+        }
+        Command::Pick { action } => {
+            if let Some(id) = task_manager.pick() {
+                match action.as_str() {
+                    "done" => {
+                        task_manager.set_task_status(id, Status::Done);
+                        task_manager.set_urgency(id, 0.0);
+                    }
+                    "start" => task_manager.set_task_status(id, Status::Active),
+                    "edit" => task_manager.edit_in_editor(id)?,
+                    _ => task_manager.show_task(id, &config),
+                }
+            }
+        }
+        Command::Today => {
+            task_manager.show_today(&config);
+        }
+        Command::Week => {
+            task_manager.show_week(&config);
+        }
+        Command::Rules => task_manager.list_rules(&rules),
+        Command::History { id } => {
+            task_manager.show_history(id, &config);
+        }
+        Command::Clone { id, name } => {
+            let tasks_before = task_manager.tasks.len();
+            task_manager.clone_task(id, name);
+            if task_manager.tasks.len() > tasks_before {
+                let new_id = task_manager.tasks.len() - 1;
+                if opt.verbose {
+                    task_manager.show_task(new_id, &config);
+                } else if opt.quiet {
+                    println!("{}", task_manager.sorted_id(new_id));
+                }
+            }
+        }
+        Command::Current => {
+            task_manager.show_current(&config);
+        }
+        Command::Workload { until } => match parse_day(&until, &config.input_date_format) {
+            Some(until) => task_manager.show_workload(until, &config),
+            None => eprintln!("{}", TaskError::InvalidDay(until)),
+        },
+        Command::Notify { digest } => {
+            task_manager.notify(digest, &config);
+        }
+        Command::Ooo { command } => match command {
+            OooCommand::Start { date, until } => match (
+                parse_day(&date, &config.input_date_format),
+                parse_day(&until, &config.input_date_format),
+            ) {
+                (Some(start), Some(until)) => {
+                    task_manager.start_out_of_office(start, until, &config)
+                }
+                (None, _) => eprintln!("{}", TaskError::InvalidDay(date)),
+                (_, None) => eprintln!("{}", TaskError::InvalidDay(until)),
+            },
+            OooCommand::End => {
+                if task_manager.out_of_office.is_some() {
+                    task_manager.finish_out_of_office(true, &config);
+                } else {
+                    println!(
+                        "{}",
+                        i18n::text("ooo_not_away", "You are not out of office")
+                    );
+                }
+            }
+            OooCommand::Status => match &task_manager.out_of_office {
+                Some(out_of_office) => println!(
+                    "{}",
+                    tr!(
+                        "ooo_started",
+                        "Out of office from {0} until {1}, {2} due dates moved",
+                        config.format_date(out_of_office.start),
+                        config.format_date(out_of_office.until),
+                        out_of_office.adjustments.len()
+                    )
+                ),
+                None => println!(
+                    "{}",
+                    i18n::text("ooo_not_away", "You are not out of office")
+                ),
+            },
+        },
+        Command::Repair
+        | Command::Status { .. }
+        | Command::Watch { .. }
+        | Command::External(_)
+        | Command::GenMan { .. } => {
+            unreachable!()
+        }
+        Command::Doctor => {
+            // Never save here, a broken data file should be left for the user to repair
+            task_manager.doctor(&app_data_dir);
+            return Ok(());
+        }
+        Command::Goal { command } => match command {
+            GoalCommand::Done { count } => task_manager.set_done_goal(Some(count)),
+            GoalCommand::Time { time, tag } => match (parse_duration(&time), tag) {
+                (None, _) => eprintln!("{}", TaskError::InvalidGoal(time)),
+                (Some(active_time), None) => task_manager.set_active_time_goal(Some(active_time)),
+                (Some(active_time), Some(tag)) => {
+                    match tags::parse_tag(tag.strip_prefix('+').unwrap_or(&tag)) {
+                        Ok(tag) => task_manager.set_tag_time_goal(tag, Some(active_time)),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+            },
+            GoalCommand::Clear => {
+                task_manager.clear_goals();
+            }
+            GoalCommand::Status => task_manager.show_goals(),
+        },
+        Command::Import {
+            file,
+            from,
+            force_new,
+            merge,
+            prefer,
+            list,
+        } => {
+            if merge {
+                let remote = merge::read_task_file(&file)?;
+                let report = task_manager.merge(remote, Prefer::parse(&prefer), opt.dry_run);
+                let verb = if opt.dry_run {
+                    i18n::text("merge_would_add", "Would add")
+                } else {
+                    i18n::text("merge_added", "Added")
+                };
+                println!(
+                    "{}",
+                    tr!(
+                        "merge_summary",
+                        "{0} {1} tasks, {2} updated, {3} conflicts",
+                        verb,
+                        report.added.len(),
+                        report.updated.len(),
+                        report.conflicts.len()
+                    )
+                );
+                for (label, titles) in [
+                    (i18n::text("merge_add", "add"), &report.added),
+                    (i18n::text("merge_update", "update"), &report.updated),
+                    (i18n::text("merge_conflict", "conflict"), &report.conflicts),
+                ] {
+                    for title in titles {
+                        println!("  {}: {}", label, title);
+                    }
+                }
+            } else {
+                let imported = match from.as_str() {
+                    "csv" => import::read_csv(&file)?,
+                    "jira" => import::read_jira(&file)?,
+                    "md" => import::read_md(&file)?,
+                    "trello" => import::read_trello(&file, &list)?,
+                    "mstodo" => import::read_mstodo(&file)?,
+                    "google-tasks" => import::read_google_tasks(&file)?,
+                    _ => unreachable!(),
+                };
+                let summary = task_manager.import_tasks(&from, imported, force_new);
+                println!(
+                    "{}",
+                    tr!(
+                        "import_summary",
+                        "Imported {0} new tasks, updated {1} existing tasks",
+                        summary.added,
+                        summary.updated
+                    )
+                );
+            }
+        }
+        Command::Serve { port } => {
+            let watch = ConfigWatch::new(opt.plain);
+            return server::serve(&mut task_manager, port, &app_data_dir, config, watch);
+        }
+        Command::Sync { command } => match command {
+            SyncCommand::Github { repo } => {
+                let (summary, closed) = task_manager.sync_github(&repo)?;
+                println!(
+                    "{}",
+                    tr!(
+                        "github_sync_summary",
+                        "Imported {0} new issues, updated {1}, closed {2}",
+                        summary.added,
+                        summary.updated,
+                        closed
+                    )
+                );
+            }
+            SyncCommand::Remote { url, token } => match token.or(config.sync_token.clone()) {
+                Some(token) => match task_manager.sync_remote(&url, &token) {
+                    Ok(report) => {
+                        let synced = tr!(
+                            "sync_summary",
+                            "Synced, {0} added, {1} updated, {2} removed, {3} conflicts",
+                            report.merged.added.len(),
+                            report.merged.updated.len(),
+                            report.removed.len(),
+                            report.merged.conflicts.len()
+                        );
+                        println!("{}", synced);
+                        for conflict in report.merged.conflicts {
+                            println!("  {}", conflict);
+                        }
+                    }
+                    Err(err) => eprintln!("{}", err),
+                },
+                None => eprintln!(
+                    "{}",
+                    i18n::text(
+                        "sync_token_missing",
+                        "No sync token, give --token or set sync_token in config"
+                    )
+                ),
+            },
+        },
+        Command::SyncServer { port, token } => match token.or(config.sync_token.clone()) {
+            Some(token) => {
+                let watch = ConfigWatch::new(opt.plain);
+                return sync::serve_sync(
+                    &mut task_manager,
+                    port,
+                    &token,
+                    &app_data_dir,
+                    config,
+                    watch,
+                );
+            }
+            None => eprintln!(
+                "{}",
+                i18n::text(
+                    "sync_token_missing",
+                    "No sync token, give --token or set sync_token in config"
+                )
+            ),
+        },
+        Command::Rpc => {
+            let watch = ConfigWatch::new(opt.plain);
+            return rpc::serve_stdio(&mut task_manager, &app_data_dir, config, watch);
+        }
+        Command::Template { command } => {
+            let template_path = match TemplateStore::default_path() {
+                Some(path) => path,
+                None => {
+                    eprintln!(
+                        "{}",
+                        i18n::text("config_dir_unknown", "Failed to determine Config Directory")
+                    );
+                    return Ok(());
+                }
+            };
+            let mut store = TemplateStore::load_from_file(&template_path).unwrap_or_default();
+            match command {
+                TemplateCommand::Save { name, id } => {
+                    if task_manager.verify_id(id) {
+                        let template = Template::from_task(&task_manager.tasks[id]);
+                        store.templates.insert(name, template);
+                        store.save_to_file(&template_path)?;
+                    } else {
+                        eprintln!("{}", TaskError::InvalidId(id));
+                    }
+                }
+                TemplateCommand::List => {
+                    if store.templates.is_empty() {
+                        println!(
+                            "{}",
+                            i18n::text("no_templates", "There are currently no templates")
+                        );
+                    }
+                    for (name, template) in store.templates.iter() {
+                        println!(
+                            "{}",
+                            tr!(
+                                "template_row",
+                                "{0} --- urgency: {1}  {2}",
+                                name,
+                                format!("{:.3}", template.urgency),
+                                template.description
+                            )
+                        );
+                    }
+                }
+                TemplateCommand::Remove { name } => {
+                    if store.templates.remove(&name).is_some() {
+                        store.save_to_file(&template_path)?;
+                    } else {
+                        eprintln!("{}", TaskError::UnknownTemplate(name));
+                    }
+                }
+            }
+        }
+    }
+
+    if opt.dry_run {
+        if show_changes {
+            dry_run::print_changes(&before_command, &serde_json::to_value(&task_manager)?);
+        }
+        return Ok(());
+    }
+    if show_summary {
+        println!("{}", task_manager.summary());
+    }
+    if config.journal_storage() {
+        task_manager.save_journaled(&app_data_dir, config.journal_compact_after)?;
+    } else {
+        task_manager.save_to_file(&app_data_dir)?;
+    }
+    if let Some(remote) = remote {
+        if let Err(err) = task_manager.push_remote(&remote, &app_data_dir) {
+            eprintln!(
+                "{}",
+                tr!(
+                    "remote_upload_failed",
+                    "Unable to upload to {0}, the changes are kept locally: {1}",
+                    remote.url(),
+                    err
+                )
+            );
+        }
+    }
+    Ok(())
+}
+// ------------------------ Debugs
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::dates::{format_duration, from_local, parse_duration, to_local};
+    use crate::filter::Field;
+    use crate::templates::Template;
+    use crate::{Status, TaskManager};
+    #[test]
+    fn create_and_modify_task() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("task_1".to_string());
+        assert_eq!(debug_manager.tasks[0].title, "task_1");
+        assert_eq!(debug_manager.tasks[0].status, Status::Inactive);
+        debug_manager.set_task_status(0, Status::Active);
+        assert_eq!(debug_manager.tasks[0].status, Status::Active);
+        debug_manager.set_task_status(0, Status::Done);
+        assert_eq!(debug_manager.tasks[0].status, Status::Done);
+    }
+
+    #[test]
+    fn template_round_trip() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("release".to_string());
+        debug_manager.set_task_description(0, "checklist".to_string());
+        debug_manager.set_urgency(0, 7.0);
+        let due = debug_manager.tasks[0].start_time.unwrap() + chrono::Duration::days(2);
+        debug_manager.set_due_date(0, due);
+        let template = Template::from_task(&debug_manager.tasks[0]);
+        assert_eq!(template.due_offset_seconds, Some(2 * 24 * 60 * 60));
+
+        debug_manager.add_task("release 2".to_string());
+        debug_manager.apply_template(1, &template);
+        assert_eq!(debug_manager.tasks[1].description, "checklist");
+        assert_eq!(debug_manager.tasks[1].urgency, 7.0);
+        assert!(debug_manager.tasks[1].due_time.is_some());
+    }
+
+    #[test]
+    fn active_interval_tracking() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("focus".to_string());
+        assert!(debug_manager.tasks[0].active_since.is_none());
+        debug_manager.set_task_status(0, Status::Active);
+        let active_since = debug_manager.tasks[0].active_since;
+        assert!(active_since.is_some());
+        debug_manager.set_task_status(0, Status::Active);
+        assert_eq!(debug_manager.tasks[0].active_since, active_since);
+        debug_manager.set_task_status(0, Status::Inactive);
+        assert!(debug_manager.tasks[0].active_since.is_none());
+        assert_eq!(format_duration(chrono::Duration::minutes(65)), "1h 05m");
+    }
+
+    #[test]
+    fn clone_resets_status() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("chore".to_string());
+        debug_manager.set_task_description(0, "same as last week".to_string());
+        debug_manager.set_task_status(0, Status::Active);
+        debug_manager.clone_task(0, Some("chore 2".to_string()));
+        assert_eq!(debug_manager.tasks[1].title, "chore 2");
+        assert_eq!(debug_manager.tasks[1].description, "same as last week");
+        assert_eq!(debug_manager.tasks[1].status, Status::Inactive);
+        assert!(debug_manager.tasks[1].active_since.is_none());
+    }
+
+    #[test]
+    fn estimates() {
+        assert_eq!(parse_duration("2h"), Some(chrono::Duration::hours(2)));
+        assert_eq!(parse_duration("1h30m"), Some(chrono::Duration::minutes(90)));
+        assert_eq!(parse_duration("soon"), None);
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("report".to_string());
+        debug_manager.set_estimate(0, "45m").unwrap();
+        assert_eq!(
+            debug_manager.tasks[0].estimate,
+            Some(chrono::Duration::minutes(45))
+        );
+    }
+
+    #[test]
+    fn out_of_office_moves_soft_due_dates() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("soft".to_string());
+        debug_manager.add_task("hard".to_string());
+        let start = chrono::Local::now().date_naive();
+        let until = start + chrono::Duration::days(6);
+        let due = from_local(
+            (start + chrono::Duration::days(2))
+                .and_hms_opt(17, 0, 0)
+                .unwrap(),
+        );
+        debug_manager.set_due_date(0, due);
+        debug_manager.set_due_date(1, due);
+        debug_manager.set_soft_due(0, true);
+        debug_manager.start_out_of_office(start, until, &Config::default());
+        assert!(to_local(debug_manager.tasks[0].due_time.unwrap()).date() > until);
+        assert_eq!(debug_manager.tasks[1].due_time, Some(due));
+        assert!(debug_manager.out_of_office_active());
+        debug_manager.finish_out_of_office(true, &Config::default());
+        assert!(debug_manager.out_of_office.is_none());
+    }
+
+    #[test]
+    fn trash_and_restore() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("oops".to_string());
+        debug_manager.trash_task(0);
+        assert!(debug_manager.tasks.is_empty());
+        assert_eq!(debug_manager.trash.len(), 1);
+        debug_manager.purge_trash();
+        assert_eq!(debug_manager.trash.len(), 1);
+        debug_manager.restore_task(0);
+        assert_eq!(debug_manager.tasks[0].title, "oops");
+        assert!(debug_manager.trash.is_empty());
+    }
+
+    #[test]
+    fn setters_record_history() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("tracked".to_string());
+        debug_manager.set_urgency(0, 5.0);
+        debug_manager.set_urgency(0, 5.0);
+        debug_manager.set_task_status(0, Status::Done);
+        let history = &debug_manager.tasks[0].history;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].field, "urgency");
+        assert_eq!(history[0].old_value, "3.00");
+        assert_eq!(history[1].new_value, "Done");
+    }
+
+    #[test]
+    fn plans_roll_forward() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("planned".to_string());
+        let today = chrono::Local::now().date_naive();
+        debug_manager.set_planned_on(0, Some(today - chrono::Duration::days(2)));
+        debug_manager.roll_forward_plans();
+        assert_eq!(debug_manager.tasks[0].planned_on, Some(today));
+        assert_eq!(debug_manager.tasks[0].rollovers, 2);
+    }
+
+    #[test]
+    fn naive_times_load_as_local() {
+        let task: crate::Task = serde_json::from_str(
+            r#"{"title": "old", "description": "", "status": "Inactive", "urgency": 3.0,
+                "start_time": "2024-03-01T09:30:00", "due_time": "2024-03-02T17:00:00Z"}"#,
+        )
+        .unwrap();
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        assert_eq!(to_local(task.start_time.unwrap()), start);
+        let due = chrono::NaiveDate::from_ymd_opt(2024, 3, 2)
+            .unwrap()
+            .and_hms_opt(17, 0, 0)
+            .unwrap();
+        assert_eq!(task.due_time.unwrap().naive_utc(), due);
+    }
+
+    #[test]
+    fn pinned_urgency_is_not_raised() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("someday".to_string());
+        debug_manager.add_task("aging".to_string());
+        for task in debug_manager.tasks.iter_mut() {
+            task.start_time = Some(chrono::Utc::now() - chrono::Duration::days(30));
+            task.urgency = 1.0;
+        }
+        debug_manager.set_urgency_pinned(0, true);
+        debug_manager.calculate_urgencies();
+        assert_eq!(debug_manager.tasks[0].effective_urgency, 1.0);
+        assert!(debug_manager.tasks[1].effective_urgency > 1.0);
+        // The base urgency is never raised, so lowering it again is predictable
+        assert_eq!(debug_manager.tasks[1].urgency, 1.0);
+    }
+
+    #[test]
+    fn archives_old_done_tasks() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("old".to_string());
+        debug_manager.add_task("recent".to_string());
+        debug_manager.set_task_status(0, Status::Done);
+        debug_manager.set_task_status(1, Status::Done);
+        debug_manager.tasks[0].history.last_mut().unwrap().time =
+            chrono::Utc::now() - chrono::Duration::days(10);
+        let config = Config {
+            archive_done_after_days: 7,
+            ..Config::default()
+        };
+        debug_manager.archive_done_tasks(&config);
+        assert_eq!(debug_manager.tasks.len(), 1);
+        assert_eq!(debug_manager.archive[0].task.title, "old");
+        debug_manager.restore_archived(0);
+        assert_eq!(debug_manager.tasks.len(), 2);
+    }
+    #[test]
+    fn archive_rules_by_tag_and_project() {
+        let mut debug_manager = TaskManager::new();
+        for title in ["milk", "report", "shelf", "other"] {
+            debug_manager.add_task(title.to_string());
+        }
+        debug_manager
+            .change_tags(0, &["errands".to_string()])
+            .unwrap();
+        debug_manager
+            .change_tags(1, &["errands".to_string(), "work".to_string()])
+            .unwrap();
+        debug_manager.set_project(2, Some("home".to_string()));
+        for id in 0..4 {
+            debug_manager.set_task_status(id, Status::Done);
+            debug_manager.tasks[id].history.last_mut().unwrap().time =
+                chrono::Utc::now() - chrono::Duration::days(10);
+        }
+        let config = Config {
+            archive_rules: [("+errands", 7), ("+work", 90), ("project:home", 0)]
+                .iter()
+                .map(|(rule, days)| (rule.to_string(), *days))
+                .collect(),
+            ..Config::default()
+        };
+        debug_manager.archive_done_tasks(&config);
+        let archived: Vec<&str> = debug_manager
+            .archive
+            .iter()
+            .map(|archived| archived.task.title.as_str())
+            .collect();
+        assert_eq!(archived, vec!["milk"]);
+    }
+    #[test]
+    fn archives_expired_tasks() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("concert tickets".to_string());
+        debug_manager.add_task("festival tickets".to_string());
+        let now = chrono::Utc::now();
+        debug_manager.set_until(0, Some(now - chrono::Duration::hours(1)));
+        debug_manager.set_until(1, Some(now + chrono::Duration::days(1)));
+        debug_manager.archive_expired_tasks();
+        assert_eq!(debug_manager.tasks.len(), 1);
+        assert_eq!(debug_manager.tasks[0].title, "festival tickets");
+        assert_eq!(debug_manager.archive[0].task.title, "concert tickets");
+        assert_eq!(debug_manager.archive[0].task.status, Status::Inactive);
+    }
+    #[test]
+    fn weekly_goals_count_tasks_done_this_week() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("finished".to_string());
+        debug_manager.add_task("open".to_string());
+        debug_manager.set_task_status(0, Status::Done);
+        assert!(debug_manager.goal_progress().is_empty());
+        debug_manager.set_done_goal(Some(2));
+        assert_eq!(
+            debug_manager.goal_progress(),
+            vec!["[##########----------] 1/2 tasks done"]
+        );
+    }
+
+    #[test]
+    fn tag_time_goals_only_count_tasks_with_the_tag() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("chapter two".to_string());
+        debug_manager.add_task("inbox".to_string());
+        debug_manager
+            .change_tags(0, &["thesis".to_string()])
+            .unwrap();
+        debug_manager.set_task_status(1, Status::Active);
+        for change in debug_manager.tasks[1].history.iter_mut() {
+            change.time -= chrono::Duration::days(100);
+        }
+        debug_manager.set_tag_time_goal("thesis".to_string(), Some(chrono::Duration::hours(6)));
+        assert_eq!(
+            debug_manager.goal_progress(),
+            vec!["[--------------------] 0m/6h 00m on +thesis"]
+        );
+        debug_manager.set_tag_time_goal("thesis".to_string(), None);
+        assert!(debug_manager.goals.is_empty());
+    }
+
+    #[test]
+    fn snapshot_undoes_later_changes() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("before".to_string());
+        debug_manager.set_task_status(0, Status::Active);
+        let at = chrono::Utc::now();
+        debug_manager.set_task_name(0, "after".to_string());
+        debug_manager.set_task_status(0, Status::Done);
+        for change in debug_manager.tasks[0].history.iter_mut().skip(1) {
+            change.time = at + chrono::Duration::minutes(1);
+        }
+        debug_manager.add_task("later".to_string());
+        debug_manager.tasks[1].created_at = Some(at + chrono::Duration::minutes(1));
+        let snapshot = debug_manager.snapshot_at(at);
+        assert_eq!(snapshot.tasks.len(), 1);
+        assert_eq!(snapshot.tasks[0].title, "before");
+        assert_eq!(snapshot.tasks[0].status, Status::Active);
+        assert!(snapshot.tasks[0].active_since.is_some());
+    }
+
+    #[test]
+    fn links_are_added_once_and_cleared() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("review".to_string());
+        let pr = "https://example.com/pr/1".to_string();
+        debug_manager.set_links(0, vec![pr.clone()], false);
+        debug_manager.set_links(0, vec![pr.clone()], false);
+        assert_eq!(debug_manager.tasks[0].links, vec![pr]);
+        debug_manager.set_links(0, Vec::new(), true);
+        assert!(debug_manager.tasks[0].links.is_empty());
+        assert_eq!(debug_manager.tasks[0].history.len(), 2);
+    }
+
+    #[test]
+    fn attachments_can_be_copied_into_the_data_dir() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-attach-{}", std::process::id()));
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let file = data_dir.join("notes.txt");
+        std::fs::write(&file, "notes").unwrap();
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("write up".to_string());
+        debug_manager.attach_file(0, &file, Some(&data_dir));
+        let uid = debug_manager.tasks[0].uid.to_string();
+        let copied = &debug_manager.tasks[0].attachments[0];
+        assert!(copied.ends_with(format!("attachments/{}/notes.txt", uid)));
+        assert_eq!(std::fs::read_to_string(copied).unwrap(), "notes");
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn summary_counts_active_and_overdue() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("late".to_string());
+        debug_manager.add_task("busy".to_string());
+        debug_manager.add_task("finished late".to_string());
+        debug_manager.set_task_status(1, Status::Active);
+        debug_manager.set_task_status(2, Status::Done);
+        let yesterday = chrono::Utc::now() - chrono::Duration::days(1);
+        debug_manager.set_due_date(0, yesterday);
+        debug_manager.set_due_date(2, yesterday);
+        assert_eq!(debug_manager.summary(), "1 active, 1 overdue, 3 total");
+    }
+
+    #[test]
+    fn raw_list_is_tab_separated() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("two\tcolumns".to_string());
+        debug_manager.set_task_description(0, "first\nsecond".to_string());
+        let fields = [Field::Id, Field::Title, Field::Description];
+        assert_eq!(
+            debug_manager.list_raw(&[0], &fields),
+            "0\ttwo columns\tfirst second\n"
+        );
+    }
+
+    #[test]
+    fn plain_list_has_one_labeled_line_per_task() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("a title longer than any column would allow it to be".to_string());
+        debug_manager.set_task_status(0, Status::Active);
+        assert_eq!(
+            debug_manager.list_plain(&[0], &Config::default()),
+            "ID: 0. Title: a title longer than any column would allow it to be. Urgency: 3. \
+             Status: Active.\n"
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::process::Command;
+
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::i18n::tr;
+use crate::TaskManager;
+
+fn links_value(links: &[String]) -> String {
+    if links.is_empty() {
+        "none".to_string()
+    } else {
+        links.join(" ")
+    }
+}
+
+// The platform's way of opening a URL or file in its default application
+pub fn open_command(link: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(link);
+        command
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", link]);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(link);
+        command
+    }
+}
+
+impl TaskManager {
+    // Replaces all links when clear is set, otherwise adds the new ones after the existing
+    pub fn set_links(&mut self, id: usize, links: Vec<String>, clear: bool) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            let mut new_links = if clear {
+                Vec::new()
+            } else {
+                task.links.clone()
+            };
+            new_links.extend(links.into_iter().filter(|link| !task.links.contains(link)));
+            task.record_change("links", links_value(&task.links), links_value(&new_links));
+            self.set_field(id, FieldValue::Links(new_links));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // Opens the link at index, counting from 0, in the default browser
+    pub fn open_link(&self, id: usize, index: usize) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        match self.tasks[id].links.get(index) {
+            Some(link) => {
+                if let Err(err) = open_command(link).status() {
+                    eprintln!(
+                        "{}",
+                        tr!("link_open_failed", "Unable to open {0}: {1}", link, err)
+                    );
+                }
+            }
+            None => eprintln!(
+                "{}",
+                TaskError::InvalidLink {
+                    index,
+                    count: self.tasks[id].links.len(),
+                }
+            ),
+        }
+    }
+}
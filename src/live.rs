@@ -0,0 +1,91 @@
+use chrono::Utc;
+use std::error::Error;
+use std::fs::metadata;
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::{Config, ConfigWatch};
+use crate::events::events_path;
+use crate::i18n::tr;
+use crate::reports::find_report;
+use crate::rules::load_rules;
+use crate::script::load_script;
+use crate::{ListOptions, TaskManager};
+
+// How often the data file is checked for changes between refreshes
+const POLL_MILLISECONDS: u64 = 250;
+
+// Journaled saves leave the data file alone, so the event log is watched as well
+fn modified(path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    let modified = |path: &Path| metadata(path).ok()?.modified().ok();
+    (modified(path), modified(&events_path(path)))
+}
+
+// Redraws the list every interval seconds, or as soon as the data file or config changes, until
+// interrupted. Urgencies are recalculated on every redraw and nothing is ever saved
+pub fn watch_list(
+    data_file: &Path,
+    interval: u64,
+    options: ListOptions,
+    mut config: Config,
+    mut watch: ConfigWatch,
+) -> Result<(), Box<dyn Error>> {
+    let mut report = find_report("list", &config).unwrap_or_default();
+    let mut rules = load_rules(&config);
+    let mut script = load_script();
+    loop {
+        let reloaded = watch.reload(&mut config);
+        if reloaded.is_some() {
+            report = find_report("list", &config).unwrap_or_default();
+            rules = load_rules(&config);
+            script = load_script();
+        }
+        let last_modified = modified(data_file);
+        // Clear the screen and move to the top left
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "{}",
+            tr!(
+                "live_header",
+                "Every {0}s or on change, updated {1}",
+                interval,
+                config.format_time(Utc::now())
+            )
+        );
+        if let Some(reloaded) = reloaded {
+            println!("{}", reloaded);
+        }
+        match TaskManager::load_from_file(&data_file.to_path_buf()) {
+            Ok(mut task_manager) => {
+                task_manager.urgency = config.urgency_settings();
+                task_manager.calculate_urgencies();
+                task_manager.derive_from_dependencies();
+                if let Some(script) = &script {
+                    task_manager.apply_script(script, &config);
+                }
+                task_manager.apply_rules(&rules, &config);
+                task_manager.sort_by_urgencies();
+                task_manager.run_report(&report, options.clone(), &config, false);
+            }
+            Err(err) => eprintln!(
+                "{}",
+                tr!(
+                    "live_unreadable_data",
+                    "Failed to read {0}: {1}",
+                    data_file.display(),
+                    err
+                )
+            ),
+        }
+        stdout().flush()?;
+        let refresh_at = Instant::now() + Duration::from_secs(interval);
+        while Instant::now() < refresh_at
+            && modified(data_file) == last_modified
+            && !watch.changed()
+        {
+            sleep(Duration::from_millis(POLL_MILLISECONDS));
+        }
+    }
+}
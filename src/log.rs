@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::dates::to_local;
+use crate::error::TaskError;
+use crate::history::Change;
+use crate::scheduled::parse_scheduled;
+use crate::{Status, Task, TaskManager};
+
+fn status_change(old: Status, new: Status, time: DateTime<Utc>) -> Change {
+    Change {
+        field: "status".to_string(),
+        old_value: old.as_str().to_string(),
+        new_value: new.as_str().to_string(),
+        time,
+    }
+}
+
+// When a logged task was done, never later than now. A bare "today" is now rather than the start
+// of the working day, which may still be to come
+pub fn parse_completed_at(
+    input: &str,
+    input_format: &str,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, TaskError> {
+    let completed_at = parse_scheduled(input, input_format)?;
+    if completed_at <= now {
+        return Ok(completed_at);
+    }
+    if !input.contains(':') && to_local(completed_at).date() == to_local(now).date() {
+        return Ok(now);
+    }
+    Err(TaskError::CompletedInFuture(input.to_string()))
+}
+
+impl TaskManager {
+    // Adds a task that is already done. With time spent it was active for that long before it
+    // was completed, so it counts towards active time goals like a tracked task
+    pub fn log_task(
+        &mut self,
+        title: String,
+        completed_at: DateTime<Utc>,
+        spent: Option<Duration>,
+    ) -> usize {
+        let started_at = completed_at - spent.unwrap_or_else(Duration::zero);
+        let mut task = Task::new(title);
+        task.start_time = Some(started_at);
+        task.created_at = Some(started_at);
+        task.modified_at = Some(completed_at);
+        task.urgency = 0.0;
+        task.effective_urgency = 0.0;
+        task.status = Status::Done;
+        task.history = match spent {
+            Some(_) => vec![
+                status_change(Status::Inactive, Status::Active, started_at),
+                status_change(Status::Active, Status::Done, completed_at),
+            ],
+            None => vec![status_change(Status::Inactive, Status::Done, completed_at)],
+        };
+        self.push_task(task);
+        self.tasks.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_completed_at;
+    use crate::dates::from_local;
+    use crate::error::TaskError;
+    use crate::{Status, TaskManager};
+    use chrono::{Duration, Local, Utc};
+
+    #[test]
+    fn logged_tasks_are_done_when_given() {
+        let mut task_manager = TaskManager::new();
+        let yesterday = Utc::now() - Duration::days(1);
+        let id = task_manager.log_task(
+            "Fixed the printer".to_string(),
+            yesterday,
+            Some(Duration::minutes(45)),
+        );
+        let task = &task_manager.tasks[id];
+        assert_eq!(task.status, Status::Done);
+        assert_eq!(task.completed_at(), Some(yesterday));
+        assert_eq!(task.created_at, Some(yesterday - Duration::minutes(45)));
+        assert_eq!(task.history.len(), 2);
+    }
+
+    #[test]
+    fn completion_times_are_not_in_the_future() {
+        let now = from_local(Local::now().date_naive().and_hms_opt(8, 0, 0).unwrap());
+        assert_eq!(parse_completed_at("today", "%d/%m/%Y", now), Ok(now));
+        assert!(parse_completed_at("yesterday 16:00", "%d/%m/%Y", now).unwrap() < now);
+        assert_eq!(
+            parse_completed_at("tomorrow", "%d/%m/%Y", now),
+            Err(TaskError::CompletedInFuture("tomorrow".to_string()))
+        );
+        assert!(parse_completed_at("today 17:00", "%d/%m/%Y", now).is_err());
+    }
+}
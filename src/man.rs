@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::fs::write;
+use std::path::Path;
+use structopt::clap::ErrorKind;
+use structopt::StructOpt;
+
+use crate::Opt;
+
+// Shown after tasks --help and as the EXAMPLES section of the man page, one per line with what it
+// does after the #
+pub const EXAMPLES: &str = "EXAMPLES:
+    tasks add \"Renew passport\" -D 01/03/2025    # Add a task due on a day
+    tasks list --sort due                        # List tasks with the soonest due first
+    tasks edit 3 --tag errands --project home    # Tag a task and put it in a project
+    tasks --tag OVERDUE done                     # Complete every overdue task
+    tasks edit 2 --assignee sam                  # Hand a task to someone else
+    tasks --dry-run gc --older-than 90d          # See what cleaning up would delete
+    tasks export json > tasks.json               # Export every task
+    tasks status --short                         # One line for a shell prompt";
+
+// Lines of text starting with . or ' would be read as roff requests
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+fn preformatted(lines: &[&str]) -> String {
+    let mut roff = String::from(".nf\n");
+    for line in lines {
+        roff += &escape(line);
+        roff.push('\n');
+    }
+    roff + ".fi\n"
+}
+
+// The help clap prints for tasks <args> --help
+fn help_text(args: &[&str]) -> String {
+    let args = ["tasks"].iter().chain(args).chain(["--help"].iter());
+    match Opt::clap().get_matches_from_safe(args) {
+        Err(err) if err.kind == ErrorKind::HelpDisplayed => err.message,
+        _ => String::new(),
+    }
+}
+
+// The lines under a heading like SUBCOMMANDS: up to the next blank line
+fn section<'a>(help: &'a str, heading: &str) -> Vec<&'a str> {
+    help.lines()
+        .skip_while(|line| line.trim() != heading)
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+        .collect()
+}
+
+fn subcommands(help: &str) -> Vec<&str> {
+    section(help, "SUBCOMMANDS:")
+        .into_iter()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| *name != "help")
+        .collect()
+}
+
+// The names of the built in commands, as listed by --help
+pub fn command_names() -> Vec<String> {
+    subcommands(&help_text(&[]))
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+// A section 1 man page built from the same definitions as --help, so the two never disagree
+pub fn man_page() -> String {
+    let help = help_text(&[]);
+    let mut roff = format!(
+        ".TH TASKS 1 \"\" \"tasks {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    roff += ".SH NAME\ntasks \\- another Rust task manager\n";
+    roff += ".SH SYNOPSIS\n.B tasks\n[\\fIFLAGS\\fR] [\\fIOPTIONS\\fR] \\fICOMMAND\\fR\n";
+    roff += ".SH DESCRIPTION\nKeeps a list of tasks ordered by urgency, which rises as tasks age \
+             and near their due time. Flags and options given before the command apply to \
+             every command.\n";
+    roff += ".SH OPTIONS\n";
+    roff += &preformatted(&[section(&help, "FLAGS:"), section(&help, "OPTIONS:")].concat());
+    roff += ".SH COMMANDS\n";
+    for name in subcommands(&help) {
+        let command_help = help_text(&[name]);
+        roff += &format!(".SS {}\n", escape(name));
+        // The first line repeats the name and version
+        let lines: Vec<&str> = command_help.lines().skip(1).collect();
+        roff += &preformatted(&lines);
+    }
+    roff += ".SH EXAMPLES\n";
+    for line in EXAMPLES.lines().skip(1) {
+        let (command, description) = line.split_once('#').unwrap_or((line, ""));
+        roff += &format!(
+            ".TP\n.B {}\n{}\n",
+            escape(command.trim()),
+            escape(description.trim())
+        );
+    }
+    roff += ".SH FILES\n.TP\n.I ~/.local/share/task/task.json\nThe tasks\n.TP\n\
+             .I ~/.config/task/config.json\nSettings, see tasks doctor for where they are read \
+             from\n";
+    roff
+}
+
+pub fn generate_man_page(output: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let roff = man_page();
+    match output {
+        Some(path) => write(path, roff)?,
+        None => print!("{}", roff),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, man_page};
+
+    #[test]
+    fn man_page_covers_every_command() {
+        assert_eq!(escape(".hidden --flag"), "\\&.hidden \\-\\-flag");
+        let roff = man_page();
+        assert!(roff.starts_with(".TH TASKS 1"));
+        for name in ["add", "list", "snapshot", "gen-man"] {
+            assert!(
+                roff.contains(&format!(".SS {}\n", escape(name))),
+                "{}",
+                name
+            );
+        }
+        assert!(roff.contains(".B tasks status \\-\\-short"));
+    }
+}
@@ -0,0 +1,149 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::events::Event;
+use crate::i18n::tr;
+use crate::migrate::migrate;
+use crate::{Task, TaskManager};
+
+// Which side wins when both changed the same task
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefer {
+    Newer,
+    Local,
+    Remote,
+}
+
+impl Prefer {
+    pub fn parse(name: &str) -> Prefer {
+        match name {
+            "local" => Prefer::Local,
+            "remote" => Prefer::Remote,
+            _ => Prefer::Newer,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct MergeReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub conflicts: Vec<String>,
+}
+
+// Another task data file, e.g. a copy synced from a different machine
+pub fn read_task_file(path: &Path) -> Result<TaskManager, Box<dyn Error>> {
+    task_data(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+}
+
+// Task data from elsewhere, brought up to this version first
+pub fn task_data(mut data: Value) -> Result<TaskManager, Box<dyn Error>> {
+    migrate(&mut data)?;
+    Ok(serde_json::from_value(data)?)
+}
+
+// Same task if it has the same uid and creation time, or failing that the same title and creation
+// time, as a task added on both sides can get the same uid
+pub fn same_task(local: &Task, remote: &Task) -> bool {
+    local.created_at == remote.created_at
+        && (local.uid == remote.uid || local.title == remote.title)
+}
+
+fn same_content(local: &Task, remote: &Task) -> bool {
+    serde_json::to_value(local).ok() == serde_json::to_value(remote).ok()
+}
+
+impl TaskManager {
+    // Adds tasks only found remotely, takes remote changes to tasks only changed remotely and
+    // settles tasks changed on both sides by prefer. Nothing changes with dry_run
+    pub fn merge(&mut self, remote: TaskManager, prefer: Prefer, dry_run: bool) -> MergeReport {
+        let mut report = MergeReport::default();
+        for mut remote_task in remote.tasks {
+            let local_id = self
+                .tasks
+                .iter()
+                .position(|local| same_task(local, &remote_task));
+            let id = match local_id {
+                Some(id) => id,
+                None => {
+                    report.added.push(remote_task.title.clone());
+                    if !dry_run {
+                        let uid_taken = self.tasks.iter().any(|task| task.uid == remote_task.uid);
+                        if uid_taken || remote_task.uid == 0 {
+                            self.push_task(remote_task);
+                        } else {
+                            self.next_uid = self.next_uid.max(remote_task.uid + 1);
+                            self.apply(Event::TaskAdded { task: remote_task });
+                        }
+                    }
+                    continue;
+                }
+            };
+            let local = &self.tasks[id];
+            if same_content(local, &remote_task) {
+                continue;
+            }
+            let take_remote = if remote_task.history.starts_with(&local.history)
+                && remote_task.history.len() > local.history.len()
+            {
+                report.updated.push(remote_task.title.clone());
+                true
+            } else if local.history.starts_with(&remote_task.history)
+                && local.history.len() > remote_task.history.len()
+            {
+                false
+            } else {
+                let take_remote = match prefer {
+                    Prefer::Local => false,
+                    Prefer::Remote => true,
+                    Prefer::Newer => remote_task.modified_at > local.modified_at,
+                };
+                let winner = if take_remote { "remote" } else { "local" };
+                report
+                    .conflicts
+                    .push(tr!("merge_kept", "{0} (kept {1})", local.title, winner));
+                take_remote
+            };
+            if take_remote && !dry_run {
+                remote_task.uid = local.uid;
+                self.update_task(id, remote_task);
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MergeReport, Prefer};
+    use crate::TaskManager;
+
+    #[test]
+    fn merges_by_uid_and_settles_conflicts() {
+        let mut local = TaskManager::new();
+        local.add_task("shared".to_string());
+        local.add_task("edited".to_string());
+        let mut remote: TaskManager =
+            serde_json::from_value(serde_json::to_value(&local).unwrap()).unwrap();
+        local.set_urgency(1, 5.0);
+        remote.set_urgency(1, 7.0);
+        remote.set_task_name(0, "shared, renamed".to_string());
+        remote.add_task("remote only".to_string());
+
+        let report = local.merge(remote, Prefer::Local, false);
+        assert_eq!(
+            report,
+            MergeReport {
+                added: vec!["remote only".to_string()],
+                updated: vec!["shared, renamed".to_string()],
+                conflicts: vec!["edited (kept local)".to_string()],
+            }
+        );
+        assert_eq!(local.tasks[0].title, "shared, renamed");
+        assert_eq!(local.tasks[1].urgency, 5.0);
+        assert_eq!(local.tasks[2].uid, 3);
+    }
+}
@@ -0,0 +1,154 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs::copy;
+use std::io;
+use std::path::Path;
+
+use crate::error::TaskError;
+use crate::i18n::tr;
+
+// Version written into the data file, raise it and add a migration when the format changes
+pub const SCHEMA_VERSION: u32 = 2;
+
+// Each migration upgrades a file from the version at its index to the next one
+const MIGRATIONS: [fn(&mut Value); 2] = [fill_missing_timestamps, assign_uids];
+
+// Version 1: tasks saved before created_at existed were created when they started
+fn fill_missing_timestamps(data: &mut Value) {
+    let tasks = match data.get_mut("tasks").and_then(Value::as_array_mut) {
+        Some(tasks) => tasks,
+        None => return,
+    };
+    for task in tasks.iter_mut().filter_map(Value::as_object_mut) {
+        if task.get("created_at").is_none_or(Value::is_null) {
+            let start_time = task.get("start_time").cloned().unwrap_or(Value::Null);
+            task.insert("created_at".to_string(), start_time);
+        }
+        if task.get("modified_at").is_none_or(Value::is_null) {
+            let created_at = task["created_at"].clone();
+            task.insert("modified_at".to_string(), created_at);
+        }
+    }
+}
+
+// Version 2: tasks were only known by their position, give every task a uid that survives sorting
+fn assign_uids(data: &mut Value) {
+    let mut next_uid = 1;
+    for (key, nested) in [("tasks", false), ("trash", true), ("archive", true)] {
+        let entries = match data.get_mut(key).and_then(Value::as_array_mut) {
+            Some(entries) => entries,
+            None => continue,
+        };
+        for (position, entry) in entries.iter_mut().enumerate() {
+            let task = if nested { &mut entry["task"] } else { entry };
+            if let Some(task) = task.as_object_mut() {
+                task.insert("uid".to_string(), json!(next_uid));
+                if !nested {
+                    let title = task
+                        .get("title")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default();
+                    println!(
+                        "{}",
+                        tr!(
+                            "migrate_new_uid",
+                            "Task {0} ({1}) now has uid #{2}",
+                            position,
+                            title,
+                            next_uid
+                        )
+                    );
+                }
+                next_uid += 1;
+            }
+        }
+    }
+    data["next_uid"] = json!(next_uid);
+}
+
+#[derive(Deserialize)]
+struct Version {
+    #[serde(default)]
+    version: u32,
+}
+
+// Whether the data in text needs no migrating, read without building the whole file as a Value
+pub fn is_current(text: &str) -> bool {
+    serde_json::from_str::<Version>(text).is_ok_and(|data| data.version == SCHEMA_VERSION)
+}
+
+fn data_version(data: &Value) -> u32 {
+    data.get("version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+// Keeps a copy of the file as it was, e.g. task.json.v1.bak, before an older version is upgraded
+pub fn backup_before_migrating(path: &Path, data: &Value) -> io::Result<()> {
+    let version = data_version(data);
+    if version < SCHEMA_VERSION {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", version));
+        copy(path, &backup)?;
+        println!(
+            "{}",
+            tr!(
+                "migrate_upgrading",
+                "Upgrading task data from version {0}, the original was kept in {1}",
+                version,
+                Path::new(&backup).display()
+            )
+        );
+    }
+    Ok(())
+}
+
+// Brings data from any older version up to date, data from a newer version is refused so it
+// isn't saved back without the fields this version doesn't know about
+pub fn migrate(data: &mut Value) -> Result<(), TaskError> {
+    let version = data_version(data);
+    if version > SCHEMA_VERSION {
+        return Err(TaskError::NewerDataVersion(version));
+    }
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(data);
+    }
+    data["version"] = json!(SCHEMA_VERSION);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{migrate, SCHEMA_VERSION};
+    use crate::error::TaskError;
+    use serde_json::json;
+
+    #[test]
+    fn upgrades_unversioned_data() {
+        let mut data = json!({"tasks": [{"title": "old", "start_time": "2024-03-01T09:30:00Z"}]});
+        migrate(&mut data).unwrap();
+        assert_eq!(data["version"], SCHEMA_VERSION);
+        assert_eq!(data["tasks"][0]["created_at"], "2024-03-01T09:30:00Z");
+        assert_eq!(data["tasks"][0]["modified_at"], "2024-03-01T09:30:00Z");
+    }
+
+    #[test]
+    fn assigns_uids_to_every_task() {
+        let mut data = json!({
+            "version": 1,
+            "tasks": [{"title": "first"}, {"title": "second"}],
+            "trash": [{"task": {"title": "removed"}}]
+        });
+        migrate(&mut data).unwrap();
+        assert_eq!(data["tasks"][1]["uid"], 2);
+        assert_eq!(data["trash"][0]["task"]["uid"], 3);
+        assert_eq!(data["next_uid"], 4);
+    }
+
+    #[test]
+    fn refuses_newer_data() {
+        let mut data = json!({"version": SCHEMA_VERSION + 1, "tasks": []});
+        assert_eq!(
+            migrate(&mut data),
+            Err(TaskError::NewerDataVersion(SCHEMA_VERSION + 1))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::pick::run_editor;
+use crate::{Task, TaskManager};
+
+// Lines of the notes view shows, the rest are left for the editor
+const PREVIEW_LINES: usize = 5;
+
+impl Task {
+    // The first lines of the notes with text on them, and how many more there are
+    pub fn notes_preview(&self) -> Option<(Vec<String>, usize)> {
+        let text = read_to_string(self.notes.as_ref()?).ok()?;
+        let lines: Vec<String> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        let more = lines.len().saturating_sub(PREVIEW_LINES);
+        Some((lines.into_iter().take(PREVIEW_LINES).collect(), more))
+    }
+}
+
+impl TaskManager {
+    // Notes live in notes/<uid>.md next to the data file, made with the title as a heading the
+    // first time they are opened
+    pub fn edit_notes(&mut self, id: usize, data_dir: &Path) -> Result<(), Box<dyn Error>> {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return Ok(());
+        }
+        let task = &mut self.tasks[id];
+        let path = match &task.notes {
+            Some(path) => path.clone(),
+            None => {
+                let directory = data_dir.join("notes");
+                create_dir_all(&directory)?;
+                let path = directory.join(format!("{}.md", task.uid));
+                task.record_change("notes", "none".to_string(), path.display().to_string());
+                self.set_field(id, FieldValue::Notes(Some(path.clone())));
+                path
+            }
+        };
+        if !path.exists() {
+            write(&path, format!("# {}\n\n", self.tasks[id].title))?;
+        }
+        if let Err(message) = run_editor(&path) {
+            eprintln!("{}", message);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TaskManager;
+    use std::env::temp_dir;
+    use std::fs::{read_to_string, remove_dir_all, write};
+
+    #[test]
+    fn notes_are_made_next_to_the_data_and_previewed() {
+        let data_dir = temp_dir().join(format!("tasks-notes-{}", std::process::id()));
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Compare laptops".to_string());
+        // true exits straight away, standing in for an editor
+        std::env::set_var("VISUAL", "true");
+        task_manager.edit_notes(0, &data_dir).unwrap();
+        let path = task_manager.tasks[0].notes.clone().unwrap();
+        assert_eq!(path, data_dir.join("notes").join("1.md"));
+        assert_eq!(read_to_string(&path).unwrap(), "# Compare laptops\n\n");
+
+        let notes: String = (1..=7)
+            .map(|line| format!("- option {}\n\n", line))
+            .collect();
+        write(&path, format!("# Compare laptops\n\n{}", notes)).unwrap();
+        let (lines, more) = task_manager.tasks[0].notes_preview().unwrap();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "- option 1");
+        assert_eq!(more, 3);
+        remove_dir_all(&data_dir).unwrap();
+    }
+}
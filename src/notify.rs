@@ -0,0 +1,138 @@
+use chrono::{Duration, Local, Utc};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::dates::{format_due, to_local};
+use crate::events::FieldValue;
+use crate::i18n::tr;
+use crate::{Status, TaskManager};
+
+const DUE_SOON_HOURS: i64 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Overdue,
+    DueToday,
+    // One of the task's own reminders went off, see reminders
+    Reminder,
+    DueSoon,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Overdue => "overdue",
+            Severity::DueToday => "due-today",
+            Severity::Reminder => "reminder",
+            Severity::DueSoon => "due-soon",
+        }
+    }
+}
+
+// Sends a desktop notification, printing it instead where notify-send isn't available
+fn send(summary: &str, body: &str) {
+    let sent = Command::new("notify-send")
+        .arg(summary)
+        .arg(body)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !sent {
+        println!("{}\n{}", summary, body);
+    }
+}
+
+impl TaskManager {
+    // Open tasks worth a notification, most severe first
+    pub fn notifications(&self) -> Vec<(usize, Severity)> {
+        let now = Utc::now();
+        let today = Local::now().date_naive();
+        let mut notifications: Vec<(usize, Severity)> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.status != Status::Done)
+            .filter_map(|(index, task)| {
+                let due_time = task.due_time?;
+                let severity = if due_time < now {
+                    Severity::Overdue
+                } else if !task.reminders.is_empty() {
+                    // A task's own reminders take the place of due today and due soon
+                    task.pending_reminder(now)?;
+                    Severity::Reminder
+                } else if to_local(due_time).date() == today {
+                    Severity::DueToday
+                } else if due_time - now < Duration::hours(DUE_SOON_HOURS) {
+                    Severity::DueSoon
+                } else {
+                    return None;
+                };
+                Some((index, severity))
+            })
+            .collect();
+        notifications.sort_by_key(|(_, severity)| *severity as u8);
+        notifications
+    }
+
+    // One notification per task, or with digest a single summary of the severities listed in config.
+    // Reminders sent are noted on their tasks so they aren't sent again
+    pub fn notify(&mut self, digest: bool, config: &Config) {
+        // Out of office and quiet hours suppress every notification, see ooo
+        if self.out_of_office_active() || config.quiet_at(Local::now().time()) {
+            return;
+        }
+        let now = Utc::now();
+        let mut digested = Vec::new();
+        for (index, severity) in self.notifications() {
+            if severity == Severity::Reminder {
+                self.set_field(index, FieldValue::Reminded(Some(now)));
+            }
+            let task = &self.tasks[index];
+            let line = format!(
+                "-{}- {} ({})",
+                index,
+                task.title,
+                format_due(task.due_time.unwrap(), now)
+            );
+            let grouped = config
+                .digest_severities
+                .iter()
+                .any(|name| name == severity.name());
+            if digest && grouped {
+                digested.push((severity, line));
+            } else {
+                send(&task.title, &line);
+            }
+        }
+        let unmet = self.unmet_goals();
+        if !unmet.is_empty() {
+            send(
+                "Weekly goals",
+                &tr!(
+                    "goal_week_ending",
+                    "The week is ending, {0}",
+                    unmet.join(", ")
+                ),
+            );
+        }
+        if digested.is_empty() {
+            return;
+        }
+        let mut counts = Vec::new();
+        for severity in [
+            Severity::Overdue,
+            Severity::DueToday,
+            Severity::Reminder,
+            Severity::DueSoon,
+        ] {
+            let count = digested.iter().filter(|(s, _)| *s == severity).count();
+            if count > 0 {
+                counts.push(format!("{} {}", count, severity.name()));
+            }
+        }
+        let lines: Vec<&str> = digested.iter().map(|(_, line)| line.as_str()).collect();
+        send(
+            &tr!("digest_tasks", "Tasks: {0}", counts.join(", ")),
+            &lines.join("\n"),
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dates::{from_local, to_local};
+use crate::error::TaskError;
+use crate::events::{Event, FieldValue};
+use crate::history::history_datetime;
+use crate::i18n::{self, tr};
+use crate::{Status, TaskManager};
+
+// A planned absence, urgencies stop escalating and soft due dates are moved out of the way
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutOfOffice {
+    pub start: NaiveDate,
+    pub until: NaiveDate,
+    // Human readable notes of what was changed, shown when the period ends
+    pub adjustments: Vec<String>,
+}
+
+impl OutOfOffice {
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        self.start <= today && today <= self.until
+    }
+
+    // First weekday after the period, where soft due dates are moved to
+    fn next_working_day(&self) -> NaiveDate {
+        let mut day = self.until + Duration::days(1);
+        while day.weekday() == Weekday::Sat || day.weekday() == Weekday::Sun {
+            day += Duration::days(1);
+        }
+        day
+    }
+}
+
+impl TaskManager {
+    pub fn out_of_office_active(&self) -> bool {
+        let today = Local::now().date_naive();
+        match &self.out_of_office {
+            Some(out_of_office) => out_of_office.is_active(today),
+            None => false,
+        }
+    }
+
+    pub fn start_out_of_office(&mut self, start: NaiveDate, until: NaiveDate, config: &Config) {
+        if until < start {
+            eprintln!("{}", TaskError::OutOfOfficeEndsBeforeStart);
+            return;
+        }
+        let mut out_of_office = OutOfOffice {
+            start,
+            until,
+            adjustments: Vec::new(),
+        };
+        let new_due_date = out_of_office.next_working_day();
+        let mut moved = Vec::new();
+        for task in self.tasks.iter_mut() {
+            if task.status == Status::Done || !task.soft_due {
+                continue;
+            }
+            if let Some(due_time) = task.due_time {
+                let local_due_time = to_local(due_time);
+                if start <= local_due_time.date() && local_due_time.date() <= until {
+                    let new_due_time = from_local(new_due_date.and_time(local_due_time.time()));
+                    out_of_office.adjustments.push(tr!(
+                        "due_moved",
+                        "{0} moved from {1} to {2}",
+                        task.title,
+                        config.format_date(local_due_time.date()),
+                        config.format_date(new_due_date)
+                    ));
+                    task.record_change(
+                        "due",
+                        history_datetime(Some(due_time)),
+                        history_datetime(Some(new_due_time)),
+                    );
+                    moved.push(Event::DueChanged {
+                        uid: task.uid,
+                        due: Some(new_due_time),
+                    });
+                }
+            }
+        }
+        for event in moved {
+            self.apply(event);
+        }
+        println!(
+            "{}",
+            tr!(
+                "ooo_status",
+                "Out of office from {0} until {1}",
+                config.format_date(start),
+                config.format_date(until)
+            )
+        );
+        for adjustment in out_of_office.adjustments.iter() {
+            println!("  {}", adjustment);
+        }
+        self.out_of_office = Some(out_of_office);
+    }
+
+    // Closes a period once it is over, shifting ages so time away doesn't count towards urgency
+    pub fn finish_out_of_office(&mut self, force: bool, config: &Config) {
+        let today = Local::now().date_naive();
+        let out_of_office = match self.out_of_office.take() {
+            Some(out_of_office) if force || today > out_of_office.until => out_of_office,
+            other => {
+                self.out_of_office = other;
+                return;
+            }
+        };
+        let last_day = if today > out_of_office.until {
+            out_of_office.until
+        } else {
+            today
+        };
+        let paused = (last_day - out_of_office.start) + Duration::days(1);
+        let mut overdue = 0;
+        let mut shifted = Vec::new();
+        for task in self.tasks.iter() {
+            if task.status == Status::Done {
+                continue;
+            }
+            match task.due_time {
+                Some(due_time) => {
+                    if due_time < Utc::now() {
+                        overdue += 1;
+                    }
+                }
+                None => {
+                    if let Some(start_time) = task.start_time {
+                        if to_local(start_time).date() < out_of_office.start
+                            && paused > Duration::zero()
+                        {
+                            shifted.push(Event::FieldChanged {
+                                uid: task.uid,
+                                change: FieldValue::StartTime(Some(start_time + paused)),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        for event in shifted {
+            self.apply(event);
+        }
+        println!(
+            "{}",
+            tr!(
+                "ooo_ended",
+                "Welcome back, out of office {0} - {1} has ended",
+                config.format_date(out_of_office.start),
+                config.format_date(out_of_office.until)
+            )
+        );
+        if out_of_office.adjustments.is_empty() {
+            println!(
+                "{}",
+                i18n::text("no_due_dates_moved", "  No due dates were moved")
+            );
+        }
+        for adjustment in out_of_office.adjustments.iter() {
+            println!("  {}", adjustment);
+        }
+        if overdue > 0 {
+            println!("{}", tr!("ooo_overdue", "  {0} tasks are overdue", overdue));
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use term_size::dimensions;
+
+const DEFAULT_PAGER: &str = "less -R";
+
+// Prints output, through $PAGER when stdout is a terminal too short to show all of it
+pub fn print_paged(output: &str) {
+    let too_tall = match dimensions() {
+        Some((_, height)) => output.lines().count() >= height,
+        None => false,
+    };
+    if too_tall {
+        let pager = env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&pager)
+            .stdin(Stdio::piped())
+            .spawn();
+        if let Ok(mut child) = child {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager closing early is not an error
+                let _ = stdin.write_all(output.as_bytes());
+            }
+            if child.wait().is_ok() {
+                return;
+            }
+        }
+    }
+    print!("{}", output);
+}
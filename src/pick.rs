@@ -0,0 +1,178 @@
+use std::env::{temp_dir, var};
+use std::error::Error;
+use std::fs::{read_to_string, remove_file, write};
+use std::io::{stdin, stdout, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use crate::i18n::{self, tr};
+use crate::{Status, TaskManager};
+
+// Matches shown at once, more can be reached by typing more of the query
+const PICK_LIMIT: usize = 10;
+
+// Score of the query's characters matched in order from start, taking the first of each
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<i32> {
+    let mut score = 0;
+    let mut position = start;
+    let mut previous: Option<usize> = None;
+    for wanted in query {
+        let found = position + text[position..].iter().position(|c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+// Characters of the query in order anywhere in the text, e.g. "wrp" in "Write report". Runs of
+// consecutive characters and word starts score higher, None when the query doesn't match
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let first = match query.first() {
+        Some(first) => first,
+        None => return Some(0),
+    };
+    // Every place the query could start, so "rep" prefers report over the r in write
+    (0..text.len())
+        .filter(|&start| text[start] == *first)
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+pub fn prompt(question: &str) -> Option<String> {
+    print!("{} ", question);
+    let _ = stdout().flush();
+    let mut answer = String::new();
+    match stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(answer.trim().to_string()),
+    }
+}
+
+// Opens the file in $VISUAL or $EDITOR and waits for it to close, the error says what failed
+pub fn run_editor(path: &Path) -> Result<ExitStatus, String> {
+    let editor = var("VISUAL")
+        .or_else(|_| var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // e.g. "code --wait", the file goes after any arguments
+    let mut words = editor.split_whitespace();
+    Command::new(words.next().unwrap_or("vi"))
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|err| tr!("editor_failed", "Failed to run {0}: {1}", editor, err))
+}
+
+impl TaskManager {
+    // Open tasks matching the query, best first and by urgency among equal scores
+    pub fn fuzzy_matches(&self, query: &str) -> Vec<usize> {
+        let mut scored: Vec<(i32, usize)> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| task.status != Status::Done)
+            .filter_map(|(index, task)| {
+                let text = format!("{} {}", task.title, task.description);
+                fuzzy_score(query, &text).map(|score| (score, index))
+            })
+            .collect();
+        scored.sort_by_key(|(score, index)| (-score, *index));
+        scored.into_iter().map(|(_, index)| index).collect()
+    }
+
+    // Asks for a search until one of the numbered matches is picked, None when cancelled
+    pub fn pick(&self) -> Option<usize> {
+        let mut query = prompt(&i18n::text("pick_search", "Search:"))?;
+        loop {
+            let matches = self.fuzzy_matches(&query);
+            if matches.is_empty() {
+                println!(
+                    "{}",
+                    tr!("no_open_tasks_match", "No open tasks match {0}", query)
+                );
+            }
+            for (number, &id) in matches.iter().take(PICK_LIMIT).enumerate() {
+                println!(" {}) -{}- {}", number + 1, id, self.tasks[id].title);
+            }
+            let answer = prompt(&i18n::text(
+                "pick_number",
+                "Number to pick, or a new search (empty cancels):",
+            ))?;
+            if answer.is_empty() {
+                return None;
+            }
+            match answer.parse::<usize>() {
+                Ok(number) if (1..=matches.len().min(PICK_LIMIT)).contains(&number) => {
+                    return Some(matches[number - 1])
+                }
+                _ => query = answer,
+            }
+        }
+    }
+
+    // Title on the first line and the description after a blank line, like a commit message
+    pub fn edit_in_editor(&mut self, id: usize) -> Result<(), Box<dyn Error>> {
+        let task = &self.tasks[id];
+        let path = temp_dir().join(format!("tasks-edit-{}.txt", task.uid));
+        write(&path, format!("{}\n\n{}\n", task.title, task.description))?;
+        let status = match run_editor(&path) {
+            Ok(status) => status,
+            Err(message) => {
+                remove_file(&path)?;
+                eprintln!("{}", message);
+                return Ok(());
+            }
+        };
+        let edited = read_to_string(&path)?;
+        remove_file(&path)?;
+        if !status.success() {
+            return Ok(());
+        }
+        let (title, description) = edited.split_once('\n').unwrap_or((&edited, ""));
+        let title = title.trim();
+        if title.is_empty() {
+            println!(
+                "{}",
+                i18n::text("pick_empty_title", "Empty title, nothing was changed")
+            );
+            return Ok(());
+        }
+        let description = description.trim_matches(['\n', '\r']).to_string();
+        if title != self.tasks[id].title {
+            self.set_task_name(id, title.to_string());
+        }
+        if description != self.tasks[id].description {
+            self.set_task_description(id, description);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+    use crate::TaskManager;
+
+    #[test]
+    fn fuzzy_matches_rank_closer_matches_first() {
+        assert!(fuzzy_score("wrp", "Write report").is_some());
+        assert_eq!(fuzzy_score("xyz", "Write report"), None);
+        assert!(fuzzy_score("rep", "Write report") > fuzzy_score("rep", "Rewrite the plan"));
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Renew passport".to_string());
+        task_manager.add_task("Report expenses".to_string());
+        assert_eq!(task_manager.fuzzy_matches("rep"), vec![1, 0]);
+    }
+}
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+
+use crate::config::Config;
+use crate::dates::to_local;
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::i18n::{self, tr};
+use crate::{Status, TaskManager};
+
+fn planned_value(planned_on: Option<NaiveDate>) -> String {
+    match planned_on {
+        Some(planned_on) => planned_on.format("%d/%m/%Y").to_string(),
+        None => "none".to_string(),
+    }
+}
+
+impl TaskManager {
+    pub fn set_planned_on(&mut self, id: usize, planned_on: Option<NaiveDate>) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "planned",
+                planned_value(task.planned_on),
+                planned_value(planned_on),
+            );
+            self.set_field(id, FieldValue::PlannedOn(planned_on));
+            self.set_field(id, FieldValue::Rollovers(0));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // Unfinished tasks planned for a past day move to today, counting the days carried
+    pub fn roll_forward_plans(&mut self) {
+        let today = Local::now().date_naive();
+        for id in 0..self.tasks.len() {
+            let task = &self.tasks[id];
+            if task.status == Status::Done {
+                continue;
+            }
+            if let Some(planned_on) = task.planned_on {
+                if planned_on < today {
+                    let rollovers = task.rollovers + (today - planned_on).num_days() as u32;
+                    self.set_field(id, FieldValue::Rollovers(rollovers));
+                    self.set_field(id, FieldValue::PlannedOn(Some(today)));
+                }
+            }
+        }
+    }
+
+    pub fn show_today(&self, config: &Config) {
+        let today = Local::now().date_naive();
+        println!("{}", i18n::text("planned_today", "Planned today:"));
+        let mut any_planned = false;
+        for (index, task) in self.tasks.iter().enumerate() {
+            if task.status == Status::Done || task.planned_on != Some(today) {
+                continue;
+            }
+            any_planned = true;
+            if task.rollovers > 0 {
+                println!(
+                    "{}",
+                    tr!(
+                        "carried_over_days",
+                        " -{0}- {1} (carried over {2} days)",
+                        index,
+                        task.title,
+                        task.rollovers
+                    )
+                );
+            } else {
+                println!(" -{}- {}", index, task.title);
+            }
+        }
+        if !any_planned {
+            println!("{}", i18n::text("nothing_planned", "  Nothing planned"));
+        }
+
+        println!("{}", i18n::text("scheduled_today", "Scheduled today:"));
+        let mut any_scheduled = false;
+        let now = Utc::now();
+        for (index, task) in self.tasks.iter().enumerate() {
+            let scheduled = match task.scheduled {
+                Some(scheduled) if task.status != Status::Done => scheduled,
+                _ => continue,
+            };
+            // Earlier days are included until the task is done
+            if to_local(scheduled).date() > today {
+                continue;
+            }
+            any_scheduled = true;
+            if task.is_waiting(now) {
+                println!(
+                    " -{}- {} at {}",
+                    index,
+                    task.title,
+                    config.format_time(scheduled)
+                );
+            } else {
+                println!(
+                    "{}",
+                    tr!(
+                        "plan_scheduled_since",
+                        " -{0}- {1} since {2}",
+                        index,
+                        task.title,
+                        config.format_datetime(scheduled)
+                    )
+                );
+            }
+        }
+        if !any_scheduled {
+            println!("{}", i18n::text("nothing_scheduled", "  Nothing scheduled"));
+        }
+
+        println!("{}", i18n::text("plan_due_today", "Due today:"));
+        let mut any_due = false;
+        for (index, task) in self.tasks.iter().enumerate() {
+            let due_today = task.due_time.map(|due_time| to_local(due_time).date()) == Some(today);
+            if task.status == Status::Done || !due_today {
+                continue;
+            }
+            any_due = true;
+            println!(
+                " -{}- {} at {}",
+                index,
+                task.title,
+                config.format_time(task.due_time.unwrap())
+            );
+        }
+        if !any_due {
+            println!("{}", i18n::text("nothing_due", "  Nothing due"));
+        }
+
+        if !self.goals.is_empty() {
+            println!("{}", i18n::text("plan_this_week", "This week:"));
+            for line in self.goal_progress() {
+                println!(" {}", line);
+            }
+        }
+    }
+
+    // Each of the next seven days with what is planned, scheduled or due, days off are marked
+    pub fn show_week(&self, config: &Config) {
+        let today = Local::now().date_naive();
+        let work_days = config.work_days();
+        let holidays = config.holidays();
+        for offset in 0..7 {
+            let day = today + Duration::days(offset);
+            let mark = if holidays.contains(&day) {
+                " (holiday)"
+            } else if !work_days.contains(&day.weekday()) {
+                " (day off)"
+            } else {
+                ""
+            };
+            println!("{} {}{}:", day.format("%a"), config.format_date(day), mark);
+            let mut any = false;
+            for (index, task) in self.tasks.iter().enumerate() {
+                if task.status == Status::Done {
+                    continue;
+                }
+                let on_day = |time: Option<DateTime<Utc>>| {
+                    time.is_some_and(|time| to_local(time).date() == day)
+                };
+                let reason = if on_day(task.due_time) {
+                    tr!(
+                        "plan_due",
+                        "due {0}",
+                        config.format_time(task.due_time.unwrap())
+                    )
+                } else if on_day(task.scheduled) {
+                    tr!(
+                        "plan_scheduled",
+                        "scheduled {0}",
+                        config.format_time(task.scheduled.unwrap())
+                    )
+                } else if task.planned_on == Some(day) {
+                    "planned".to_string()
+                } else {
+                    continue;
+                };
+                any = true;
+                println!(" -{}- {} ({})", index, task.title, reason);
+            }
+            if !any {
+                println!("{}", i18n::text("plan_nothing_this_week", "  Nothing"));
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::io::{stdin, ErrorKind, IsTerminal};
+use std::path::Path;
+use std::process::{exit, Command};
+
+use crate::config::{config_path, Config};
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::man::command_names;
+use crate::suggest::closest_command;
+use crate::{confirm, Opt};
+
+fn flag(set: bool) -> &'static str {
+    if set {
+        "1"
+    } else {
+        "0"
+    }
+}
+
+// tasks standup --week runs task-standup --week from PATH, like git runs git-<name>. Where the data
+// is and the global flags given before the command are passed in the environment
+fn plugin_command(args: &[String], data_file: &Path, opt: &Opt) -> Command {
+    let mut command = Command::new(format!("task-{}", args[0]));
+    command
+        .args(&args[1..])
+        .env("TASK_DATA_FILE", data_file)
+        .env("TASK_QUIET", flag(opt.quiet))
+        .env("TASK_VERBOSE", flag(opt.verbose))
+        .env("TASK_DRY_RUN", flag(opt.dry_run))
+        .env(
+            "TASK_FILTER",
+            opt.selection.expression().unwrap_or_default(),
+        );
+    if let Some(config_file) = config_path("config.json") {
+        command.env("TASK_CONFIG_FILE", config_file);
+    }
+    command
+}
+
+// Exits with the plugin's status when it fails, so scripts see the same result
+pub fn run_plugin(
+    args: &[String],
+    argv: &[String],
+    data_file: &Path,
+    opt: &Opt,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    match plugin_command(args, data_file, opt).status() {
+        Ok(status) if !status.success() => exit(status.code().unwrap_or(1)),
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            eprintln!("{}", TaskError::UnknownCommand(args[0].clone()));
+            suggest_command(args, argv, config)?;
+            // A mistyped command fails like any other bad command line, so scripts notice
+            exit(1)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+// Suggests the command or alias closest to an unknown one, with the same arguments, and offers
+// to run it when someone is there to answer. argv is the whole command line, ending with args
+fn suggest_command(
+    args: &[String],
+    argv: &[String],
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    let mut commands = command_names();
+    commands.extend(config.aliases.keys().cloned());
+    let command = match closest_command(&args[0], &commands) {
+        Some(command) => command,
+        None => return Ok(()),
+    };
+    let before = &argv[1..argv.len() - args.len()];
+    let corrected: Vec<&str> = before
+        .iter()
+        .map(String::as_str)
+        .chain([command])
+        .chain(args[1..].iter().map(String::as_str))
+        .collect();
+    let suggestion = tr!(
+        "plugin_did_you_mean",
+        "Did you mean tasks {0}?",
+        corrected.join(" ")
+    );
+    if !stdin().is_terminal() {
+        eprintln!("{}", suggestion);
+        return Ok(());
+    }
+    if confirm(&suggestion) {
+        let status = Command::new(std::env::current_exe()?)
+            .args(&corrected)
+            .status()?;
+        exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::plugin_command;
+    use crate::{Command, Opt};
+    use std::ffi::OsStr;
+    use std::path::Path;
+    use structopt::StructOpt;
+
+    #[test]
+    fn unknown_commands_run_plugins_with_the_flags() {
+        let opt = Opt::from_iter([
+            "tasks",
+            "--dry-run",
+            "--tag",
+            "OVERDUE",
+            "standup",
+            "--week",
+        ]);
+        let args = match &opt.command {
+            Command::External(args) => args.clone(),
+            command => panic!("parsed as {:?}", command),
+        };
+        assert_eq!(args, vec!["standup", "--week"]);
+        let command = plugin_command(&args, Path::new("/data/task.json"), &opt);
+        assert_eq!(command.get_program(), "task-standup");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["--week"]);
+        let env = |name: &str| {
+            command
+                .get_envs()
+                .find(|(key, _)| *key == name)
+                .and_then(|(_, value)| value)
+        };
+        assert_eq!(env("TASK_DATA_FILE"), Some(OsStr::new("/data/task.json")));
+        assert_eq!(env("TASK_DRY_RUN"), Some(OsStr::new("1")));
+        assert_eq!(env("TASK_QUIET"), Some(OsStr::new("0")));
+        assert_eq!(env("TASK_FILTER"), Some(OsStr::new("+OVERDUE")));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::goals::progress_bar;
+use crate::{Status, TaskManager};
+
+// e.g. [############--------] 60%
+pub fn percent_bar(progress: u8) -> String {
+    format!("{} {}%", progress_bar(progress as f64, 100.0), progress)
+}
+
+fn progress_value(progress: Option<u8>) -> String {
+    progress.map_or_else(|| "none".to_string(), |progress| progress.to_string())
+}
+
+impl TaskManager {
+    pub fn set_progress(&mut self, id: usize, progress: Option<u8>) -> Result<(), TaskError> {
+        if !self.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        if let Some(percent) = progress.filter(|percent| *percent > 100) {
+            return Err(TaskError::InvalidProgress(percent));
+        }
+        let task = &mut self.tasks[id];
+        task.record_change(
+            "progress",
+            progress_value(task.progress),
+            progress_value(progress),
+        );
+        self.set_field(id, FieldValue::Progress(progress));
+        Ok(())
+    }
+
+    // Tasks without a progress of their own get the share of the tasks they wait on that are done.
+    // Those no longer in the list were archived or removed, so they count as finished
+    pub fn derive_progress(&mut self) {
+        let derived: Vec<Option<u8>> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                if task.progress.is_some() || task.depends_on.is_empty() {
+                    return task.progress;
+                }
+                let open = self
+                    .dependencies(task)
+                    .iter()
+                    .filter(|(_, dependency)| dependency.status != Status::Done)
+                    .count();
+                let total = task.depends_on.len();
+                Some(((total - open) * 100 / total) as u8)
+            })
+            .collect();
+        for (task, progress) in self.tasks.iter_mut().zip(derived) {
+            task.effective_progress = progress;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::percent_bar;
+    use crate::{Status, TaskManager};
+
+    #[test]
+    fn progress_is_set_or_taken_from_dependencies() {
+        assert_eq!(percent_bar(60), "[############--------] 60%");
+
+        let mut task_manager = TaskManager::new();
+        for title in ["Release", "Write notes", "Tag version", "Draft post"] {
+            task_manager.add_task(title.to_string());
+        }
+        task_manager.set_dependencies(0, &[1, 2], false);
+        task_manager.set_task_status(1, Status::Done);
+        task_manager.set_progress(3, Some(40)).unwrap();
+        assert!(task_manager.set_progress(2, Some(120)).is_err());
+        assert_eq!(task_manager.tasks[2].progress, None);
+        task_manager.derive_progress();
+        let progress: Vec<Option<u8>> = task_manager
+            .tasks
+            .iter()
+            .map(|task| task.effective_progress)
+            .collect();
+        assert_eq!(progress, vec![Some(50), None, None, Some(40)]);
+
+        task_manager.set_progress(0, Some(90)).unwrap();
+        task_manager.derive_progress();
+        assert_eq!(task_manager.tasks[0].effective_progress, Some(90));
+    }
+}
@@ -0,0 +1,436 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc, Weekday};
+
+use crate::dates::{from_local, to_local};
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::i18n::tr;
+use crate::{Task, TaskManager};
+
+// Periods searched for the next occurrence before giving up, e.g. BYMONTHDAY=31 with FREQ=YEARLY
+const MAX_PERIODS: u32 = 1000;
+
+const ORDINALS: [(&str, i32); 5] = [
+    ("first", 1),
+    ("second", 2),
+    ("third", 3),
+    ("fourth", 4),
+    ("last", -1),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// The part of RFC 5545 recurrence rules tasks need: FREQ, INTERVAL, BYDAY, BYMONTHDAY, COUNT and UNTIL
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    // Weekdays, with a position in the month for monthly rules, e.g. 1MO or -1FR
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    // Days of the month, negative ones count from the end
+    pub by_month_day: Vec<i32>,
+    // Occurrences left, counting the current one
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+fn rrule_weekday(code: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, weekday)| *weekday)
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    WEEKDAYS[weekday.num_days_from_monday() as usize].0
+}
+
+// 20240131, or 20240131T170000Z of which the date is enough for due dates
+fn parse_until(value: &str) -> Option<NaiveDate> {
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse().ok();
+    NaiveDate::from_ymd_opt(number(0..4)? as i32, number(4..6)?, number(6..8)?)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let first_of_next = NaiveDate::from_ymd_opt(next_year, next_month, 1).unwrap();
+    first_of_next.pred_opt().unwrap().day()
+}
+
+// The nth weekday of a month, e.g. the first Monday, or counted from the end when negative
+fn nth_weekday(year: i32, month: u32, nth: i32, weekday: Weekday) -> Option<NaiveDate> {
+    let days: Vec<NaiveDate> = (1..=days_in_month(year, month))
+        .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .filter(|date| date.weekday() == weekday)
+        .collect();
+    match nth {
+        n if n > 0 => days.get(n as usize - 1).copied(),
+        n if n < 0 => days.iter().rev().nth((-n) as usize - 1).copied(),
+        _ => None,
+    }
+}
+
+impl Rule {
+    // Either an RRULE such as FREQ=MONTHLY;BYDAY=1MO or a shortcut like "first monday of month"
+    pub fn parse(input: &str) -> Result<Self, TaskError> {
+        let input = input.trim();
+        let rule = input.strip_prefix("RRULE:").unwrap_or(input);
+        if rule.to_uppercase().contains("FREQ=") {
+            Rule::parse_rrule(rule)
+        } else {
+            Rule::parse_shortcut(rule)
+        }
+        .ok_or_else(|| TaskError::InvalidRecurrence(input.to_string()))
+    }
+
+    fn parse_rrule(rule: &str) -> Option<Self> {
+        let mut frequency = None;
+        let mut parsed = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            count: None,
+            until: None,
+        };
+        for part in rule
+            .to_uppercase()
+            .split(';')
+            .filter(|part| !part.is_empty())
+        {
+            let (key, value) = part.split_once('=')?;
+            match key {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => parsed.interval = value.parse().ok().filter(|n| *n > 0)?,
+                "COUNT" => parsed.count = Some(value.parse().ok().filter(|n| *n > 0)?),
+                "UNTIL" => parsed.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        let split = day.len().checked_sub(2)?;
+                        let nth = match &day[..split] {
+                            "" => None,
+                            nth => Some(
+                                nth.trim_start_matches('+')
+                                    .parse()
+                                    .ok()
+                                    .filter(|n: &i32| *n != 0)?,
+                            ),
+                        };
+                        parsed.by_day.push((nth, rrule_weekday(&day[split..])?));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for day in value.split(',') {
+                        let day: i32 = day.parse().ok()?;
+                        if day == 0 || day.abs() > 31 {
+                            return None;
+                        }
+                        parsed.by_month_day.push(day);
+                    }
+                }
+                "WKST" if value == "MO" => {}
+                _ => return None,
+            }
+        }
+        parsed.frequency = frequency?;
+        // Positions in BYDAY only mean something within a month
+        let positioned = parsed.by_day.iter().any(|(nth, _)| nth.is_some());
+        if positioned && parsed.frequency != Frequency::Monthly {
+            return None;
+        }
+        Some(parsed)
+    }
+
+    fn parse_shortcut(input: &str) -> Option<Self> {
+        let rrule = |rule: &str| Rule::parse_rrule(rule);
+        let input = input.to_lowercase();
+        let words: Vec<&str> = input
+            .split_whitespace()
+            .filter(|word| !["the", "of", "every", "each"].contains(word))
+            .collect();
+        match words.as_slice() {
+            ["daily"] | ["day"] => rrule("FREQ=DAILY"),
+            ["weekly"] | ["week"] => rrule("FREQ=WEEKLY"),
+            ["monthly"] | ["month"] => rrule("FREQ=MONTHLY"),
+            ["yearly"] | ["year"] | ["annually"] => rrule("FREQ=YEARLY"),
+            ["weekdays"] | ["weekday"] => rrule("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR"),
+            [weekday] => {
+                let weekday: Weekday = weekday.parse().ok()?;
+                rrule(&format!("FREQ=WEEKLY;BYDAY={}", weekday_code(weekday)))
+            }
+            [interval, unit] => {
+                let interval: u32 = interval.parse().ok()?;
+                let frequency = match unit.trim_end_matches('s') {
+                    "day" => "DAILY",
+                    "week" => "WEEKLY",
+                    "month" => "MONTHLY",
+                    "year" => "YEARLY",
+                    _ => return None,
+                };
+                rrule(&format!("FREQ={};INTERVAL={}", frequency, interval))
+            }
+            [nth, weekday, "month"] => {
+                let (_, nth) = ORDINALS.iter().find(|(name, _)| name == nth)?;
+                let weekday: Weekday = weekday.parse().ok()?;
+                rrule(&format!(
+                    "FREQ=MONTHLY;BYDAY={}{}",
+                    nth,
+                    weekday_code(weekday)
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_rrule(&self) -> String {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        let mut parts = vec![format!("FREQ={}", frequency)];
+        if self.interval != 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(nth, weekday)| match nth {
+                    Some(nth) => format!("{}{}", nth, weekday_code(*weekday)),
+                    None => weekday_code(*weekday).to_string(),
+                })
+                .collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i32::to_string).collect();
+            parts.push(format!("BYMONTHDAY={}", days.join(",")));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", until.format("%Y%m%d")));
+        }
+        parts.join(";")
+    }
+
+    // Dates the rule gives in the period `period` intervals after the one holding start
+    fn candidates(&self, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let steps = (period * self.interval) as i64;
+        let weekdays: Vec<Weekday> = self.by_day.iter().map(|(_, weekday)| *weekday).collect();
+        let mut dates = match self.frequency {
+            Frequency::Daily => {
+                let date = start + Duration::days(steps);
+                if weekdays.is_empty() || weekdays.contains(&date.weekday()) {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(steps);
+                if weekdays.is_empty() {
+                    vec![monday + Duration::days(start.weekday().num_days_from_monday() as i64)]
+                } else {
+                    weekdays
+                        .iter()
+                        .map(|weekday| {
+                            monday + Duration::days(weekday.num_days_from_monday() as i64)
+                        })
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + steps;
+                let (year, month) = ((months / 12) as i32, (months % 12) as u32 + 1);
+                let length = days_in_month(year, month) as i32;
+                let mut dates = Vec::new();
+                for day in self.by_month_day.iter() {
+                    let day = if *day < 0 { length + day + 1 } else { *day };
+                    dates.extend(NaiveDate::from_ymd_opt(year, month, day.max(0) as u32));
+                }
+                for (nth, weekday) in self.by_day.iter() {
+                    match nth {
+                        Some(nth) => dates.extend(nth_weekday(year, month, *nth, *weekday)),
+                        None => dates.extend(
+                            (1..=4)
+                                .filter_map(|nth| nth_weekday(year, month, nth, *weekday))
+                                .chain(nth_weekday(year, month, 5, *weekday)),
+                        ),
+                    }
+                }
+                if self.by_month_day.is_empty() && self.by_day.is_empty() {
+                    // Months without the day are skipped, as in RFC 5545
+                    dates.extend(NaiveDate::from_ymd_opt(year, month, start.day()));
+                }
+                dates
+            }
+            Frequency::Yearly => {
+                NaiveDate::from_ymd_opt(start.year() + steps as i32, start.month(), start.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    // The first occurrence after `after`, counting from start, None once the rule has run out
+    pub fn next_after(&self, start: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        if self.count.is_some_and(|count| count <= 1) {
+            return None;
+        }
+        let next = (0..MAX_PERIODS)
+            .flat_map(|period| self.candidates(start, period))
+            .find(|date| *date > after && *date >= start)?;
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+}
+
+impl TaskManager {
+    pub fn set_recurrence(&mut self, id: usize, rule: Option<&str>) -> Result<(), TaskError> {
+        if !self.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        let rule = match rule.map(Rule::parse) {
+            Some(rule) => Some(rule?.to_rrule()),
+            None => None,
+        };
+        let task = &mut self.tasks[id];
+        task.record_change(
+            "recur",
+            task.recur.clone().unwrap_or_else(|| "none".to_string()),
+            rule.clone().unwrap_or_else(|| "none".to_string()),
+        );
+        self.set_field(id, FieldValue::Recur(rule));
+        Ok(())
+    }
+
+    // Called when a task is completed, adds its next occurrence if it recurs. The next one is
+    // due after the current due date, or after today for tasks without one
+    pub fn create_next_occurrence(&mut self, id: usize) {
+        let task = &self.tasks[id];
+        let rule = match task.recur.as_deref().map(Rule::parse) {
+            Some(Ok(rule)) => rule,
+            _ => return,
+        };
+        let now = Utc::now();
+        let due = to_local(task.due_time.unwrap_or(now));
+        let next_day = match rule.next_after(due.date(), due.date()) {
+            Some(next_day) => next_day,
+            None => return,
+        };
+        let mut next_rule = rule;
+        next_rule.count = next_rule.count.map(|count| count - 1);
+
+        let mut next = Task::new(task.title.clone());
+        next.description = task.description.clone();
+        next.urgency = task.urgency;
+        next.estimate = task.estimate;
+        next.soft_due = task.soft_due;
+        next.reminders = task.reminders.clone();
+        next.urgency_pinned = task.urgency_pinned;
+        next.links = task.links.clone();
+        next.custom = task.custom.clone();
+        next.due_time = Some(from_local(next_day.and_time(due.time())));
+        next.recur = Some(next_rule.to_rrule());
+        println!(
+            "{}",
+            tr!(
+                "next_due",
+                "Next {0} is due {1}",
+                next.title,
+                next_day.format("%d/%m/%Y")
+            )
+        );
+        self.push_task(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rule;
+    use chrono::NaiveDate;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn shortcuts_match_rrules() {
+        let first_monday = Rule::parse("first monday of the month").unwrap();
+        assert_eq!(first_monday, Rule::parse("FREQ=MONTHLY;BYDAY=1MO").unwrap());
+        assert_eq!(first_monday.to_rrule(), "FREQ=MONTHLY;BYDAY=1MO");
+        assert_eq!(
+            Rule::parse("every 2 weeks").unwrap().to_rrule(),
+            "FREQ=WEEKLY;INTERVAL=2"
+        );
+        assert!(Rule::parse("FREQ=HOURLY").is_err());
+        assert!(Rule::parse("FREQ=WEEKLY;BYDAY=1MO").is_err());
+        assert!(Rule::parse("sometimes").is_err());
+    }
+
+    #[test]
+    fn next_occurrences() {
+        let first_monday = Rule::parse("FREQ=MONTHLY;BYDAY=1MO").unwrap();
+        let start = date(2024, 1, 1);
+        assert_eq!(
+            first_monday.next_after(start, start),
+            Some(date(2024, 2, 5))
+        );
+
+        let last_day = Rule::parse("FREQ=MONTHLY;BYMONTHDAY=-1").unwrap();
+        let start = date(2024, 1, 31);
+        assert_eq!(last_day.next_after(start, start), Some(date(2024, 2, 29)));
+
+        let weekdays = Rule::parse("weekdays").unwrap();
+        let friday = date(2024, 1, 5);
+        assert_eq!(weekdays.next_after(friday, friday), Some(date(2024, 1, 8)));
+
+        let fortnightly = Rule::parse("FREQ=WEEKLY;INTERVAL=2;UNTIL=20240120").unwrap();
+        assert_eq!(
+            fortnightly.next_after(friday, friday),
+            Some(date(2024, 1, 19))
+        );
+        let after = date(2024, 1, 19);
+        assert_eq!(fortnightly.next_after(friday, after), None);
+
+        let last = Rule::parse("FREQ=DAILY;COUNT=1").unwrap();
+        assert_eq!(last.next_after(friday, friday), None);
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::dates::parse_duration;
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::{Status, Task, TaskManager};
+
+// A lead time the way it would be given, e.g. 1d 2h or 30m
+pub fn format_lead(lead: Duration) -> String {
+    let minutes = lead.num_minutes();
+    let parts: Vec<String> = [
+        (minutes / (24 * 60), 'd'),
+        (minutes / 60 % 24, 'h'),
+        (minutes % 60, 'm'),
+    ]
+    .iter()
+    .filter(|(amount, _)| *amount > 0)
+    .map(|(amount, unit)| format!("{}{}", amount, unit))
+    .collect();
+    if parts.is_empty() {
+        "0m".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn reminders_value(reminders: &[Duration]) -> String {
+    if reminders.is_empty() {
+        "none".to_string()
+    } else {
+        let leads: Vec<String> = reminders.iter().map(|lead| format_lead(*lead)).collect();
+        leads.join(", ")
+    }
+}
+
+impl Task {
+    // When each reminder goes off, none without a due date
+    pub fn reminder_times(&self) -> Vec<DateTime<Utc>> {
+        match self.due_time {
+            Some(due_time) => self.reminders.iter().map(|lead| due_time - *lead).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // The latest reminder that went off since one was last sent, while the task is open and not
+    // due yet. Reminders missed while notify wasn't running are sent as one
+    pub fn pending_reminder(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.status == Status::Done || self.due_time? <= now {
+            return None;
+        }
+        self.reminder_times()
+            .into_iter()
+            .filter(|time| *time <= now && self.reminded.is_none_or(|reminded| reminded < *time))
+            .max()
+    }
+}
+
+impl TaskManager {
+    // Reminders going off while out of office are dropped rather than sent on return, the way
+    // notify sends nothing while away
+    pub fn drop_reminders_while_away(&mut self) {
+        if !self.out_of_office_active() {
+            return;
+        }
+        let now = Utc::now();
+        for id in 0..self.tasks.len() {
+            if self.tasks[id].pending_reminder(now).is_some() {
+                self.set_field(id, FieldValue::Reminded(Some(now)));
+            }
+        }
+    }
+
+    // Replaces all reminders when clear is set, otherwise adds the new ones, the longest lead
+    // first
+    pub fn set_reminders(
+        &mut self,
+        id: usize,
+        reminders: &[String],
+        clear: bool,
+    ) -> Result<(), TaskError> {
+        if !self.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        let task = &mut self.tasks[id];
+        let mut new_reminders = if clear {
+            Vec::new()
+        } else {
+            task.reminders.clone()
+        };
+        for reminder in reminders {
+            let lead = parse_duration(reminder)
+                .filter(|lead| *lead > Duration::zero())
+                .ok_or_else(|| TaskError::InvalidReminder(reminder.clone()))?;
+            if !new_reminders.contains(&lead) {
+                new_reminders.push(lead);
+            }
+        }
+        new_reminders.sort_by(|a, b| b.cmp(a));
+        task.record_change(
+            "reminders",
+            reminders_value(&task.reminders),
+            reminders_value(&new_reminders),
+        );
+        let seconds = new_reminders.iter().map(Duration::num_seconds).collect();
+        self.set_field(id, FieldValue::Reminders(seconds));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_lead;
+    use crate::config::Config;
+    use crate::error::TaskError;
+    use crate::notify::Severity;
+    use crate::TaskManager;
+    use chrono::{Duration, Local, Utc};
+
+    #[test]
+    fn each_reminder_goes_off_once() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Submit tax return".to_string());
+        let now = Utc::now();
+        let due_time = now + Duration::hours(3);
+        task_manager.tasks[0].due_time = Some(due_time);
+        let leads = ["2h".to_string(), "1d".to_string(), "2h".to_string()];
+        task_manager.set_reminders(0, &leads, false).unwrap();
+        let task = &task_manager.tasks[0];
+        assert_eq!(task.reminders, vec![Duration::days(1), Duration::hours(2)]);
+        assert_eq!(
+            task.pending_reminder(now),
+            Some(due_time - Duration::days(1))
+        );
+        assert_eq!(task_manager.notifications(), vec![(0, Severity::Reminder)]);
+
+        task_manager.tasks[0].reminded = Some(now);
+        let task = &task_manager.tasks[0];
+        assert_eq!(task.pending_reminder(now), None);
+        assert!(task_manager.notifications().is_empty());
+        let later = now + Duration::minutes(90);
+        assert_eq!(
+            task.pending_reminder(later),
+            Some(due_time - Duration::hours(2))
+        );
+        assert_eq!(task.pending_reminder(due_time), None);
+
+        task_manager.tasks[0].reminded = None;
+        let today = Local::now().date_naive();
+        task_manager.start_out_of_office(today, today, &Config::default());
+        task_manager.drop_reminders_while_away();
+        assert!(task_manager.tasks[0].reminded.is_some());
+
+        assert_eq!(
+            task_manager.set_reminders(0, &["soon".to_string()], true),
+            Err(TaskError::InvalidReminder("soon".to_string()))
+        );
+        assert_eq!(task_manager.tasks[0].reminders.len(), 2);
+        assert_eq!(format_lead(Duration::minutes(26 * 60 + 5)), "1d 2h 5m");
+    }
+}
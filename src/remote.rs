@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::i18n::tr;
+use crate::merge::task_data;
+use crate::TaskManager;
+
+// Uploads that find the remote copy changed again after merging it in give up after this many
+const PUSH_ATTEMPTS: usize = 3;
+
+// Where the storage keeps the tasks besides the data file, and how to log in there
+#[derive(Debug, PartialEq)]
+pub enum Remote {
+    // Basic authentication as the user, the password is read from TASKS_WEBDAV_PASSWORD
+    WebDav { url: String, user: Option<String> },
+    // Requests signed with AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, for S3 or minio
+    S3 { url: String, region: String },
+}
+
+fn quote(value: String) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Remote {
+    // The remote of the storage in config, none for the local ones
+    pub fn from_config(config: &Config) -> Option<Remote> {
+        match config.storage.as_str() {
+            "webdav" => Some(Remote::WebDav {
+                url: config.webdav_url.clone()?,
+                user: config.webdav_user.clone(),
+            }),
+            "s3" => Some(Remote::S3 {
+                url: config.s3_url.clone()?,
+                region: config.s3_region.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        match self {
+            Remote::WebDav { url, .. } | Remote::S3 { url, .. } => url,
+        }
+    }
+
+    // Lines of curl's config with the credentials, which go in on stdin rather than as
+    // arguments, where other users could see them. env looks up the environment variables
+    fn curl_config(&self, env: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let var = |name: &str| env(name).unwrap_or_default();
+        match self {
+            Remote::WebDav {
+                user: Some(user), ..
+            } => vec![format!(
+                "user = {}",
+                quote(format!("{}:{}", user, var("TASKS_WEBDAV_PASSWORD")))
+            )],
+            Remote::WebDav { user: None, .. } => Vec::new(),
+            Remote::S3 { region, .. } => {
+                let mut lines = vec![
+                    format!(
+                        "user = {}",
+                        quote(format!(
+                            "{}:{}",
+                            var("AWS_ACCESS_KEY_ID"),
+                            var("AWS_SECRET_ACCESS_KEY")
+                        ))
+                    ),
+                    format!("aws-sigv4 = {}", quote(format!("aws:amz:{}:s3", region))),
+                ];
+                // Temporary credentials come with a token that has to be sent along
+                if let Some(token) = env("AWS_SESSION_TOKEN") {
+                    lines.push(format!(
+                        "header = {}",
+                        quote(format!("x-amz-security-token: {}", token))
+                    ));
+                }
+                lines
+            }
+        }
+    }
+}
+
+// What was last seen of the remote copy, kept next to the data file
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct RemoteState {
+    // Another url is another copy, whose ETags mean nothing here
+    url: String,
+    etag: Option<String>,
+    // The remote copy was there when last seen, which with no ETag means the server sends none
+    #[serde(default)]
+    exists: bool,
+    // Hash of the data last uploaded, so runs that changed nothing don't upload it again
+    pushed: Option<u64>,
+}
+
+fn state_path(data_file: &Path) -> PathBuf {
+    data_file.with_file_name("remote.json")
+}
+
+fn download_path(data_file: &Path) -> PathBuf {
+    data_file.with_extension("download.json")
+}
+
+fn load_state(data_file: &Path, url: &str) -> RemoteState {
+    read_to_string(state_path(data_file))
+        .ok()
+        .and_then(|text| serde_json::from_str::<RemoteState>(&text).ok())
+        .filter(|state| state.url == url)
+        .unwrap_or_else(|| RemoteState {
+            url: url.to_string(),
+            ..Default::default()
+        })
+}
+
+fn save_state(data_file: &Path, state: &RemoteState) -> Result<(), Box<dyn Error>> {
+    write(state_path(data_file), serde_json::to_string(state)?)?;
+    Ok(())
+}
+
+fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: u16,
+    etag: Option<String>,
+}
+
+// curl's headers followed by the status code from --write-out. A PUT can be answered with
+// 100 Continue first, so the ETag is taken from the last headers
+fn parse_reply(output: &str) -> Result<Reply, Box<dyn Error>> {
+    let (headers, status) = output.rsplit_once("\r\n\r\n").unwrap_or(("", output));
+    let headers = headers.rsplit("\r\n\r\n").next().unwrap_or_default();
+    let etag = headers.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("etag")
+            .then(|| value.trim().to_string())
+    });
+    Ok(Reply {
+        status: status.trim().parse()?,
+        etag,
+    })
+}
+
+// Runs curl, which takes care of https and signing requests
+fn curl(remote: &Remote, args: &[&str], body_file: &Path) -> Result<Reply, Box<dyn Error>> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--netrc-optional",
+            "--config",
+            "-",
+        ])
+        .args([
+            "--dump-header",
+            "-",
+            "--write-out",
+            "%{http_code}",
+            "--output",
+        ])
+        .arg(body_file)
+        .args(args)
+        .arg(remote.url())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            tr!(
+                "curl_missing",
+                "Unable to run curl, is it installed? {0}",
+                err
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in remote.curl_config(|name| std::env::var(name).ok()) {
+            writeln!(stdin, "{}", line)?;
+        }
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()
+            .into());
+    }
+    parse_reply(&String::from_utf8_lossy(&output.stdout))
+}
+
+impl TaskManager {
+    // Merges in the remote copy when it changed since it was last seen and saves the result
+    // straight away, so the data file always holds everything up to the ETag kept for it
+    pub fn pull_remote(
+        &mut self,
+        remote: &Remote,
+        data_file: &PathBuf,
+    ) -> Result<(), Box<dyn Error>> {
+        // A new device has nothing locally yet, not even the directory
+        if let Some(directory) = data_file.parent() {
+            create_dir_all(directory)?;
+        }
+        let mut state = load_state(data_file, remote.url());
+        let condition = state
+            .etag
+            .as_ref()
+            .map(|etag| format!("If-None-Match: {}", etag));
+        let args: Vec<&str> = match &condition {
+            Some(condition) => vec!["--header", condition],
+            None => Vec::new(),
+        };
+        let download = download_path(data_file);
+        let reply = curl(remote, &args, &download);
+        let remote = match reply {
+            Ok(Reply { status: 200, .. }) => read_to_string(&download).map(Some),
+            _ => Ok(None),
+        };
+        let _ = remove_file(&download);
+        match reply? {
+            Reply { status: 304, .. } => {}
+            // Nothing uploaded yet, or removed there, the next upload puts it back
+            Reply { status: 404, .. } => {
+                state.etag = None;
+                state.exists = false;
+            }
+            Reply { status: 200, etag } => {
+                if let Some(remote) = remote? {
+                    self.sync_with(task_data(serde_json::from_str(&remote)?)?);
+                    self.end_housekeeping();
+                    self.save_to_file(data_file)?;
+                }
+                state.etag = etag;
+                state.exists = true;
+            }
+            Reply { status, .. } => {
+                return Err(tr!("server_answered", "the server answered {0}", status).into())
+            }
+        }
+        save_state(data_file, &state)
+    }
+
+    // Uploads the data file when it changed, only over the copy last seen, so changes made
+    // elsewhere in the meantime are merged in and uploaded together instead of overwritten.
+    // Servers without ETags can't check that, the remote copy is merged in right before
+    // uploading instead
+    pub fn push_remote(
+        &mut self,
+        remote: &Remote,
+        data_file: &PathBuf,
+    ) -> Result<(), Box<dyn Error>> {
+        let download = download_path(data_file);
+        for _ in 0..PUSH_ATTEMPTS {
+            let mut state = load_state(data_file, remote.url());
+            let mut hash = content_hash(&read_to_string(data_file)?);
+            if state.exists && state.pushed == Some(hash) {
+                return Ok(());
+            }
+            let condition = match (&state.etag, state.exists) {
+                (Some(etag), _) => Some(format!("If-Match: {}", etag)),
+                (None, false) => Some("If-None-Match: *".to_string()),
+                (None, true) => None,
+            };
+            if condition.is_none() {
+                self.pull_remote(remote, data_file)?;
+                state = load_state(data_file, remote.url());
+                hash = content_hash(&read_to_string(data_file)?);
+            }
+            let data = data_file.to_string_lossy();
+            let mut args = vec!["--upload-file", &data];
+            if let Some(condition) = &condition {
+                args.extend(["--header", condition.as_str()]);
+            }
+            let reply = curl(remote, &args, &download);
+            let _ = remove_file(&download);
+            match reply? {
+                Reply {
+                    status: 200 | 201 | 204,
+                    etag,
+                } => {
+                    // Not every server sends the new ETag with the upload
+                    state.etag = match etag {
+                        Some(etag) => Some(etag),
+                        None => {
+                            let reply = curl(remote, &["--head"], &download);
+                            let _ = remove_file(&download);
+                            reply?.etag
+                        }
+                    };
+                    state.exists = true;
+                    state.pushed = Some(hash);
+                    return save_state(data_file, &state);
+                }
+                Reply { status: 412, .. } => self.pull_remote(remote, data_file)?,
+                Reply { status, .. } => {
+                    return Err(tr!("server_answered", "the server answered {0}", status).into())
+                }
+            }
+        }
+        Err(tr!(
+            "remote_kept_changing",
+            "the remote tasks kept changing while uploading, try again"
+        )
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_reply, Remote, Reply};
+
+    #[test]
+    fn reads_status_and_etag_from_curl() {
+        let output = "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 201 Created\r\nETag: \"5f2a\"\r\nContent-Length: 0\r\n\r\n201";
+        assert_eq!(
+            parse_reply(output).unwrap(),
+            Reply {
+                status: 201,
+                etag: Some("\"5f2a\"".to_string()),
+            }
+        );
+        let output = "HTTP/1.1 304 Not Modified\r\nDate: today\r\n\r\n304";
+        assert_eq!(parse_reply(output).unwrap().etag, None);
+        assert!(parse_reply("").is_err());
+
+        let remote = Remote::WebDav {
+            url: "https://cloud.example.com/tasks.json".to_string(),
+            user: Some("me \"at\" home".to_string()),
+        };
+        let env = |name: &str| (name == "TASKS_WEBDAV_PASSWORD").then(|| "pass".to_string());
+        assert_eq!(
+            remote.curl_config(env),
+            vec![r#"user = "me \"at\" home:pass""#]
+        );
+        let remote = Remote::S3 {
+            url: "http://localhost:9000/bucket/tasks.json".to_string(),
+            region: "eu-west-1".to_string(),
+        };
+        let config = remote.curl_config(|_| None);
+        assert_eq!(config[1], r#"aws-sigv4 = "aws:amz:eu-west-1:s3""#);
+        assert_eq!(config.len(), 2);
+        let config = remote.curl_config(|_| Some("token".to_string()));
+        assert_eq!(config[2], r#"header = "x-amz-security-token: token""#);
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde_json::{Deserializer, Value};
+use std::error::Error;
+use std::fs::{read_to_string, rename};
+use std::path::Path;
+
+use crate::i18n::tr;
+use crate::migrate::migrate;
+use crate::TaskManager;
+
+// Every entry of a JSON array that still deserializes, skipping the ones that don't
+fn salvage_array<T: DeserializeOwned>(data: &Value, key: &str) -> Vec<T> {
+    match data.get(key).and_then(Value::as_array) {
+        Some(entries) => entries
+            .iter()
+            .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn salvage_value<T: DeserializeOwned + Default>(data: &Value, key: &str) -> T {
+    data.get(key)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_default()
+}
+
+// For files cut short, reads task objects one by one from the tasks array until the damage
+fn salvage_truncated(contents: &str) -> Value {
+    let mut tasks = Vec::new();
+    let start = contents
+        .find("\"tasks\"")
+        .and_then(|key| contents[key..].find('[').map(|bracket| key + bracket + 1));
+    if let Some(start) = start {
+        let mut rest = &contents[start..];
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            let mut stream = Deserializer::from_str(rest).into_iter::<Value>();
+            match stream.next() {
+                Some(Ok(task)) if task.is_object() => tasks.push(task),
+                _ => break,
+            }
+            rest = &rest[stream.byte_offset()..];
+        }
+    }
+    serde_json::json!({ "tasks": tasks })
+}
+
+impl TaskManager {
+    // Everything that can still be read from damaged data
+    pub fn salvage(contents: &str) -> TaskManager {
+        let mut data =
+            serde_json::from_str(contents).unwrap_or_else(|_| salvage_truncated(contents));
+        // A newer file is never repaired, so an error here only means there is no version
+        let _ = migrate(&mut data);
+        let mut task_manager = TaskManager::new();
+        task_manager.tasks = salvage_array(&data, "tasks");
+        task_manager.trash = salvage_array(&data, "trash");
+        task_manager.archive = salvage_array(&data, "archive");
+        task_manager.out_of_office = salvage_value(&data, "out_of_office");
+        task_manager.goals = salvage_value(&data, "goals");
+        task_manager.last_replace = salvage_value(&data, "last_replace");
+        let archived = task_manager.archive.iter().map(|archived| &archived.task);
+        let trashed = task_manager.trash.iter().map(|trashed| &trashed.task);
+        task_manager.next_uid = task_manager
+            .tasks
+            .iter()
+            .chain(trashed)
+            .chain(archived)
+            .map(|task| task.uid + 1)
+            .max()
+            .unwrap_or(1);
+        task_manager
+    }
+}
+
+// Moves a data file that can't be loaded aside and saves what could be salvaged in its place
+pub fn repair(path: &Path) -> Result<(), Box<dyn Error>> {
+    if TaskManager::load_from_file(&path.to_path_buf()).is_ok() {
+        println!(
+            "{}",
+            tr!(
+                "repair_not_needed",
+                "{0} loads fine, nothing to repair",
+                path.display()
+            )
+        );
+        return Ok(());
+    }
+    let contents = read_to_string(path)?;
+    let mut task_manager = TaskManager::salvage(&contents);
+    let mut quarantine = path.as_os_str().to_owned();
+    quarantine.push(format!(".broken-{}", Utc::now().format("%Y%m%d%H%M%S")));
+    rename(path, &quarantine)?;
+    task_manager.save_to_file(&path.to_path_buf())?;
+    println!(
+        "{}",
+        tr!(
+            "repair_salvaged",
+            "Salvaged {0} tasks, {1} in trash and {2} archived. The broken file was moved to {3}",
+            task_manager.tasks.len(),
+            task_manager.trash.len(),
+            task_manager.archive.len(),
+            Path::new(&quarantine).display()
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TaskManager;
+
+    #[test]
+    fn salvages_tasks_from_truncated_file() {
+        let contents = r#"{"version": 1, "tasks": [
+            {"title": "first", "description": "", "status": "Inactive", "urgency": 3.0},
+            {"title": "bad", "urgency": "high"},
+            {"title": "cut off", "descr"#;
+        let task_manager = TaskManager::salvage(contents);
+        assert_eq!(task_manager.tasks.len(), 1);
+        assert_eq!(task_manager.tasks[0].title, "first");
+    }
+}
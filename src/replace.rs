@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::i18n;
+use crate::theme::Element;
+use crate::TaskManager;
+
+// The last replace that was applied, kept so it can be undone
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replacement {
+    pub find: String,
+    pub replace: String,
+    #[serde(deserialize_with = "crate::dates::serde_utc::deserialize")]
+    pub time: DateTime<Utc>,
+}
+
+// A title or description before and after a replace
+#[derive(Debug, PartialEq)]
+pub struct TextChange {
+    pub id: usize,
+    pub field: &'static str,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+impl TaskManager {
+    // What replacing find in the titles and descriptions of ids would change
+    pub fn replacements(&self, ids: &[usize], find: &str, replace: &str) -> Vec<TextChange> {
+        let mut changes = Vec::new();
+        for &id in ids {
+            let task = &self.tasks[id];
+            for (field, value) in [("title", &task.title), ("description", &task.description)] {
+                if value.contains(find) {
+                    changes.push(TextChange {
+                        id,
+                        field,
+                        old_value: value.clone(),
+                        new_value: value.replace(find, replace),
+                    });
+                }
+            }
+        }
+        changes
+    }
+
+    pub fn show_replacements(&self, changes: &[TextChange], config: &Config) {
+        if changes.is_empty() {
+            println!("{}", i18n::text("nothing_replace", "Nothing to replace"));
+        }
+        let theme = config.theme();
+        for change in changes {
+            println!(
+                " -{}- {}: {} -> {}",
+                change.id,
+                change.field,
+                theme.paint(Element::Removed, &change.old_value),
+                theme.paint(Element::Added, &change.new_value)
+            );
+        }
+    }
+
+    pub fn apply_replacements(&mut self, changes: Vec<TextChange>, find: String, replace: String) {
+        self.last_replace = Some(Replacement {
+            find,
+            replace,
+            time: Utc::now(),
+        });
+        for change in changes {
+            match change.field {
+                "title" => self.set_task_name(change.id, change.new_value),
+                _ => self.set_task_description(change.id, change.new_value),
+            }
+        }
+    }
+
+    // Puts back text changed by the last replace, unless it was edited again since
+    pub fn undo_replace(&mut self) -> usize {
+        let replacement = match self.last_replace.take() {
+            Some(replacement) => replacement,
+            None => return 0,
+        };
+        let mut undone = Vec::new();
+        for (id, task) in self.tasks.iter().enumerate() {
+            for (field, value) in [("title", &task.title), ("description", &task.description)] {
+                let replaced = task.history.iter().rev().find(|change| {
+                    change.field == field
+                        && change.time >= replacement.time
+                        && &change.new_value == value
+                        && change
+                            .old_value
+                            .replace(&replacement.find, &replacement.replace)
+                            == change.new_value
+                });
+                if let Some(change) = replaced {
+                    undone.push((id, field, change.old_value.clone()));
+                }
+            }
+        }
+        let count = undone.len();
+        for (id, field, old_value) in undone {
+            match field {
+                "title" => self.set_task_name(id, old_value),
+                _ => self.set_task_description(id, old_value),
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TaskManager;
+
+    #[test]
+    fn replace_can_be_undone() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Call Acme".to_string());
+        debug_manager.add_task("Email Acme Corp".to_string());
+        let changes = debug_manager.replacements(&[0], "Acme", "Acme Corp");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new_value, "Call Acme Corp");
+        debug_manager.apply_replacements(changes, "Acme".to_string(), "Acme Corp".to_string());
+        assert_eq!(debug_manager.tasks[0].title, "Call Acme Corp");
+        assert_eq!(debug_manager.undo_replace(), 1);
+        assert_eq!(debug_manager.tasks[0].title, "Call Acme");
+        assert_eq!(debug_manager.tasks[1].title, "Email Acme Corp");
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::dates::{from_local, parse_day, to_local};
+use crate::error::TaskError;
+use crate::filter::{parse_fields, Sort};
+use crate::format::RowFormat;
+use crate::i18n::{self, tr};
+use crate::pager::print_paged;
+use crate::theme::Element;
+use crate::virtual_tags::tag_term;
+use crate::{parse_filter, ListOptions, Status, Task, TaskManager};
+
+// Columns of list --raw unless --fields is given
+const RAW_FIELDS: &str = "id,urgency,status,due,title";
+
+// A saved list from config, e.g. "reports": {"work": {"filter": "status:open", "sort": "due"}}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Report {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub fields: Option<String>,
+    pub format: Option<String>,
+    pub limit: Option<usize>,
+    pub group_by: Option<String>,
+}
+
+// Sections for list --group-by, a task can be in more than one group by tag
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupBy {
+    Status,
+    DueWeek,
+    Project,
+    Tag,
+    Custom(String),
+}
+
+impl GroupBy {
+    pub fn parse(name: &str, config: &Config) -> Result<Self, TaskError> {
+        match name {
+            "status" => Ok(GroupBy::Status),
+            "due-week" => Ok(GroupBy::DueWeek),
+            "project" => Ok(GroupBy::Project),
+            "tag" => Ok(GroupBy::Tag),
+            _ if config.custom_fields.contains_key(name) => Ok(GroupBy::Custom(name.to_string())),
+            _ => Err(TaskError::UnknownGroup(name.to_string())),
+        }
+    }
+
+    // (sort key, heading) of each group the task is in, tasks without a value sort last
+    fn groups(&self, task: &Task, config: &Config) -> Vec<(String, String)> {
+        match self {
+            GroupBy::Status => {
+                let order = match task.status {
+                    Status::Active => 0,
+                    Status::Inactive => 1,
+                    Status::Done => 2,
+                };
+                vec![(order.to_string(), task.status.as_str().to_string())]
+            }
+            GroupBy::DueWeek => match task.due_time {
+                Some(due_time) => {
+                    let due = to_local(due_time).date();
+                    let monday = due - Duration::days(due.weekday().num_days_from_monday() as i64);
+                    vec![(
+                        monday.format("%Y-%m-%d").to_string(),
+                        tr!("report_week_of", "Week of {0}", config.format_date(monday)),
+                    )]
+                }
+                None => vec![(
+                    "~".to_string(),
+                    i18n::text("group_no_due_date", "No due date"),
+                )],
+            },
+            GroupBy::Project => match &task.project {
+                Some(project) => vec![(project.to_lowercase(), project.clone())],
+                None => vec![(
+                    "~".to_string(),
+                    i18n::text("group_no_project", "No project"),
+                )],
+            },
+            // The user's own tags come before the virtual ones
+            GroupBy::Tag => {
+                let user_tags = task
+                    .tags
+                    .iter()
+                    .map(|tag| (format!("0{}", tag), format!("+{}", tag)));
+                let virtual_tags = task
+                    .virtual_tags(Utc::now(), config.stale_after_days)
+                    .into_iter()
+                    .map(|tag| (format!("1{}", tag.name()), tag.name().to_string()));
+                let tags: Vec<(String, String)> = user_tags.chain(virtual_tags).collect();
+                if tags.is_empty() {
+                    vec![("~".to_string(), i18n::text("group_no_tags", "No tags"))]
+                } else {
+                    tags
+                }
+            }
+            GroupBy::Custom(name) => match task.custom.get(name) {
+                Some(value) => vec![(value.to_lowercase(), value.clone())],
+                None => vec![("~".to_string(), tr!("group_no_custom", "No {0}", name))],
+            },
+        }
+    }
+}
+
+// Reports from config by name, list is always there and is what plain list shows
+pub fn find_report(name: &str, config: &Config) -> Option<Report> {
+    match config.reports.get(name) {
+        Some(report) => Some(report.clone()),
+        None if name == "list" => Some(Report::default()),
+        None => None,
+    }
+}
+
+impl ListOptions {
+    // Filters from both have to match, anything else given on the command line wins
+    fn with_report(mut self, report: &Report) -> Self {
+        let filter: Vec<String> = report
+            .filter
+            .iter()
+            .chain(self.filter.iter())
+            .cloned()
+            .collect();
+        self.filter = Some(filter.join(" "));
+        self.sort = self.sort.or_else(|| report.sort.clone());
+        if self.fields.is_none() && self.format.is_none() {
+            self.fields = report.fields.clone();
+            self.format = report.format.clone();
+        }
+        self.limit = self.limit.or(report.limit);
+        self.group_by = self.group_by.or_else(|| report.group_by.clone());
+        self
+    }
+}
+
+fn print_list(output: &str, paged: bool) {
+    if paged {
+        print_paged(output);
+    } else {
+        print!("{}", output);
+    }
+}
+
+impl TaskManager {
+    // Long lists go through the pager when paged, watch redraws in place instead
+    pub fn run_report(&self, report: &Report, options: ListOptions, config: &Config, paged: bool) {
+        let ListOptions {
+            filter,
+            tag,
+            mine,
+            stale,
+            assignee,
+            project,
+            fields,
+            format,
+            sort,
+            width,
+            due,
+            no_due,
+            tags,
+            no_tags,
+            limit,
+            offset,
+            at,
+            group_by,
+            raw,
+            bars,
+        } = options.with_report(report);
+        let group_by = match group_by.map(|name| GroupBy::parse(&name, config)) {
+            Some(Ok(group_by)) => Some(group_by),
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                return;
+            }
+            None => None,
+        };
+        let tag_terms = tag.iter().map(|tag| tag_term(tag));
+        let assignee = assignee
+            .or_else(|| mine.then(|| "me".to_string()))
+            .map(|assignee| format!("assignee:{}", assignee));
+        let project = project.map(|project| format!("project:{}", project));
+        let stale = stale.map(|days| match days {
+            Some(days) => format!("stale:{}", days),
+            None => "+STALE".to_string(),
+        });
+        let filter = filter
+            .into_iter()
+            .chain(tag_terms)
+            .chain(assignee)
+            .chain(project)
+            .chain(stale)
+            .collect::<Vec<String>>();
+        let filter = match parse_filter(Some(filter.join(" ")), config) {
+            Some(filter) => filter,
+            None => return,
+        };
+        let snapshot;
+        let task_manager = match at {
+            Some(at) => match parse_day(&at, &config.input_date_format)
+                .or_else(|| NaiveDate::parse_from_str(&at, "%Y-%m-%d").ok())
+            {
+                // The end of the day, so changes made during it are included
+                Some(day) => {
+                    snapshot = self.snapshot_at(from_local(day.and_hms_opt(23, 59, 59).unwrap()));
+                    &snapshot
+                }
+                None => {
+                    eprintln!("{}", TaskError::InvalidDay(at));
+                    return;
+                }
+            },
+            None => self,
+        };
+        let mut ids = task_manager.filtered_ids(&filter);
+        match Sort::parse(sort.as_deref().unwrap_or("urgency")) {
+            Ok(sort) => task_manager.sort_ids(&mut ids, sort),
+            Err(err) => eprintln!("{}", err),
+        }
+        let ids: Vec<usize> = ids
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        // Groups are left out too, every line is a task
+        if raw {
+            let fields = fields.unwrap_or_else(|| RAW_FIELDS.to_string());
+            match parse_fields(&fields, config) {
+                Ok(fields) => print!("{}", task_manager.list_raw(&ids, &fields)),
+                Err(err) => eprintln!("{}", err),
+            }
+            return;
+        }
+        let groups = match &group_by {
+            Some(group_by) => task_manager.group_ids(&ids, group_by, config),
+            None => vec![(String::new(), ids)],
+        };
+        let ids: Vec<usize> = groups.iter().flat_map(|(_, ids)| ids.clone()).collect();
+        // Rendered as one list so the columns line up across groups
+        let (listed, has_header) = match (format, fields) {
+            (Some(format), _) => {
+                // Presets from config are looked up by name first
+                let template = config.list_formats.get(&format).unwrap_or(&format);
+                match RowFormat::parse(template, config) {
+                    Ok(format) => (task_manager.list_formatted(&ids, &format), false),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        return;
+                    }
+                }
+            }
+            (None, Some(fields)) => match parse_fields(&fields, config) {
+                Ok(fields) => (task_manager.list_fields(&ids, &fields, config), true),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return;
+                }
+            },
+            (None, None) => {
+                let forced = |on: bool, off: bool| {
+                    if on {
+                        Some(true)
+                    } else if off {
+                        Some(false)
+                    } else {
+                        None
+                    }
+                };
+                let show_due = forced(due, no_due);
+                let show_tags = forced(tags, no_tags);
+                if config.plain {
+                    (task_manager.list_plain(&ids, config), false)
+                } else {
+                    let bars = bars || config.urgency_bars;
+                    let listed =
+                        task_manager.list_tasks(&ids, width, show_due, show_tags, bars, config);
+                    (listed, !task_manager.tasks.is_empty())
+                }
+            }
+        };
+        if group_by.is_none() {
+            print_list(&listed, paged && !config.plain);
+            return;
+        }
+        let theme = config.theme();
+        let mut lines = listed.lines();
+        let mut output = String::new();
+        if has_header {
+            output += lines.next().unwrap_or_default();
+            output.push('\n');
+        }
+        for (heading, ids) in groups {
+            let heading = format!("{} ({})", heading, ids.len());
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            output += &theme.paint(Element::Header, &heading);
+            output.push('\n');
+            for line in lines.by_ref().take(ids.len()) {
+                output += line;
+                output.push('\n');
+            }
+        }
+        // Whatever follows the tasks, like the urgency legend
+        for line in lines {
+            output += line;
+            output.push('\n');
+        }
+        print_list(&output, paged && !config.plain)
+    }
+
+    // Groups in order of their sort keys, keeping the listed order inside each
+    pub fn group_ids(
+        &self,
+        ids: &[usize],
+        group_by: &GroupBy,
+        config: &Config,
+    ) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, String, Vec<usize>)> = Vec::new();
+        for &id in ids {
+            for (key, heading) in group_by.groups(&self.tasks[id], config) {
+                match groups.iter_mut().find(|(other, _, _)| *other == key) {
+                    Some((_, _, group)) => group.push(id),
+                    None => groups.push((key, heading, vec![id])),
+                }
+            }
+        }
+        groups.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+        groups
+            .into_iter()
+            .map(|(_, heading, ids)| (heading, ids))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_report, GroupBy, Report};
+    use crate::config::Config;
+    use crate::{ListOptions, Status, TaskManager};
+
+    #[test]
+    fn command_line_options_narrow_reports() {
+        let mut config = Config::default();
+        let work = Report {
+            filter: Some("status:open".to_string()),
+            sort: Some("due".to_string()),
+            fields: Some("id,title".to_string()),
+            ..Report::default()
+        };
+        config.reports.insert("work".to_string(), work);
+        assert_eq!(find_report("list", &config), Some(Report::default()));
+        assert_eq!(find_report("home", &config), None);
+
+        let options = ListOptions {
+            filter: Some("urgency>5".to_string()),
+            sort: Some("age".to_string()),
+            ..ListOptions::default()
+        };
+        let options = options.with_report(&find_report("work", &config).unwrap());
+        assert_eq!(options.filter.as_deref(), Some("status:open urgency>5"));
+        assert_eq!(options.sort.as_deref(), Some("age"));
+        assert_eq!(options.fields.as_deref(), Some("id,title"));
+    }
+
+    #[test]
+    fn groups_keep_the_listed_order() {
+        let config = Config::default();
+        let mut debug_manager = TaskManager::new();
+        for title in ["a", "b", "c"] {
+            debug_manager.add_task(title.to_string());
+        }
+        debug_manager.set_task_status(1, Status::Active);
+        let groups = debug_manager.group_ids(&[2, 1, 0], &GroupBy::Status, &config);
+        assert_eq!(
+            groups,
+            vec![
+                ("Active".to_string(), vec![1]),
+                ("Inactive".to_string(), vec![2, 0])
+            ]
+        );
+        assert!(GroupBy::parse("assignee", &config).is_err());
+
+        debug_manager.set_project(0, Some("home".to_string()));
+        debug_manager.set_project(2, Some("Garden".to_string()));
+        debug_manager
+            .change_tags(2, &["errands".to_string()])
+            .unwrap();
+        let by_project = GroupBy::parse("project", &config).unwrap();
+        assert_eq!(
+            debug_manager.group_ids(&[0, 1, 2], &by_project, &config),
+            vec![
+                ("Garden".to_string(), vec![2]),
+                ("home".to_string(), vec![0]),
+                ("No project".to_string(), vec![1])
+            ]
+        );
+        let by_tag = debug_manager.group_ids(&[0, 1, 2], &GroupBy::Tag, &config);
+        assert_eq!(by_tag[0], ("+errands".to_string(), vec![2]));
+    }
+}
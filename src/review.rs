@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+
+use crate::config::Config;
+use crate::dates::format_days;
+use crate::i18n::{self, tr};
+use crate::pick::prompt;
+use crate::scheduled::parse_scheduled;
+use crate::{Task, TaskManager};
+
+// Snoozed tasks come back on this day unless another is given
+const DEFAULT_SNOOZE: &str = "monday";
+
+impl TaskManager {
+    // Oldest first, so the ones forgotten longest come up before a review is cut short
+    pub fn stale_uids(&self, now: DateTime<Utc>, days: i64) -> Vec<u64> {
+        let mut stale: Vec<&Task> = self
+            .tasks
+            .iter()
+            .filter(|task| task.is_stale(now, days))
+            .collect();
+        stale.sort_by_key(|task| std::cmp::Reverse(task.untouched_for(now)));
+        stale.iter().map(|task| task.uid).collect()
+    }
+
+    // Asks what to do with each stale task in turn until there are none left or the review is
+    // quit, the answers come from ask so the review can be scripted
+    pub fn review_with(
+        &mut self,
+        days: i64,
+        mut ask: impl FnMut(&str) -> Option<String>,
+        config: &Config,
+    ) {
+        let now = Utc::now();
+        let uids = self.stale_uids(now, days);
+        if uids.is_empty() {
+            println!(
+                "{}",
+                tr!(
+                    "review_nothing_stale",
+                    "Nothing to review, every open task changed in the last {0} days",
+                    days
+                )
+            );
+            return;
+        }
+        let mut reviewed = 0;
+        for (position, uid) in uids.iter().enumerate() {
+            // Deleting a task moves the ones after it, so each is found again by uid
+            let id = match self.tasks.iter().position(|task| task.uid == *uid) {
+                Some(id) => id,
+                None => continue,
+            };
+            println!(
+                "{}",
+                tr!(
+                    "review_untouched",
+                    "({0}/{1}) untouched for {2}",
+                    position + 1,
+                    uids.len(),
+                    format_days(self.tasks[id].untouched_for(now))
+                )
+            );
+            self.show_task_short(id);
+            let answer = ask(&i18n::text(
+                "review_choice",
+                "[k]eep, [s]nooze, [u]rgency, [d]elete or [q]uit?",
+            ));
+            match answer.as_deref().map(str::to_lowercase).as_deref() {
+                Some("k" | "keep") => self.tasks[id].modified_at = Some(now),
+                Some("s" | "snooze") => {
+                    let day = ask(&tr!("snooze_until", "Snooze until? [{0}]", DEFAULT_SNOOZE))
+                        .filter(|day| !day.is_empty())
+                        .unwrap_or_else(|| DEFAULT_SNOOZE.to_string());
+                    match parse_scheduled(&day, &config.input_date_format) {
+                        Ok(scheduled) => self.set_scheduled(id, Some(scheduled)),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+                Some("u" | "urgency") => {
+                    match ask(&i18n::text("review_new_urgency", "New urgency?"))
+                        .and_then(|urgency| urgency.parse().ok())
+                    {
+                        Some(urgency) => self.set_urgency(id, urgency),
+                        None => eprintln!(
+                            "{}",
+                            i18n::text(
+                                "review_urgency_not_number",
+                                "Not a number, the urgency is unchanged"
+                            )
+                        ),
+                    }
+                }
+                Some("d" | "delete") => self.trash_task(id),
+                Some("q" | "quit") | None => break,
+                Some(_) => println!("{}", i18n::text("review_skipped", "Skipped")),
+            }
+            reviewed += 1;
+        }
+        println!(
+            "{}",
+            tr!(
+                "reviewed_stale_tasks",
+                "Reviewed {0} of {1} stale tasks",
+                reviewed,
+                uids.len()
+            )
+        );
+    }
+
+    pub fn review(&mut self, days: i64, config: &Config) {
+        self.review_with(days, prompt, config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::TaskManager;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn review_goes_through_stale_tasks_oldest_first() {
+        let mut task_manager = TaskManager::new();
+        for title in ["Fresh", "Old", "Older", "Oldest"] {
+            task_manager.add_task(title.to_string());
+        }
+        let now = Utc::now();
+        for (id, days) in [(1, 20), (2, 30), (3, 40)] {
+            task_manager.tasks[id].modified_at = Some(now - Duration::days(days));
+        }
+        assert_eq!(task_manager.stale_uids(now, 14), vec![4, 3, 2]);
+
+        let mut answers = ["d", "u", "9", "k"].iter().map(|answer| answer.to_string());
+        task_manager.review_with(14, |_| answers.next(), &Config::default());
+        let titles: Vec<&str> = task_manager
+            .tasks
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Fresh", "Old", "Older"]);
+        assert_eq!(task_manager.tasks[2].urgency, 9.0);
+        assert!(task_manager.stale_uids(Utc::now(), 14).is_empty());
+    }
+}
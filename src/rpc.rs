@@ -0,0 +1,214 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::{stdin, stdout, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, ConfigWatch};
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::server::task_json;
+use crate::{Status, TaskManager};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+fn rpc_error(code: i64, message: String, key: Option<&str>) -> Value {
+    json!({ "code": code, "message": message, "data": key })
+}
+
+impl TaskManager {
+    fn uid_param(&self, params: &Value) -> Result<usize, Value> {
+        let uid = params["uid"].as_u64().unwrap_or_default();
+        self.tasks
+            .iter()
+            .position(|task| task.uid == uid)
+            .ok_or_else(|| {
+                let err = TaskError::InvalidId(uid as usize);
+                rpc_error(INVALID_PARAMS, err.to_string(), Some(err.key()))
+            })
+    }
+
+    // Runs one method, also saying whether the tasks changed and need saving
+    pub fn call_method(&mut self, method: &str, params: &Value) -> (Result<Value, Value>, bool) {
+        let invalid = |err: TaskError| rpc_error(INVALID_PARAMS, err.to_string(), Some(err.key()));
+        match method {
+            "list" => {
+                let tasks: Vec<Value> = self
+                    .tasks
+                    .iter()
+                    .enumerate()
+                    .map(|(id, task)| task_json(id, task))
+                    .collect();
+                (Ok(json!(tasks)), false)
+            }
+            "add" => {
+                let title = match params["title"].as_str() {
+                    Some(title) => title.to_string(),
+                    None => {
+                        let err = TaskError::InvalidRequest("title is required".to_string());
+                        return (Err(invalid(err)), false);
+                    }
+                };
+                self.add_task(title);
+                let id = self.tasks.len() - 1;
+                match self.apply_fields(id, params) {
+                    Ok(()) => (Ok(task_json(id, &self.tasks[id])), true),
+                    Err(err) => {
+                        self.tasks.pop();
+                        self.next_uid -= 1;
+                        (Err(invalid(err)), false)
+                    }
+                }
+            }
+            "done" => match self.uid_param(params) {
+                Ok(id) => {
+                    self.set_task_status(id, Status::Done);
+                    self.set_urgency(id, 0.0);
+                    (Ok(task_json(id, &self.tasks[id])), true)
+                }
+                Err(err) => (Err(err), false),
+            },
+            "edit" => match self.uid_param(params) {
+                Ok(id) => match self.apply_fields(id, params) {
+                    Ok(()) => (Ok(task_json(id, &self.tasks[id])), true),
+                    Err(err) => (Err(invalid(err)), false),
+                },
+                Err(err) => (Err(err), false),
+            },
+            _ => (
+                Err(rpc_error(
+                    METHOD_NOT_FOUND,
+                    tr!("rpc_unknown_method", "Unknown method: {0}", method),
+                    None,
+                )),
+                false,
+            ),
+        }
+    }
+
+    // A JSON-RPC 2.0 request to its response, None for notifications which get no reply
+    pub fn handle_rpc(&mut self, message: &str) -> (Option<Value>, bool) {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(err) => {
+                let error = rpc_error(PARSE_ERROR, err.to_string(), None);
+                let response = json!({ "jsonrpc": "2.0", "id": null, "error": error });
+                return (Some(response), false);
+            }
+        };
+        let method = request["method"].as_str().unwrap_or_default();
+        let (result, changed) = self.call_method(method, &request["params"]);
+        let id = match request.get("id") {
+            Some(id) => id.clone(),
+            None => return (None, changed),
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        };
+        (Some(response), changed)
+    }
+}
+
+// Answers a request when the data file can't be read again before it
+fn unreadable(message: &str, path: &Path, err: Box<dyn Error>) -> Option<Value> {
+    let request: Value = serde_json::from_str(message).unwrap_or_default();
+    let id = request.get("id")?.clone();
+    let err = TaskError::UnreadableData {
+        path: path.display().to_string(),
+        reason: err.to_string(),
+    };
+    let error = rpc_error(INTERNAL_ERROR, err.to_string(), Some(err.key()));
+    Some(json!({ "jsonrpc": "2.0", "id": id, "error": error }))
+}
+
+// Reads requests from stdin until it closes, one JSON message per line or framed with a
+// Content-Length header like the language server protocol, replying in the same framing
+pub fn serve_stdio(
+    task_manager: &mut TaskManager,
+    path: &PathBuf,
+    mut config: Config,
+    mut watch: ConfigWatch,
+) -> Result<(), Box<dyn Error>> {
+    let stdin = stdin();
+    let mut input = stdin.lock();
+    let mut output = stdout();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let (message, framed) = match line.trim().strip_prefix("Content-Length:") {
+            Some(length) => {
+                let length: usize = length.trim().parse()?;
+                // Skip any other headers up to the blank line
+                loop {
+                    line.clear();
+                    if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut body = vec![0; length];
+                input.read_exact(&mut body)?;
+                (String::from_utf8(body)?, true)
+            }
+            None if line.trim().is_empty() => continue,
+            None => (line.clone(), false),
+        };
+        // An editor keeps this running for a whole session, in which the tasks are also changed
+        // from the command line
+        if let Some(message) = watch.reload(&mut config) {
+            eprintln!("{}", message);
+        }
+        let (response, changed) = match task_manager.reload(path, &config) {
+            Ok(()) => task_manager.handle_rpc(&message),
+            Err(err) => (unreadable(&message, path, err), false),
+        };
+        if changed {
+            task_manager.calculate_urgencies();
+            task_manager.derive_from_dependencies();
+            task_manager.sort_by_urgencies();
+            task_manager.save_to_file(path)?;
+        }
+        if let Some(response) = response {
+            let response = response.to_string();
+            if framed {
+                write!(
+                    output,
+                    "Content-Length: {}\r\n\r\n{}",
+                    response.len(),
+                    response
+                )?;
+            } else {
+                writeln!(output, "{}", response)?;
+            }
+            output.flush()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TaskManager;
+
+    #[test]
+    fn add_and_done_over_rpc() {
+        let mut task_manager = TaskManager::new();
+        let (response, changed) = task_manager.handle_rpc(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "add", "params": {"title": "Write report"}}"#,
+        );
+        assert!(changed);
+        let uid = response.unwrap()["result"]["uid"].as_u64().unwrap();
+        let (response, _) = task_manager.handle_rpc(&format!(
+            r#"{{"jsonrpc": "2.0", "id": 2, "method": "done", "params": {{"uid": {}}}}}"#,
+            uid
+        ));
+        assert_eq!(response.unwrap()["result"]["status"], "Done");
+        let (response, _) =
+            task_manager.handle_rpc(r#"{"jsonrpc": "2.0", "id": 3, "method": "nope"}"#);
+        assert_eq!(response.unwrap()["error"]["code"], -32601);
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Deserialize;
+use std::fs::read_to_string;
+
+use crate::config::{config_path, Config};
+use crate::error::TaskError;
+use crate::filter::Filter;
+use crate::i18n::{self, tr};
+use crate::TaskManager;
+
+// A rule as written in XDG_CONFIG/task/rules.json, e.g.
+// {"name": "escalate bugs", "when": "+OVERDUE kind:bug", "urgency": "+2", "set": ["escalated=yes"]}
+#[derive(Debug, Deserialize)]
+struct RuleEntry {
+    name: String,
+    when: String,
+    #[serde(default)]
+    urgency: Option<String>,
+    #[serde(default)]
+    set: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UrgencyChange {
+    Add(f32),
+    Multiply(f32),
+    Set(f32),
+}
+
+impl UrgencyChange {
+    // +2 or -1 change the calculated urgency by that much, *1.5 scales it and =10 or 10 replaces it
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(factor) = text.strip_prefix('*') {
+            return factor.trim().parse().ok().map(UrgencyChange::Multiply);
+        }
+        if let Some(value) = text.strip_prefix('=') {
+            return value.trim().parse().ok().map(UrgencyChange::Set);
+        }
+        let value: f32 = text.parse().ok()?;
+        if text.starts_with(['+', '-']) {
+            Some(UrgencyChange::Add(value))
+        } else {
+            Some(UrgencyChange::Set(value))
+        }
+    }
+
+    fn apply(self, urgency: f32) -> f32 {
+        match self {
+            UrgencyChange::Add(amount) => urgency + amount,
+            UrgencyChange::Multiply(factor) => urgency * factor,
+            UrgencyChange::Set(value) => value,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Rule {
+    pub name: String,
+    filter: Filter,
+    urgency: Option<UrgencyChange>,
+    // Custom field assignments like edit --set, checked against config
+    set: Vec<String>,
+}
+
+fn parse_rule(entry: RuleEntry, config: &Config) -> Result<Rule, TaskError> {
+    let invalid = |reason: String| TaskError::InvalidRule {
+        name: entry.name.clone(),
+        reason,
+    };
+    let filter = Filter::parse(&entry.when, config).map_err(|err| invalid(err.to_string()))?;
+    let urgency = match &entry.urgency {
+        Some(text) => Some(UrgencyChange::parse(text).ok_or_else(|| {
+            invalid(tr!(
+                "rule_invalid_urgency",
+                "urgency {0}, expected a change like +2, *1.5 or =10",
+                text
+            ))
+        })?),
+        None => None,
+    };
+    Ok(Rule {
+        name: entry.name,
+        filter,
+        urgency,
+        set: entry.set,
+    })
+}
+
+// Rules that can't be read are reported and left out, the rest still apply
+pub fn parse_rules(text: &str, config: &Config) -> Vec<Rule> {
+    let entries: Vec<RuleEntry> = match serde_json::from_str(text) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!(
+                "{}",
+                tr!("rules_unreadable", "Failed to read rules: {0}", err)
+            );
+            return Vec::new();
+        }
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| match parse_rule(entry, config) {
+            Ok(rule) => Some(rule),
+            Err(err) => {
+                eprintln!("{}", err);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn load_rules(config: &Config) -> Vec<Rule> {
+    match config_path("rules.json").and_then(|path| read_to_string(path).ok()) {
+        Some(text) => parse_rules(&text, config),
+        None => Vec::new(),
+    }
+}
+
+impl TaskManager {
+    // Run after urgencies are calculated and before sorting, in the order the rules are written so
+    // later rules see the urgency earlier ones left. Pinned urgencies are never changed
+    pub fn apply_rules(&mut self, rules: &[Rule], config: &Config) {
+        for rule in rules {
+            for id in self.filtered_ids(&rule.filter) {
+                if let Some(change) = rule.urgency {
+                    let task = &mut self.tasks[id];
+                    if !task.urgency_pinned {
+                        task.effective_urgency = change.apply(task.effective_urgency);
+                    }
+                }
+                if !rule.set.is_empty() {
+                    self.set_custom_fields(id, &rule.set, config);
+                }
+            }
+        }
+    }
+
+    pub fn list_rules(&self, rules: &[Rule]) {
+        if rules.is_empty() {
+            println!(
+                "{}",
+                i18n::text(
+                    "rules_empty",
+                    "No rules, add them to rules.json in the config directory"
+                )
+            );
+        }
+        for rule in rules {
+            println!(
+                "{}",
+                tr!(
+                    "rule_matches",
+                    " {0} --- matches {1} tasks",
+                    rule.name,
+                    self.filtered_ids(&rule.filter).len()
+                )
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_rules, UrgencyChange};
+    use crate::config::Config;
+    use crate::custom::CustomField;
+    use crate::TaskManager;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn rules_raise_urgency_and_set_fields() {
+        assert_eq!(UrgencyChange::parse("+2"), Some(UrgencyChange::Add(2.0)));
+        assert_eq!(UrgencyChange::parse("-1"), Some(UrgencyChange::Add(-1.0)));
+        assert_eq!(
+            UrgencyChange::parse("*1.5"),
+            Some(UrgencyChange::Multiply(1.5))
+        );
+        assert_eq!(UrgencyChange::parse("10"), Some(UrgencyChange::Set(10.0)));
+        assert_eq!(UrgencyChange::parse("lots"), None);
+
+        let mut config = Config::default();
+        for name in ["kind", "escalated"] {
+            config
+                .custom_fields
+                .insert(name.to_string(), CustomField::default());
+        }
+        let rules = parse_rules(
+            r#"[
+                {"name": "escalate bugs", "when": "+OVERDUE kind:bug", "urgency": "+2",
+                 "set": ["escalated=yes"]},
+                {"name": "broken", "when": "status:open", "urgency": "lots"}
+            ]"#,
+            &config,
+        );
+        assert_eq!(rules.len(), 1);
+
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Crash on start".to_string());
+        task_manager.add_task("Update docs".to_string());
+        for id in 0..2 {
+            task_manager.set_custom_fields(id, &["kind=bug".to_string()], &config);
+        }
+        task_manager.set_due_date(0, Utc::now() - Duration::hours(1));
+        task_manager.calculate_urgencies();
+        let before = task_manager.tasks[0].effective_urgency;
+        task_manager.apply_rules(&rules, &config);
+        assert_eq!(task_manager.tasks[0].effective_urgency, before + 2.0);
+        assert_eq!(
+            task_manager.tasks[0]
+                .custom
+                .get("escalated")
+                .map(String::as_str),
+            Some("yes")
+        );
+        assert!(!task_manager.tasks[1].custom.contains_key("escalated"));
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::dates::{from_local, parse_day};
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::history::history_datetime;
+use crate::{Task, TaskManager};
+
+// Work is scheduled for the start of the working day unless a time is given
+const DEFAULT_SCHEDULED_TIME: (u32, u32) = (9, 0);
+
+// A day as accepted for due dates, optionally followed by a time, e.g. "monday 14:00"
+pub fn parse_scheduled(input: &str, input_format: &str) -> Result<DateTime<Utc>, TaskError> {
+    let invalid = || TaskError::InvalidDate {
+        input: input.to_string(),
+        expected: format!("{} [%H:%M]", input_format),
+    };
+    let input = input.trim();
+    let (day, time) = match input.rsplit_once(' ') {
+        Some((day, time)) if time.contains(':') => (
+            day,
+            NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| invalid())?,
+        ),
+        _ => {
+            let (hour, minute) = DEFAULT_SCHEDULED_TIME;
+            (input, NaiveTime::from_hms_opt(hour, minute, 0).unwrap())
+        }
+    };
+    let day = parse_day(day, input_format).ok_or_else(invalid)?;
+    Ok(from_local(day.and_time(time)))
+}
+
+impl Task {
+    // Scheduled for later, so not something to work on yet
+    pub fn is_waiting(&self, now: DateTime<Utc>) -> bool {
+        self.scheduled.is_some_and(|scheduled| scheduled > now)
+    }
+}
+
+impl TaskManager {
+    pub fn set_scheduled(&mut self, id: usize, scheduled: Option<DateTime<Utc>>) {
+        if self.verify_id(id) {
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "scheduled",
+                history_datetime(task.scheduled),
+                history_datetime(scheduled),
+            );
+            self.set_field(id, FieldValue::Scheduled(scheduled));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_scheduled;
+    use crate::{Task, UrgencySettings};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn waiting_tasks_keep_their_base_urgency() {
+        let now = Utc::now();
+        let mut task = Task::new("Quarterly report".to_string());
+        task.urgency = 2.0;
+        task.start_time = Some(now - Duration::days(30));
+        task.scheduled = Some(now + Duration::days(1));
+        task.calculate_urgency(now, &UrgencySettings::default());
+        assert_eq!(task.effective_urgency, 2.0);
+        task.scheduled = Some(now - Duration::days(2));
+        task.calculate_urgency(now, &UrgencySettings::default());
+        assert_eq!(task.effective_urgency, 2.0);
+        task.scheduled = Some(now - Duration::days(10));
+        task.calculate_urgency(now, &UrgencySettings::default());
+        assert_eq!(task.effective_urgency, 5.0);
+
+        assert!(parse_scheduled("tomorrow 14:30", "%d/%m/%Y").is_ok());
+        assert!(parse_scheduled("tomorrow 25:00", "%d/%m/%Y").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use chrono::{DateTime, Utc};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::fs::read_to_string;
+
+use crate::config::{config_path, Config};
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::{Status, Task, TaskManager};
+
+// What one call may do before it's stopped, so a loop in a script can't hang every command
+const MAX_OPERATIONS: u64 = 100_000;
+
+// A Rhai script in XDG_CONFIG/task/rules.rhai that may define either of
+//   fn urgency(task) { if task.overdue && task.custom.kind == "bug" { task.urgency + 2 } }
+//   fn fields(task) { if task.overdue && task.custom.kind == "bug" { #{escalated: "yes"} } }
+// urgency returns the urgency to use instead of the calculated one, or nothing to keep it, and
+// fields returns custom fields to set like edit --set
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    urgency: bool,
+    fields: bool,
+}
+
+impl Script {
+    pub fn compile(text: &str) -> Result<Self, TaskError> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(text)
+            .map_err(|err| invalid(err.to_string()))?;
+        let defines = |name: &str| {
+            ast.iter_functions()
+                .any(|function| function.name == name && function.params.len() == 1)
+        };
+        let (urgency, fields) = (defines("urgency"), defines("fields"));
+        Ok(Script {
+            engine,
+            ast,
+            urgency,
+            fields,
+        })
+    }
+
+    fn call(&self, function: &str, task: &Map) -> Result<Dynamic, TaskError> {
+        self.engine
+            .call_fn(&mut Scope::new(), &self.ast, function, (task.clone(),))
+            .map_err(|err| invalid(err.to_string()))
+    }
+}
+
+fn invalid(reason: String) -> TaskError {
+    TaskError::InvalidRule {
+        name: "rules.rhai".to_string(),
+        reason,
+    }
+}
+
+// A script that can't be compiled is reported and left out like a broken rule
+pub fn load_script() -> Option<Script> {
+    let text = config_path("rules.rhai").and_then(|path| read_to_string(path).ok())?;
+    match Script::compile(&text) {
+        Ok(script) => Some(script),
+        Err(err) => {
+            eprintln!("{}", err);
+            None
+        }
+    }
+}
+
+fn optional(value: Option<f64>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Dynamic::from_float)
+}
+
+// The task as the script sees it, times are in hours or days from now
+fn task_map(task: &Task, now: DateTime<Utc>) -> Map {
+    let mut map = Map::new();
+    let hours = |seconds: i64| seconds as f64 / 3600.0;
+    map.insert("title".into(), task.title.clone().into());
+    map.insert("description".into(), task.description.clone().into());
+    map.insert("status".into(), task.status.as_str().to_lowercase().into());
+    map.insert(
+        "base_urgency".into(),
+        Dynamic::from_float(task.urgency.into()),
+    );
+    map.insert(
+        "urgency".into(),
+        Dynamic::from_float(task.effective_urgency.into()),
+    );
+    map.insert("pinned".into(), task.urgency_pinned.into());
+    map.insert(
+        "overdue".into(),
+        (task.status != Status::Done && task.due_time.is_some_and(|due| due < now)).into(),
+    );
+    map.insert(
+        "due_in_hours".into(),
+        optional(task.due_time.map(|due| hours((due - now).num_seconds()))),
+    );
+    map.insert(
+        "age_days".into(),
+        optional(
+            task.start_time
+                .map(|start| hours((now - start).num_seconds()) / 24.0),
+        ),
+    );
+    map.insert(
+        "estimate_hours".into(),
+        optional(task.estimate.map(|estimate| hours(estimate.num_seconds()))),
+    );
+    map.insert(
+        "assignee".into(),
+        task.assignee.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    map.insert(
+        "project".into(),
+        task.project.clone().map_or(Dynamic::UNIT, Dynamic::from),
+    );
+    let tags: Array = task.tags.iter().cloned().map(Dynamic::from).collect();
+    map.insert("tags".into(), tags.into());
+    let custom: Map = task
+        .custom
+        .iter()
+        .map(|(name, value)| (name.as_str().into(), value.clone().into()))
+        .collect();
+    map.insert("custom".into(), custom.into());
+    map
+}
+
+fn urgency_value(value: Dynamic) -> Result<Option<f32>, TaskError> {
+    if value.is_unit() {
+        return Ok(None);
+    }
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|int| int as f64))
+        .map(|urgency| Some(urgency as f32))
+        .map_err(|kind| {
+            invalid(tr!(
+                "script_urgency_not_number",
+                "urgency returned {0}, expected a number",
+                kind
+            ))
+        })
+}
+
+fn field_values(value: Dynamic) -> Result<Vec<String>, TaskError> {
+    if value.is_unit() {
+        return Ok(Vec::new());
+    }
+    let kind = value.type_name();
+    let map = value.try_cast::<Map>().ok_or_else(|| {
+        invalid(tr!(
+            "script_fields_not_map",
+            "fields returned {0}, expected a map like #{escalated: \"yes\"}",
+            kind
+        ))
+    })?;
+    Ok(map
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect())
+}
+
+impl TaskManager {
+    // Run after urgencies are calculated and before the rules, so rules.json still sees and adjusts
+    // what the script returned. Pinned urgencies are never changed. The first error is reported and
+    // the script isn't run on the remaining tasks
+    pub fn apply_script(&mut self, script: &Script, config: &Config) {
+        let now = self.urgency_time();
+        for id in 0..self.tasks.len() {
+            if let Err(err) = self.run_script(id, script, now, config) {
+                eprintln!("{}", err);
+                return;
+            }
+        }
+    }
+
+    fn run_script(
+        &mut self,
+        id: usize,
+        script: &Script,
+        now: DateTime<Utc>,
+        config: &Config,
+    ) -> Result<(), TaskError> {
+        let task = task_map(&self.tasks[id], now);
+        if script.urgency && !self.tasks[id].urgency_pinned {
+            if let Some(urgency) = urgency_value(script.call("urgency", &task)?)? {
+                self.tasks[id].effective_urgency = urgency;
+            }
+        }
+        if script.fields {
+            let assignments = field_values(script.call("fields", &task)?)?;
+            if !assignments.is_empty() {
+                self.set_custom_fields(id, &assignments, config);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Script;
+    use crate::config::Config;
+    use crate::custom::CustomField;
+    use crate::TaskManager;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn script_overrides_urgency_and_sets_fields() {
+        let script = Script::compile(
+            r#"
+            fn urgency(task) {
+                if task.overdue && task.custom.kind == "bug" { task.urgency + 2 }
+            }
+            fn fields(task) {
+                if task.overdue && task.custom.kind == "bug" { #{escalated: "yes"} }
+            }
+            "#,
+        )
+        .unwrap();
+        assert!(Script::compile("fn urgency(task) {").is_err());
+
+        let mut config = Config::default();
+        for name in ["kind", "escalated"] {
+            config
+                .custom_fields
+                .insert(name.to_string(), CustomField::default());
+        }
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Crash on start".to_string());
+        task_manager.add_task("Update docs".to_string());
+        task_manager.set_custom_fields(0, &["kind=bug".to_string()], &config);
+        task_manager.set_due_date(0, Utc::now() - Duration::hours(1));
+        task_manager.calculate_urgencies();
+        let before = task_manager.tasks[0].effective_urgency;
+        let untouched = task_manager.tasks[1].effective_urgency;
+        task_manager.apply_script(&script, &config);
+        assert_eq!(task_manager.tasks[0].effective_urgency, before + 2.0);
+        assert_eq!(task_manager.tasks[1].effective_urgency, untouched);
+        assert_eq!(
+            task_manager.tasks[0]
+                .custom
+                .get("escalated")
+                .map(String::as_str),
+            Some("yes")
+        );
+        assert!(!task_manager.tasks[1].custom.contains_key("escalated"));
+
+        // A loop that never ends is stopped instead of hanging the command
+        let endless = Script::compile("fn urgency(task) { loop {} }").unwrap();
+        task_manager.apply_script(&endless, &config);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::config::Config;
+use crate::{Task, TaskManager};
+
+// Reduces an English word to a rough stem so tests, testing and tested all match
+fn stem(word: &str) -> String {
+    let mut word = word.to_string();
+    // Only stem plain latin words, other scripts are compared as written
+    if !word.chars().all(|c| c.is_ascii_alphabetic()) || word.len() <= 3 {
+        return word;
+    }
+    if word.ends_with("sses") {
+        word.truncate(word.len() - 2);
+    } else if word.ends_with("ies") {
+        word.truncate(word.len() - 3);
+        word.push('y');
+    } else if word.ends_with("ches") || word.ends_with("shes") || word.ends_with("xes") {
+        word.truncate(word.len() - 2);
+    } else if word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us") {
+        word.truncate(word.len() - 1);
+    }
+    for suffix in ["ing", "ed"] {
+        if word.ends_with(suffix) && word.len() - suffix.len() >= 3 {
+            word.truncate(word.len() - suffix.len());
+            // running -> runn -> run
+            let bytes = word.as_bytes();
+            let last = bytes[bytes.len() - 1];
+            if bytes[bytes.len() - 2] == last && !b"aeioulsz".contains(&last) {
+                word.pop();
+            }
+            break;
+        }
+    }
+    word
+}
+
+// Lowercases text and splits it into comparable terms
+pub fn normalize(text: &str, stemming: bool) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word = word.to_lowercase();
+            if stemming {
+                stem(&word)
+            } else {
+                word
+            }
+        })
+        .collect()
+}
+
+fn task_matches(task: &Task, query_terms: &[String], stemming: bool) -> bool {
+    let mut terms = normalize(&task.title, stemming);
+    terms.extend(normalize(&task.description, stemming));
+    query_terms.iter().all(|query_term| {
+        terms
+            .iter()
+            .any(|term| term.starts_with(query_term.as_str()))
+    })
+}
+
+fn matching_ids<'a>(
+    tasks: impl Iterator<Item = &'a Task>,
+    query: &str,
+    config: &Config,
+) -> Vec<usize> {
+    let query_terms = normalize(query, config.search_stemming);
+    tasks
+        .enumerate()
+        .filter(|(_, task)| task_matches(task, &query_terms, config.search_stemming))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+impl TaskManager {
+    // IDs of tasks whose title or description contain every term of the query
+    pub fn search(&self, query: &str, config: &Config) -> Vec<usize> {
+        matching_ids(self.tasks.iter(), query, config)
+    }
+
+    // Same as search but over the trash, returning trash IDs
+    pub fn search_trash(&self, query: &str, config: &Config) -> Vec<usize> {
+        matching_ids(
+            self.trash.iter().map(|trashed| &trashed.task),
+            query,
+            config,
+        )
+    }
+
+    // Same as search but over the archive, returning archive IDs
+    pub fn search_archive(&self, query: &str, config: &Config) -> Vec<usize> {
+        matching_ids(
+            self.archive.iter().map(|archived| &archived.task),
+            query,
+            config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn stems_common_suffixes() {
+        assert_eq!(normalize("testing tests tested", true), vec!["test"; 3]);
+        assert_eq!(normalize("Running stories", true), vec!["run", "story"]);
+        assert_eq!(normalize("testing", false), vec!["testing"]);
+        assert_eq!(normalize("テスト", true), vec!["テスト"]);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::config::Config;
+use crate::error::TaskError;
+use crate::filter::Filter;
+use crate::i18n;
+use crate::virtual_tags::tag_term;
+use crate::{Selection, TaskManager};
+
+impl Selection {
+    // The selection as one filter expression, None when nothing was given
+    pub fn expression(&self) -> Option<String> {
+        let terms: Vec<String> = self
+            .filter
+            .iter()
+            .cloned()
+            .chain(self.tag.iter().map(|tag| tag_term(tag)))
+            .chain(self.due_before.iter().map(|day| format!("due<{}", day)))
+            .collect();
+        (!terms.is_empty()).then(|| terms.join(" "))
+    }
+}
+
+impl TaskManager {
+    // The task given by ID, or without one every task the selection matches in list order
+    pub fn select(&self, id: Option<usize>, selection: &Selection, config: &Config) -> Vec<usize> {
+        let ids = match (id, selection.expression()) {
+            (Some(id), _) if !self.verify_id(id) => {
+                eprintln!("{}", TaskError::InvalidId(id));
+                return Vec::new();
+            }
+            (Some(id), _) => vec![id],
+            (None, Some(expression)) => match Filter::parse(&expression, config) {
+                Ok(filter) => self.filtered_ids(&filter),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return Vec::new();
+                }
+            },
+            (None, None) => {
+                eprintln!("{}", TaskError::MissingSelection);
+                return Vec::new();
+            }
+        };
+        if ids.is_empty() {
+            println!("{}", i18n::text("no_matches", "No tasks match"));
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::{Selection, Status, TaskManager};
+
+    #[test]
+    fn selection_matches_tasks() {
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Pay rent".to_string());
+        task_manager.add_task("Pay bills".to_string());
+        task_manager.add_task("Water plants".to_string());
+        task_manager.set_task_status(1, Status::Active);
+        let config = Config::default();
+        let selection = Selection {
+            filter: Some("pay".to_string()),
+            tag: vec!["-active".to_string()],
+            ..Selection::default()
+        };
+        assert_eq!(task_manager.select(None, &selection, &config), vec![0]);
+        assert_eq!(task_manager.select(Some(2), &selection, &config), vec![2]);
+        assert!(task_manager
+            .select(None, &Selection::default(), &config)
+            .is_empty());
+    }
+}
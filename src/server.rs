@@ -0,0 +1,381 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+
+use crate::config::{Config, ConfigWatch};
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::{Status, Task, TaskManager, MAXIMUM_URGENCY, MINIMUM_URGENCY};
+
+// Bodies are task data at most, anything bigger is refused before it is read into memory
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+// A client that stops sending mid request would otherwise hold up every other one
+const READ_TIMEOUT_SECS: u64 = 10;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    pub fn ok(body: Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    pub fn error(status: u16, err: TaskError) -> Self {
+        Response {
+            status,
+            body: err.to_json(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+// Just enough HTTP/1.1 for a local front end, one request per connection
+pub fn read_request(stream: &TcpStream) -> Result<Request, Box<dyn Error>> {
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(READ_TIMEOUT_SECS)))?;
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let mut content_length = 0;
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(format!("body over {} bytes", MAX_BODY_BYTES).into());
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        authorization,
+        body: String::from_utf8(body)?,
+    })
+}
+
+pub fn write_response(mut stream: &TcpStream, response: &Response) -> std::io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        body.len(),
+        body
+    )
+}
+
+fn invalid(message: &str) -> TaskError {
+    TaskError::InvalidRequest(message.to_string())
+}
+
+pub fn task_json(id: usize, task: &Task) -> Value {
+    let mut value = serde_json::to_value(task).unwrap_or_default();
+    value["id"] = json!(id);
+    value["effective_urgency"] = json!(task.effective_urgency);
+    value
+}
+
+impl TaskManager {
+    // Tasks are addressed by uid in URLs, positions change whenever the list is sorted
+    fn position_of_uid(&self, uid: &str) -> Option<usize> {
+        let uid: u64 = uid.parse().ok()?;
+        self.tasks.iter().position(|task| task.uid == uid)
+    }
+
+    // Sets the fields present in a JSON object, all of them are checked before any is changed
+    pub fn apply_fields(&mut self, id: usize, fields: &Value) -> Result<(), TaskError> {
+        let fields = fields
+            .as_object()
+            .ok_or_else(|| invalid("expected an object"))?;
+        let text = |key: &str| -> Result<Option<String>, TaskError> {
+            match fields.get(key) {
+                None => Ok(None),
+                Some(value) => value
+                    .as_str()
+                    .map(|value| Some(value.to_string()))
+                    .ok_or_else(|| invalid(&format!("{} must be a string", key))),
+            }
+        };
+        let title = text("title")?;
+        let description = text("description")?;
+        let urgency = match fields.get("urgency") {
+            None => None,
+            Some(value) => {
+                let urgency = value
+                    .as_f64()
+                    .ok_or_else(|| invalid("urgency must be a number"))?
+                    as f32;
+                if !(MINIMUM_URGENCY..=MAXIMUM_URGENCY).contains(&urgency) {
+                    return Err(TaskError::InvalidUrgency(urgency));
+                }
+                Some(urgency)
+            }
+        };
+        let status: Option<Status> = match fields.get("status") {
+            None => None,
+            Some(value) => Some(
+                serde_json::from_value(value.clone())
+                    .map_err(|_| invalid("status must be Inactive, Active or Done"))?,
+            ),
+        };
+        let due_time = match text("due_time")? {
+            None => None,
+            Some(due_time) => Some(
+                DateTime::parse_from_rfc3339(&due_time)
+                    .map_err(|_| invalid("due_time must be an RFC 3339 time"))?
+                    .with_timezone(&Utc),
+            ),
+        };
+
+        if let Some(title) = title {
+            self.set_task_name(id, title);
+        }
+        if let Some(description) = description {
+            self.set_task_description(id, description);
+        }
+        if let Some(urgency) = urgency {
+            self.set_urgency(id, urgency);
+        }
+        if let Some(status) = status {
+            self.set_task_status(id, status);
+        }
+        if let Some(due_time) = due_time {
+            self.set_due_date(id, due_time);
+        }
+        Ok(())
+    }
+
+    // Routes one request, also saying whether the tasks changed and need saving
+    pub fn handle_request(&mut self, request: &Request) -> (Response, bool) {
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let body = || -> Result<Value, TaskError> {
+            serde_json::from_str(&request.body).map_err(|err| invalid(&err.to_string()))
+        };
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["tasks"]) => {
+                let tasks: Vec<Value> = self
+                    .tasks
+                    .iter()
+                    .enumerate()
+                    .map(|(id, task)| task_json(id, task))
+                    .collect();
+                (Response::ok(json!(tasks)), false)
+            }
+            ("POST", ["tasks"]) => {
+                let fields = match body() {
+                    Ok(fields) => fields,
+                    Err(err) => return (Response::error(400, err), false),
+                };
+                let title = match fields.get("title").and_then(Value::as_str) {
+                    Some(title) => title.to_string(),
+                    None => return (Response::error(400, invalid("title is required")), false),
+                };
+                self.add_task(title);
+                let id = self.tasks.len() - 1;
+                match self.apply_fields(id, &fields) {
+                    Ok(()) => {
+                        let response = Response {
+                            status: 201,
+                            body: task_json(id, &self.tasks[id]),
+                        };
+                        (response, true)
+                    }
+                    Err(err) => {
+                        self.tasks.pop();
+                        self.next_uid -= 1;
+                        (Response::error(400, err), false)
+                    }
+                }
+            }
+            (method, ["tasks", uid]) => {
+                let id = match self.position_of_uid(uid) {
+                    Some(id) => id,
+                    None => {
+                        let uid = uid.parse().unwrap_or_default();
+                        return (Response::error(404, TaskError::InvalidId(uid)), false);
+                    }
+                };
+                match method {
+                    "GET" => (Response::ok(task_json(id, &self.tasks[id])), false),
+                    "PATCH" => match body().and_then(|fields| self.apply_fields(id, &fields)) {
+                        Ok(()) => (Response::ok(task_json(id, &self.tasks[id])), true),
+                        Err(err) => (Response::error(400, err), false),
+                    },
+                    "DELETE" => {
+                        let deleted = self.tasks[id].uid;
+                        self.trash_task(id);
+                        (Response::ok(json!({ "deleted": deleted })), true)
+                    }
+                    _ => (Response::error(405, invalid("unsupported method")), false),
+                }
+            }
+            _ => (Response::error(404, invalid("unknown path")), false),
+        }
+    }
+}
+
+// Answered when the data file can't be read again before a request
+pub fn unreadable(path: &Path, err: Box<dyn Error>) -> Response {
+    Response::error(
+        503,
+        TaskError::UnreadableData {
+            path: path.display().to_string(),
+            reason: err.to_string(),
+        },
+    )
+}
+
+// Serves the task list on localhost until interrupted, saving after every change
+pub fn serve(
+    task_manager: &mut TaskManager,
+    port: u16,
+    path: &PathBuf,
+    mut config: Config,
+    mut watch: ConfigWatch,
+) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!(
+        "{}",
+        tr!(
+            "server_started",
+            "Serving tasks on http://127.0.0.1:{0}",
+            port
+        )
+    );
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let response = match read_request(&stream) {
+            Ok(request) => {
+                if let Some(message) = watch.reload(&mut config) {
+                    eprintln!("{}", message);
+                }
+                if let Err(err) = task_manager.reload(path, &config) {
+                    let _ = write_response(&stream, &unreadable(path, err));
+                    continue;
+                }
+                let (response, changed) = task_manager.handle_request(&request);
+                if changed {
+                    task_manager.calculate_urgencies();
+                    task_manager.derive_from_dependencies();
+                    task_manager.sort_by_urgencies();
+                    task_manager.save_to_file(path)?;
+                }
+                response
+            }
+            Err(err) => Response::error(400, invalid(&err.to_string())),
+        };
+        let _ = write_response(&stream, &response);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Request;
+    use crate::config::Config;
+    use crate::TaskManager;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            authorization: None,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn creates_and_patches_tasks() {
+        let mut task_manager = TaskManager::new();
+        let (response, changed) =
+            task_manager.handle_request(&request("POST", "/tasks", r#"{"title": "Write report"}"#));
+        assert_eq!(response.status, 201);
+        assert!(changed);
+        let uid = response.body["uid"].as_u64().unwrap();
+
+        let (response, _) = task_manager.handle_request(&request(
+            "PATCH",
+            &format!("/tasks/{}", uid),
+            r#"{"urgency": 11}"#,
+        ));
+        assert_eq!(response.status, 400);
+        let (response, _) = task_manager.handle_request(&request(
+            "PATCH",
+            &format!("/tasks/{}", uid),
+            r#"{"status": "Active"}"#,
+        ));
+        assert_eq!(response.body["status"], "Active");
+        let (response, _) = task_manager.handle_request(&request("GET", "/tasks/99", ""));
+        assert_eq!(response.status, 404);
+    }
+
+    #[test]
+    fn keeps_changes_made_while_serving() {
+        let data_dir = std::env::temp_dir().join(format!("tasks-serve-{}", std::process::id()));
+        create_dir_all(&data_dir).unwrap();
+        let data_file = data_dir.join("task.json");
+        let mut server = TaskManager::new();
+        server.save_to_file(&data_file).unwrap();
+
+        // Added from the command line while the server runs
+        let mut cli = TaskManager::load_from_file(&data_file).unwrap();
+        cli.add_task("Pay rent".to_string());
+        cli.save_to_file(&data_file).unwrap();
+
+        server.reload(&data_file, &Config::default()).unwrap();
+        server.handle_request(&request("POST", "/tasks", r#"{"title": "Write report"}"#));
+        server.save_to_file(&data_file).unwrap();
+        let saved = TaskManager::load_from_file(&data_file).unwrap();
+        assert_eq!(saved.tasks.len(), 2);
+        remove_dir_all(&data_dir).unwrap();
+    }
+}
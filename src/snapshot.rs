@@ -0,0 +1,130 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::dates::parse_duration;
+use crate::history::parse_history_datetime;
+use crate::{Status, Task, TaskManager};
+
+fn parse_status(value: &str) -> Option<Status> {
+    [Status::Inactive, Status::Active, Status::Done]
+        .into_iter()
+        .find(|status| status.as_str() == value)
+}
+
+impl Task {
+    // The task as it was at a past time by undoing later changes, None if it didn't exist yet
+    fn as_of(&self, at: DateTime<Utc>) -> Option<Task> {
+        if self
+            .created_at
+            .or(self.start_time)
+            .is_some_and(|created| created > at)
+        {
+            return None;
+        }
+        let mut task = self.clone();
+        task.history.retain(|change| change.time <= at);
+        for change in self.history.iter().rev().filter(|change| change.time > at) {
+            let old_value = change.old_value.as_str();
+            match change.field.as_str() {
+                "title" => task.title = old_value.to_string(),
+                "description" => task.description = old_value.to_string(),
+                "status" => task.status = parse_status(old_value).unwrap_or(task.status),
+                "urgency" => task.urgency = old_value.parse().unwrap_or(task.urgency),
+                "due" => task.due_time = parse_history_datetime(old_value),
+                "scheduled" => task.scheduled = parse_history_datetime(old_value),
+                "until" => task.until = parse_history_datetime(old_value),
+                "estimate" => task.estimate = parse_duration(&old_value.replace(' ', "")),
+                "soft due" => task.soft_due = old_value == "true",
+                "urgency pinned" => task.urgency_pinned = old_value == "true",
+                "links" => {
+                    task.links = match old_value {
+                        "none" => Vec::new(),
+                        links => links.split(' ').map(str::to_string).collect(),
+                    }
+                }
+                "assignee" => {
+                    task.assignee = match old_value {
+                        "none" => None,
+                        assignee => Some(assignee.to_string()),
+                    }
+                }
+                "tags" => {
+                    task.tags = match old_value {
+                        "none" => Vec::new(),
+                        tags => tags.split(' ').map(str::to_string).collect(),
+                    }
+                }
+                "project" => {
+                    task.project = match old_value {
+                        "none" => None,
+                        project => Some(project.to_string()),
+                    }
+                }
+                "progress" => task.progress = old_value.parse().ok(),
+                "recur" => {
+                    task.recur = match old_value {
+                        "none" => None,
+                        rule => Some(rule.to_string()),
+                    }
+                }
+                "depends on" => {
+                    task.depends_on = old_value
+                        .split(' ')
+                        .filter_map(|uid| uid.strip_prefix('#')?.parse().ok())
+                        .collect()
+                }
+                "attachment" => task
+                    .attachments
+                    .retain(|path| path.display().to_string() != change.new_value),
+                "planned" => {
+                    task.planned_on = NaiveDate::parse_from_str(old_value, "%d/%m/%Y").ok()
+                }
+                _ => {}
+            }
+        }
+        task.active_since = match task.status {
+            Status::Active => task
+                .history
+                .iter()
+                .rev()
+                .find(|change| change.field == "status")
+                .map(|change| change.time),
+            _ => None,
+        };
+        task.modified_at = task
+            .history
+            .last()
+            .map(|change| change.time)
+            .or(task.created_at);
+        Some(task)
+    }
+}
+
+impl TaskManager {
+    // A read only copy of the task list as it was at a past time, including tasks trashed or
+    // archived since. Changes older than the history can't be undone and keep their current value
+    pub fn snapshot_at(&self, at: DateTime<Utc>) -> TaskManager {
+        let trashed = self
+            .trash
+            .iter()
+            .filter(|trashed| trashed.deleted_at > at)
+            .map(|trashed| &trashed.task);
+        let archived = self
+            .archive
+            .iter()
+            .filter(|archived| archived.archived_at > at)
+            .map(|archived| &archived.task);
+        let mut snapshot = TaskManager::new();
+        snapshot.tasks = self
+            .tasks
+            .iter()
+            .chain(trashed)
+            .chain(archived)
+            .filter_map(|task| task.as_of(at))
+            .collect();
+        for task in snapshot.tasks.iter_mut() {
+            task.calculate_urgency(at, &self.urgency);
+        }
+        snapshot.sort_by_urgencies();
+        snapshot
+    }
+}
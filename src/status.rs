@@ -0,0 +1,156 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{read_to_string, File};
+use std::path::{Path, PathBuf};
+
+use crate::dates::{format_duration, to_local};
+use crate::i18n::{self, tr};
+use crate::{Status, TaskManager};
+
+// Written next to the task data on every save, so status doesn't have to read every task
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusCache {
+    pub active: Vec<ActiveTask>,
+    // Due times of open tasks, counted against the time status is run
+    pub due: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActiveTask {
+    pub title: String,
+    pub since: Option<DateTime<Utc>>,
+}
+
+pub fn status_path(data_file: &Path) -> PathBuf {
+    data_file.with_file_name("status.json")
+}
+
+impl StatusCache {
+    // e.g. 1 overdue and 3 due today, counts of none are left out
+    fn counts(&self, now: DateTime<Utc>) -> Vec<String> {
+        let today = Local::now().date_naive();
+        let overdue = self.due.iter().filter(|due| **due < now).count();
+        let due_today = self
+            .due
+            .iter()
+            .filter(|due| **due >= now && to_local(**due).date() == today)
+            .count();
+        let mut counts = Vec::new();
+        if overdue > 0 {
+            counts.push(tr!("status_overdue", "{0} overdue", overdue));
+        }
+        if due_today > 0 {
+            counts.push(tr!("status_due_today", "{0} due today", due_today));
+        }
+        counts
+    }
+
+    // e.g. ▶ Fix deploy script (2h 13m) | 3 due today, empty when there is nothing to show
+    pub fn short_line(&self, now: DateTime<Utc>) -> String {
+        let mut parts = Vec::new();
+        if let Some(active) = self.active.first() {
+            let mut part = format!("▶ {}", active.title);
+            if let Some(since) = active.since {
+                part += &format!(" ({})", format_duration(now - since));
+            }
+            if self.active.len() > 1 {
+                part += &format!(" +{}", self.active.len() - 1);
+            }
+            parts.push(part);
+        }
+        parts.extend(self.counts(now));
+        parts.join(" | ")
+    }
+
+    pub fn long_lines(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .active
+            .iter()
+            .map(|active| match active.since {
+                Some(since) => tr!(
+                    "status_active_for",
+                    "Active: {0} for {1}",
+                    active.title,
+                    format_duration(now - since)
+                ),
+                None => tr!("status_active", "Active: {0}", active.title),
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(i18n::text("nothing_active", "Nothing active"));
+        }
+        let counts = self.counts(now);
+        if !counts.is_empty() {
+            lines.push(counts.join(", "));
+        }
+        lines
+    }
+}
+
+impl TaskManager {
+    pub fn status_cache(&self) -> StatusCache {
+        StatusCache {
+            active: self
+                .tasks
+                .iter()
+                .filter(|task| task.status == Status::Active)
+                .map(|task| ActiveTask {
+                    title: task.title.clone(),
+                    since: task.active_since,
+                })
+                .collect(),
+            due: self
+                .tasks
+                .iter()
+                .filter(|task| task.status != Status::Done)
+                .filter_map(|task| task.due_time)
+                .collect(),
+        }
+    }
+
+    pub fn save_status(&self, data_file: &Path) -> Result<(), Box<dyn Error>> {
+        let file = File::create(status_path(data_file))?;
+        serde_json::to_writer(file, &self.status_cache())?;
+        Ok(())
+    }
+}
+
+// Reads only the cache, before the task data is loaded. Nothing is printed before the first save
+pub fn print_status(data_file: &Path, short: bool) -> Result<(), Box<dyn Error>> {
+    let cache: StatusCache = match read_to_string(status_path(data_file)) {
+        Ok(text) => serde_json::from_str(&text)?,
+        Err(_) => StatusCache::default(),
+    };
+    let now = Utc::now();
+    if short {
+        println!("{}", cache.short_line(now));
+    } else {
+        for line in cache.long_lines(now) {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Status, TaskManager};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn short_line_shows_the_active_task_and_counts() {
+        let mut task_manager = TaskManager::new();
+        let now = Utc::now();
+        assert_eq!(task_manager.status_cache().short_line(now), "");
+        task_manager.add_task("Fix deploy script".to_string());
+        task_manager.add_task("Renew domain".to_string());
+        task_manager.set_task_status(0, Status::Active);
+        task_manager.tasks[0].active_since = Some(now - Duration::minutes(133));
+        task_manager.set_due_date(1, now - Duration::hours(1));
+        assert_eq!(
+            task_manager.status_cache().short_line(now),
+            "▶ Fix deploy script (2h 13m) | 1 overdue"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use crate::duplicates::edit_distance;
+
+// How many letters can be wrong for a name to be taken for a typo, e.g. dnoe for done. Short
+// names are allowed fewer, or every two letter word would match something
+fn max_distance(name: &[char]) -> usize {
+    if name.len() <= 3 {
+        1
+    } else {
+        2
+    }
+}
+
+fn sorted(letters: &[char]) -> Vec<char> {
+    let mut letters = letters.to_vec();
+    letters.sort_unstable();
+    letters
+}
+
+// The command closest to name, if it is close enough to be what was meant. Of those as close, one
+// with the same letters wins, since swapping two is the usual typo, then the first one listed
+pub fn closest_command<'a>(name: &str, commands: &'a [String]) -> Option<&'a str> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    commands
+        .iter()
+        .map(|command| {
+            let letters: Vec<char> = command.chars().collect();
+            let distance = edit_distance(&name, &letters);
+            (distance, sorted(&name) != sorted(&letters), command)
+        })
+        .filter(|(distance, _, _)| *distance <= max_distance(&name))
+        .min_by_key(|(distance, other_letters, _)| (*distance, *other_letters))
+        .map(|(_, _, command)| command.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::closest_command;
+    use crate::man::command_names;
+
+    #[test]
+    fn typos_suggest_the_closest_command() {
+        let mut commands = command_names();
+        assert!(commands.contains(&"done".to_string()));
+        assert_eq!(closest_command("dnoe", &commands), Some("done"));
+        assert_eq!(closest_command("LSIT", &commands), Some("list"));
+        assert_eq!(closest_command("ad", &commands), Some("add"));
+        assert_eq!(closest_command("standup", &commands), None);
+        commands.push("standup".to_string());
+        assert_eq!(closest_command("stnadup", &commands), Some("standup"));
+    }
+}
@@ -0,0 +1,247 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+use crate::config::{Config, ConfigWatch};
+use crate::error::TaskError;
+use crate::events::Event;
+use crate::i18n::tr;
+use crate::merge::{same_task, task_data, MergeReport, Prefer};
+use crate::server::{read_request, unreadable, write_response, Request, Response};
+use crate::trash::TrashedTask;
+use crate::TaskManager;
+
+// What a sync changed on one side
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    pub merged: MergeReport,
+    pub removed: Vec<String>,
+}
+
+fn invalid(message: &str) -> TaskError {
+    TaskError::InvalidRequest(message.to_string())
+}
+
+// Tokens are compared in full whatever the first difference, so the time taken gives nothing away
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+// "http://host:port/path" as the address to connect to and the path, there's no TLS so https is
+// left to a proxy in front of the server
+fn parse_url(url: &str) -> Result<(String, String), TaskError> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| invalid("the sync url must start with http://"))?;
+    let (host, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], rest[slash..].trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    if host.is_empty() {
+        return Err(invalid("the sync url has no host"));
+    }
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    Ok((address, format!("{}/sync", path)))
+}
+
+impl TaskManager {
+    // Takes in another device's tasks. A task removed on one side stays removed unless it was
+    // changed on the other side after that, everything else is merged with the newer change
+    // winning, so both sides end up the same once each has the other's data
+    pub fn sync_with(&mut self, mut remote: TaskManager) -> SyncReport {
+        let mut report = SyncReport::default();
+        remote.tasks.retain(|task| {
+            !self.trash.iter().any(|trashed| {
+                same_task(&trashed.task, task) && task.modified_at <= Some(trashed.deleted_at)
+            })
+        });
+        for trashed in std::mem::take(&mut remote.trash) {
+            let id = self.tasks.iter().position(|task| {
+                same_task(task, &trashed.task) && task.modified_at <= Some(trashed.deleted_at)
+            });
+            if let Some(id) = id {
+                let task = self.tasks[id].clone();
+                self.apply(Event::TaskRemoved { uid: task.uid });
+                report.removed.push(task.title.clone());
+                self.trash.push(TrashedTask {
+                    task,
+                    deleted_at: trashed.deleted_at,
+                });
+            }
+        }
+        report.merged = self.merge(remote, Prefer::Newer, false);
+        report
+    }
+
+    // POST /sync with the client's task data, answered with the data after merging
+    pub fn handle_sync_request(&mut self, request: &Request, token: &str) -> (Response, bool) {
+        let authorized = request
+            .authorization
+            .as_deref()
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .is_some_and(|given| same_token(given, token));
+        if !authorized {
+            return (
+                Response::error(401, invalid("missing or wrong sync token")),
+                false,
+            );
+        }
+        if request.path.trim_end_matches('/') != "/sync" {
+            return (Response::error(404, invalid("unknown path")), false);
+        }
+        if request.method != "POST" {
+            return (Response::error(405, invalid("unsupported method")), false);
+        }
+        let remote = match serde_json::from_str(&request.body)
+            .map_err(|err| err.into())
+            .and_then(task_data)
+        {
+            Ok(remote) => remote,
+            Err(err) => return (Response::error(400, invalid(&err.to_string())), false),
+        };
+        let report = self.sync_with(remote);
+        let changed = report != SyncReport::default();
+        let body = serde_json::to_value(&*self).unwrap_or_else(|_| json!({}));
+        (Response::ok(body), changed)
+    }
+
+    // Sends the tasks to a sync server and takes in what it sends back
+    pub fn sync_remote(&mut self, url: &str, token: &str) -> Result<SyncReport, Box<dyn Error>> {
+        let (address, path) = parse_url(url)?;
+        let body = serde_json::to_string(&*self)?;
+        let mut stream = TcpStream::connect(&address)?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nAuthorization: Bearer {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            address,
+            token,
+            body.len(),
+            body
+        )?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| invalid("the sync server sent no body"))?;
+        let status = head.split_whitespace().nth(1).unwrap_or_default();
+        let body: Value = serde_json::from_str(body)?;
+        if status != "200" {
+            let message = body["message"].as_str().unwrap_or(status);
+            return Err(tr!("sync_failed", "Sync failed: {0}", message).into());
+        }
+        Ok(self.sync_with(task_data(body)?))
+    }
+}
+
+// Serves the task data to sync with until interrupted, saving after every change. Unlike serve
+// this listens on every address, so other devices can reach it. Each sync starts from the data
+// file as it is then, so changes made on this machine in the meantime are synced too
+pub fn serve_sync(
+    task_manager: &mut TaskManager,
+    port: u16,
+    token: &str,
+    path: &PathBuf,
+    mut config: Config,
+    mut watch: ConfigWatch,
+) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!(
+        "{}",
+        tr!("sync_server_started", "Serving sync on port {0}", port)
+    );
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let response = match read_request(&stream) {
+            Ok(request) => {
+                if let Some(message) = watch.reload(&mut config) {
+                    eprintln!("{}", message);
+                }
+                if let Err(err) = task_manager.reload(path, &config) {
+                    let _ = write_response(&stream, &unreadable(path, err));
+                    continue;
+                }
+                let (response, changed) = task_manager.handle_sync_request(&request, token);
+                if changed {
+                    task_manager.calculate_urgencies();
+                    task_manager.derive_from_dependencies();
+                    task_manager.sort_by_urgencies();
+                    task_manager.save_to_file(path)?;
+                }
+                response
+            }
+            Err(err) => Response::error(400, invalid(&err.to_string())),
+        };
+        let _ = write_response(&stream, &response);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_url;
+    use crate::server::Request;
+    use crate::TaskManager;
+
+    fn copy(task_manager: &TaskManager) -> TaskManager {
+        serde_json::from_value(serde_json::to_value(task_manager).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn devices_converge_through_the_server() {
+        let mut server = TaskManager::new();
+        server.add_task("Pay rent".to_string());
+        server.add_task("Water plants".to_string());
+        let mut laptop = copy(&server);
+        let mut phone = copy(&server);
+        laptop.set_urgency(0, 8.0);
+        laptop.add_task("Book flights".to_string());
+        phone.trash_task(1);
+
+        let sync = |device: &mut TaskManager, server: &mut TaskManager, token: &str| {
+            let request = Request {
+                method: "POST".to_string(),
+                path: "/sync".to_string(),
+                authorization: Some(format!("Bearer {}", token)),
+                body: serde_json::to_string(device).unwrap(),
+            };
+            let (response, _) = server.handle_sync_request(&request, "secret");
+            if response.status == 200 {
+                device.sync_with(serde_json::from_value(response.body).unwrap());
+            }
+            response.status
+        };
+        assert_eq!(sync(&mut phone, &mut server, "guess"), 401);
+        assert_eq!(sync(&mut laptop, &mut server, "secret"), 200);
+        assert_eq!(sync(&mut phone, &mut server, "secret"), 200);
+        assert_eq!(sync(&mut laptop, &mut server, "secret"), 200);
+
+        let titles = |device: &TaskManager| -> Vec<String> {
+            device.tasks.iter().map(|task| task.title.clone()).collect()
+        };
+        assert_eq!(titles(&laptop), vec!["Pay rent", "Book flights"]);
+        assert_eq!(titles(&phone), titles(&laptop));
+        assert_eq!(titles(&server), titles(&laptop));
+        assert_eq!(phone.tasks[0].urgency, 8.0);
+
+        assert_eq!(
+            parse_url("http://example.com/tasks/").unwrap(),
+            ("example.com:80".to_string(), "/tasks/sync".to_string())
+        );
+        assert!(parse_url("https://example.com").is_err());
+    }
+}
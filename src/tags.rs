@@ -0,0 +1,114 @@
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::virtual_tags::VirtualTag;
+use crate::TaskManager;
+
+// As in the history, e.g. "errands thesis"
+pub fn tags_value(tags: &[String]) -> String {
+    if tags.is_empty() {
+        "none".to_string()
+    } else {
+        tags.join(" ")
+    }
+}
+
+// Tags are single lowercase words. The names of virtual tags are taken, +OVERDUE couldn't tell
+// the two apart
+pub fn parse_tag(name: &str) -> Result<String, TaskError> {
+    let tag = name.trim().to_lowercase();
+    let invalid = tag.is_empty()
+        || tag.starts_with(['+', '-'])
+        || tag.contains(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        || VirtualTag::parse(&tag).is_some();
+    if invalid {
+        return Err(TaskError::InvalidTag(name.to_string()));
+    }
+    Ok(tag)
+}
+
+// Labels from other tools, e.g. "good first issue" becomes good-first-issue. None when nothing
+// usable is left or it would clash with a virtual tag
+pub fn tag_from_label(label: &str) -> Option<String> {
+    let words: Vec<&str> = label
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+        .filter(|word| !word.is_empty())
+        .collect();
+    let tag = words.join("-");
+    parse_tag(tag.trim_start_matches(['+', '-'])).ok()
+}
+
+impl TaskManager {
+    // Each change adds a tag, given bare or as +tag, or removes one given as -tag
+    pub fn change_tags(&mut self, id: usize, changes: &[String]) -> Result<(), TaskError> {
+        if !self.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        let mut tags = self.tasks[id].tags.clone();
+        for change in changes {
+            match change.strip_prefix('-') {
+                Some(tag) => {
+                    let tag = parse_tag(tag)?;
+                    tags.retain(|other| *other != tag);
+                }
+                None => {
+                    let tag = parse_tag(change.strip_prefix('+').unwrap_or(change))?;
+                    if !tags.contains(&tag) {
+                        tags.push(tag);
+                    }
+                }
+            }
+        }
+        let task = &mut self.tasks[id];
+        task.record_change("tags", tags_value(&task.tags), tags_value(&tags));
+        self.set_field(id, FieldValue::Tags(tags));
+        Ok(())
+    }
+
+    pub fn set_project(&mut self, id: usize, project: Option<String>) {
+        if self.verify_id(id) {
+            let project = project
+                .map(|project| project.trim().to_string())
+                .filter(|project| !project.is_empty());
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "project",
+                task.project.clone().unwrap_or_else(|| "none".to_string()),
+                project.clone().unwrap_or_else(|| "none".to_string()),
+            );
+            self.set_field(id, FieldValue::Project(project));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_tag, tag_from_label};
+    use crate::TaskManager;
+
+    #[test]
+    fn tags_are_added_once_and_removed() {
+        let mut debug_manager = TaskManager::new();
+        debug_manager.add_task("Buy milk".to_string());
+        let changes = [
+            "Errands".to_string(),
+            "+shop".to_string(),
+            "errands".to_string(),
+        ];
+        debug_manager.change_tags(0, &changes).unwrap();
+        assert_eq!(debug_manager.tasks[0].tags, vec!["errands", "shop"]);
+        debug_manager
+            .change_tags(0, &["-shop".to_string()])
+            .unwrap();
+        assert_eq!(debug_manager.tasks[0].tags, vec!["errands"]);
+        assert!(parse_tag("overdue").is_err());
+        assert!(parse_tag("two words").is_err());
+        assert!(debug_manager.change_tags(0, &["+".to_string()]).is_err());
+        assert_eq!(
+            tag_from_label("Good First Issue").as_deref(),
+            Some("good-first-issue")
+        );
+        assert_eq!(tag_from_label("Overdue"), None);
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{create_dir_all, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use crate::config::config_path;
+use crate::Task;
+
+// A reusable task skeleton, due time is stored relative to when the task is created
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    pub description: String,
+    pub urgency: f32,
+    pub due_offset_seconds: Option<i64>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TemplateStore {
+    pub templates: BTreeMap<String, Template>,
+}
+
+impl Template {
+    pub fn from_task(task: &Task) -> Self {
+        let due_offset_seconds = match (task.start_time, task.due_time) {
+            (Some(start_time), Some(due_time)) => Some((due_time - start_time).num_seconds()),
+            _ => None,
+        };
+        Template {
+            description: task.description.clone(),
+            urgency: task.urgency,
+            due_offset_seconds,
+        }
+    }
+
+    pub fn due_offset(&self) -> Option<Duration> {
+        self.due_offset_seconds.map(Duration::seconds)
+    }
+}
+
+impl TemplateStore {
+    // Templates live next to the config, in XDG_CONFIG/task/templates.json
+    pub fn default_path() -> Option<PathBuf> {
+        config_path("templates.json")
+    }
+
+    pub fn load_from_file(filename: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+        let store: TemplateStore = serde_json::from_reader(reader)?;
+        Ok(store)
+    }
+
+    pub fn save_to_file(&self, filename: &PathBuf) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = filename.parent() {
+            create_dir_all(parent)?;
+        }
+        let file = File::create(filename)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Columns the text takes up in a terminal, wide CJK characters and emoji count as two
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+// The longest start of the text that fits in width columns, never splitting a grapheme
+pub fn truncate(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+// Pads with spaces on the right up to width columns
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+// Breaks text into lines of at most width columns, between words where it can
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let separator = usize::from(!line.is_empty());
+            if display_width(&line) + separator + display_width(word) <= width {
+                if separator == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Words longer than a whole line are split wherever they have to be
+            let mut rest = word;
+            while display_width(rest) > width {
+                let mut head = truncate(rest, width);
+                if head.is_empty() {
+                    // A grapheme wider than the line still has to go somewhere
+                    head = rest.graphemes(true).next().unwrap_or(rest);
+                }
+                lines.push(head.to_string());
+                rest = &rest[head.len()..];
+            }
+            line.push_str(rest);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display_width, pad, truncate, wrap};
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(truncate("日本語", 5), "日本");
+        assert_eq!(truncate("e\u{301}te\u{301}", 2), "e\u{301}t");
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(
+            wrap("write the 日本語 report", 9),
+            vec!["write the", "日本語", "report"]
+        );
+        assert_eq!(wrap("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(wrap("one\n\ntwo", 10), vec!["one", "", "two"]);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::io::{stdout, IsTerminal};
+
+use crate::config::Config;
+use crate::MAXIMUM_URGENCY;
+
+const RESET: &str = "\x1b[0m";
+
+// Urgencies from here up are painted as urgency-high in list
+pub const HIGH_URGENCY: f32 = 7.0;
+
+pub const THEMES: [&str; 3] = ["default", "solarized", "mono"];
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// Printed under a list drawn with urgency bars
+pub const URGENCY_LEGEND: &str = "Urgency: ▂ 2.5  ▄ 5  ▆ 7.5  █ 10 or more";
+
+// A block as tall as the urgency is of the maximum, overdue tasks go past it and stay full
+pub fn urgency_bar(urgency: f32) -> char {
+    let level = (urgency / MAXIMUM_URGENCY * BARS.len() as f32).ceil() as usize;
+    BARS[level.clamp(1, BARS.len()) - 1]
+}
+
+// Parts of the output that can be given their own style under colors in config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Element {
+    Overdue,
+    Active,
+    Done,
+    Header,
+    UrgencyHigh,
+    Warning,
+    Removed,
+    Added,
+}
+
+pub const ALL_ELEMENTS: [Element; 8] = [
+    Element::Overdue,
+    Element::Active,
+    Element::Done,
+    Element::Header,
+    Element::UrgencyHigh,
+    Element::Warning,
+    Element::Removed,
+    Element::Added,
+];
+
+impl Element {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Overdue => "overdue",
+            Element::Active => "active",
+            Element::Done => "done",
+            Element::Header => "header",
+            Element::UrgencyHigh => "urgency-high",
+            Element::Warning => "warning",
+            Element::Removed => "removed",
+            Element::Added => "added",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        ALL_ELEMENTS
+            .iter()
+            .find(|element| element.name() == name)
+            .copied()
+    }
+}
+
+fn colour_code(name: &str) -> Option<u8> {
+    let colours = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+    colours
+        .iter()
+        .position(|colour| *colour == name)
+        .map(|index| index as u8)
+}
+
+// A style such as "bold red", "bright-blue", "on-yellow", "none" or raw codes like "38;5;136"
+pub fn parse_style(style: &str) -> Option<String> {
+    let mut codes = Vec::new();
+    for word in style.split_whitespace() {
+        let code = match word {
+            "none" => continue,
+            "bold" => "1".to_string(),
+            "dim" => "2".to_string(),
+            "italic" => "3".to_string(),
+            "underline" => "4".to_string(),
+            _ if word.chars().all(|c| c.is_ascii_digit() || c == ';') => word.to_string(),
+            _ => {
+                if let Some(colour) = word.strip_prefix("bright-") {
+                    (90 + colour_code(colour)?).to_string()
+                } else if let Some(colour) = word.strip_prefix("on-") {
+                    (40 + colour_code(colour)?).to_string()
+                } else {
+                    (30 + colour_code(word)?).to_string()
+                }
+            }
+        };
+        codes.push(code);
+    }
+    if codes.is_empty() {
+        Some(String::new())
+    } else {
+        Some(format!("\x1b[{}m", codes.join(";")))
+    }
+}
+
+fn builtin_styles(theme: &str) -> [(Element, &'static str); 8] {
+    match theme {
+        "solarized" => [
+            (Element::Overdue, "38;5;136"),
+            (Element::Active, "38;5;64"),
+            (Element::Done, "38;5;240"),
+            (Element::Header, "bold 38;5;33"),
+            (Element::UrgencyHigh, "38;5;160"),
+            (Element::Warning, "38;5;166"),
+            (Element::Removed, "38;5;160"),
+            (Element::Added, "38;5;64"),
+        ],
+        "mono" => [
+            (Element::Overdue, "bold"),
+            (Element::Active, "underline"),
+            (Element::Done, "dim"),
+            (Element::Header, "bold"),
+            (Element::UrgencyHigh, "bold"),
+            (Element::Warning, "bold"),
+            (Element::Removed, "dim"),
+            (Element::Added, "bold"),
+        ],
+        _ => [
+            (Element::Overdue, "yellow"),
+            (Element::Active, "green"),
+            (Element::Done, "dim"),
+            (Element::Header, "bold"),
+            (Element::UrgencyHigh, "red"),
+            (Element::Warning, "yellow"),
+            (Element::Removed, "red"),
+            (Element::Added, "green"),
+        ],
+    }
+}
+
+// Escape codes for each element, the chosen theme with the colors from config on top
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    styles: BTreeMap<Element, String>,
+}
+
+impl Theme {
+    pub fn new(name: &str, colors: &BTreeMap<String, String>) -> Self {
+        let mut styles: BTreeMap<Element, String> = builtin_styles(name)
+            .iter()
+            .filter_map(|(element, style)| Some((*element, parse_style(style)?)))
+            .collect();
+        for (name, style) in colors {
+            if let (Some(element), Some(style)) = (Element::parse(name), parse_style(style)) {
+                styles.insert(element, style);
+            }
+        }
+        Theme { styles }
+    }
+
+    pub fn paint(&self, element: Element, text: &str) -> String {
+        match self.styles.get(&element) {
+            Some(style) if !style.is_empty() => format!("{}{}{}", style, text, RESET),
+            _ => text.to_string(),
+        }
+    }
+}
+
+// NO_COLOR set to anything but empty turns colors off, see no-color.org
+fn no_color(value: Option<std::ffi::OsString>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+impl Config {
+    // No colors at all in plain output, whatever the theme, nor when NO_COLOR is set or the
+    // output goes to a pipe or file, where escape codes would only get in the way
+    pub fn theme(&self) -> Theme {
+        if self.plain || no_color(std::env::var_os("NO_COLOR")) || !stdout().is_terminal() {
+            return Theme {
+                styles: BTreeMap::new(),
+            };
+        }
+        Theme::new(&self.theme, &self.colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{no_color, parse_style, urgency_bar, Element, Theme};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn urgency_bars_grow_with_urgency() {
+        let bars: String = [0.0, 2.5, 5.0, 7.5, 10.0, 14.0]
+            .iter()
+            .map(|urgency| urgency_bar(*urgency))
+            .collect();
+        assert_eq!(bars, "▁▂▄▆██");
+    }
+
+    #[test]
+    fn styles_and_overrides() {
+        assert_eq!(parse_style("bold red").as_deref(), Some("\x1b[1;31m"));
+        assert_eq!(
+            parse_style("bright-blue on-white").as_deref(),
+            Some("\x1b[94;47m")
+        );
+        assert_eq!(parse_style("none").as_deref(), Some(""));
+        assert_eq!(parse_style("purple"), None);
+
+        let mut colors = BTreeMap::new();
+        colors.insert("overdue".to_string(), "none".to_string());
+        let theme = Theme::new("mono", &colors);
+        assert_eq!(theme.paint(Element::Overdue, "late"), "late");
+        assert_eq!(theme.paint(Element::Header, "ID"), "\x1b[1mID\x1b[0m");
+
+        assert!(no_color(Some("1".into())));
+        assert!(!no_color(Some("".into())));
+        assert!(!no_color(None));
+    }
+}
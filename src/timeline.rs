@@ -0,0 +1,197 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use term_size::dimensions;
+
+use crate::config::Config;
+use crate::dates::to_local;
+use crate::filter::Filter;
+use crate::i18n::tr;
+use crate::text::{pad, truncate};
+use crate::theme::Element;
+use crate::{Status, Task, TaskManager, DEFAULT_TERMINAL_WIDTH};
+
+// Room for the ID and title before the bars start
+const LABEL_WIDTH: usize = 24;
+
+const BAR: char = '█';
+const OVERDUE: char = '!';
+
+// First and last day of the task's bar: from when it is scheduled, or else when it started, to its
+// due date. Open tasks past due carry on to today as their overdue span
+fn span(task: &Task, today: NaiveDate) -> Option<(NaiveDate, NaiveDate, NaiveDate)> {
+    let due = to_local(task.due_time?).date();
+    let start = task
+        .scheduled
+        .or(task.start_time)
+        .map(|start| to_local(start).date())
+        .unwrap_or(due)
+        .min(due);
+    let end = if task.status != Status::Done && due < today {
+        today
+    } else {
+        due
+    };
+    Some((start, due, end))
+}
+
+impl TaskManager {
+    // Tasks with a due date as bars over the days from..=to, each column a day or, when the range
+    // is wider than the terminal, a few days. The last row counts the tasks on each column so
+    // crowded days stand out
+    pub fn timeline(
+        &self,
+        filter: &Filter,
+        from: NaiveDate,
+        to: NaiveDate,
+        width: Option<usize>,
+        today: NaiveDate,
+        config: &Config,
+    ) -> String {
+        let spans: Vec<(usize, (NaiveDate, NaiveDate, NaiveDate))> = self
+            .filtered_ids(filter)
+            .into_iter()
+            .filter_map(|id| Some((id, span(&self.tasks[id], today)?)))
+            .filter(|(_, (start, _, end))| *start <= to && *end >= from)
+            .collect();
+        if spans.is_empty() {
+            return tr!(
+                "timeline_empty",
+                "No tasks due between {0} and {1}\n",
+                config.format_date(from),
+                config.format_date(to)
+            );
+        }
+        if config.plain {
+            return spans
+                .iter()
+                .map(|(id, (start, due, end))| {
+                    let mut line = tr!(
+                        "timeline_plain_row",
+                        "ID: {0}. Title: {1}. From: {2}. Due: {3}.",
+                        id,
+                        self.tasks[*id].title,
+                        config.format_date(*start),
+                        config.format_date(*due)
+                    );
+                    if end > due {
+                        line += &tr!(
+                            "timeline_overdue_days",
+                            " Overdue by {0} days.",
+                            (*end - *due).num_days()
+                        );
+                    }
+                    line + "\n"
+                })
+                .collect();
+        }
+
+        let term_width = width
+            .or_else(|| dimensions().map(|(w, _)| w))
+            .unwrap_or(DEFAULT_TERMINAL_WIDTH);
+        let available = term_width.saturating_sub(LABEL_WIDTH + 1).max(1);
+        let days = (to - from).num_days() as usize + 1;
+        let days_per_column = days.div_ceil(available);
+        let columns: Vec<(NaiveDate, NaiveDate)> = (0..days)
+            .step_by(days_per_column)
+            .map(|offset| {
+                let first = from + Duration::days(offset as i64);
+                let last = (first + Duration::days(days_per_column as i64 - 1)).min(to);
+                (first, last)
+            })
+            .collect();
+
+        let theme = config.theme();
+        // Weekday initials, or the day of the month every week when columns are several days
+        let scale: String = columns
+            .iter()
+            .enumerate()
+            .map(|(index, (first, _))| {
+                if days_per_column == 1 {
+                    first.weekday().to_string()[..1].to_string()
+                } else if index % 7 == 0 {
+                    format!("{:<7}", first.day())
+                } else {
+                    String::new()
+                }
+            })
+            .collect();
+        let heading = format!("{} to {}", config.format_date(from), config.format_date(to));
+        let mut lines = vec![
+            theme.paint(Element::Header, &heading),
+            format!("{} {}", " ".repeat(LABEL_WIDTH), scale.trim_end()),
+        ];
+
+        let mut load = vec![0; columns.len()];
+        for (id, (start, due, end)) in spans.iter() {
+            let label = format!("{:>2} {}", id, self.tasks[*id].title);
+            let mut bar = String::new();
+            for (index, (first, last)) in columns.iter().enumerate() {
+                if first > end || last < start {
+                    bar.push(' ');
+                } else if first > due {
+                    load[index] += 1;
+                    bar += &theme.paint(Element::Overdue, &OVERDUE.to_string());
+                } else {
+                    load[index] += 1;
+                    bar.push(BAR);
+                }
+            }
+            lines.push(format!(
+                "{} {}",
+                pad(truncate(&label, LABEL_WIDTH), LABEL_WIDTH),
+                bar.trim_end()
+            ));
+        }
+        let overlaps: String = load
+            .iter()
+            .map(|&count| match count {
+                0 | 1 => " ".to_string(),
+                2..=9 => theme.paint(Element::Overdue, &count.to_string()),
+                _ => theme.paint(Element::Overdue, "+"),
+            })
+            .collect();
+        lines.push(format!(
+            "{} {}",
+            pad("overlapping", LABEL_WIDTH),
+            overlaps.trim_end()
+        ));
+        lines.join("\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::dates::from_local;
+    use crate::filter::Filter;
+    use crate::TaskManager;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn timeline_draws_bars_overlaps_and_overdue_spans() {
+        let day = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let at = |day: NaiveDate| from_local(day.and_hms_opt(9, 0, 0).unwrap());
+        let mut task_manager = TaskManager::new();
+        task_manager.add_task("Slides".to_string());
+        task_manager.add_task("Budget".to_string());
+        task_manager.add_task("Someday".to_string());
+        task_manager.tasks[0].start_time = Some(at(day(3)));
+        task_manager.tasks[0].due_time = Some(at(day(5)));
+        task_manager.tasks[1].start_time = Some(at(day(1)));
+        task_manager.tasks[1].scheduled = Some(at(day(4)));
+        task_manager.tasks[1].due_time = Some(at(day(2)));
+
+        let config = Config::default();
+        let filter = Filter::parse("", &config).unwrap();
+        let timeline = task_manager.timeline(&filter, day(1), day(7), Some(80), day(6), &config);
+        let lines: Vec<&str> = timeline.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].ends_with("SSMTWTF"));
+        assert!(lines[2].starts_with(" 0 Slides"));
+        assert!(lines[2].contains("  ███"));
+        assert_eq!(lines[2].matches('!').count(), 1);
+        // Scheduled after it was due, so only the due day and the days it has been overdue since
+        assert!(lines[3].contains(" █"));
+        assert_eq!(lines[3].matches('!').count(), 4);
+        assert!(lines[4].contains('2'));
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use crate::error::TaskError;
+use crate::i18n::{self, tr};
+use crate::{Task, TaskManager};
+
+// The changes one command makes to a task, a step at a time. Once a step fails the rest aren't
+// tried and the task is put back as it was, or removed again when the command added it, so a bad
+// value never leaves a task half changed
+pub struct Transaction {
+    pub id: usize,
+    // The task as it was, none when the transaction added it
+    before: Option<Task>,
+    next_uid: u64,
+    // Events applied before it began, the ones after are dropped with the changes on a rollback
+    events: usize,
+    applied: Vec<String>,
+    failed: Option<TaskError>,
+    skipped: Vec<String>,
+}
+
+// What was undone after a step failed
+#[derive(Debug, PartialEq)]
+pub struct RolledBack {
+    pub error: TaskError,
+    pub added: bool,
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl fmt::Display for RolledBack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut undone = self.applied.clone();
+        if self.added {
+            undone.insert(0, i18n::text("rolled_back_new_task", "the new task"));
+        }
+        write!(f, "{}", self.error)?;
+        if undone.is_empty() {
+            write!(
+                f,
+                "\n{}",
+                i18n::text("rolled_back_nothing", "Nothing was changed")
+            )?;
+        } else {
+            write!(
+                f,
+                "\n{}",
+                tr!("rolled_back", "Rolled back {0}", undone.join(", "))
+            )?;
+        }
+        if !self.skipped.is_empty() {
+            write!(
+                f,
+                "\n{}",
+                tr!(
+                    "rolled_back_not_tried",
+                    "Not tried {0}",
+                    self.skipped.join(", ")
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Transaction {
+    // Adds the task the steps then change
+    pub fn add(task_manager: &mut TaskManager, title: String) -> Self {
+        let next_uid = task_manager.next_uid;
+        let events = task_manager.pending_events.len();
+        task_manager.add_task(title);
+        Transaction {
+            id: task_manager.tasks.len() - 1,
+            before: None,
+            next_uid,
+            events,
+            applied: Vec::new(),
+            failed: None,
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn edit(task_manager: &TaskManager, id: usize) -> Result<Self, TaskError> {
+        if !task_manager.verify_id(id) {
+            return Err(TaskError::InvalidId(id));
+        }
+        Ok(Transaction {
+            id,
+            before: Some(task_manager.tasks[id].clone()),
+            next_uid: task_manager.next_uid,
+            events: task_manager.pending_events.len(),
+            applied: Vec::new(),
+            failed: None,
+            skipped: Vec::new(),
+        })
+    }
+
+    // Makes one change, named by what it sets, unless an earlier one failed
+    pub fn step(
+        &mut self,
+        task_manager: &mut TaskManager,
+        what: &str,
+        change: impl FnOnce(&mut TaskManager, usize) -> Result<(), TaskError>,
+    ) {
+        if self.failed.is_some() {
+            self.skipped.push(what.to_string());
+            return;
+        }
+        match change(task_manager, self.id) {
+            Ok(()) => self.applied.push(what.to_string()),
+            Err(err) => self.failed = Some(err),
+        }
+    }
+
+    // Keeps the changes when every step was made, otherwise undoes them
+    pub fn commit(self, task_manager: &mut TaskManager) -> Result<(), Box<RolledBack>> {
+        let error = match self.failed {
+            Some(error) => error,
+            None => return Ok(()),
+        };
+        let added = self.before.is_none();
+        match self.before {
+            Some(task) => task_manager.tasks[self.id] = task,
+            None => {
+                task_manager.tasks.remove(self.id);
+                task_manager.next_uid = self.next_uid;
+            }
+        }
+        task_manager.pending_events.truncate(self.events);
+        Err(Box::new(RolledBack {
+            error,
+            added,
+            applied: self.applied,
+            skipped: self.skipped,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transaction;
+    use crate::error::TaskError;
+    use crate::TaskManager;
+
+    #[test]
+    fn failed_step_undoes_the_others() {
+        let mut task_manager = TaskManager::new();
+        let mut transaction = Transaction::add(&mut task_manager, "Renew passport".to_string());
+        transaction.step(&mut task_manager, "description", |task_manager, id| {
+            task_manager.set_task_description(id, "photos first".to_string());
+            Ok(())
+        });
+        transaction.step(&mut task_manager, "due", |task_manager, id| {
+            task_manager.set_partial_due_date(id, "31/02/2026", "%d/%m/%Y")
+        });
+        transaction.step(&mut task_manager, "estimate", |task_manager, id| {
+            task_manager.set_estimate(id, "1h")
+        });
+        let rolled_back = transaction.commit(&mut task_manager).unwrap_err();
+        assert!(task_manager.tasks.is_empty());
+        assert_eq!(task_manager.next_uid, 1);
+        assert_eq!(
+            rolled_back.to_string(),
+            "Invalid date, submitted: 31/02/2026, expected format %d/%m/%Y\n\
+             Rolled back the new task, description\n\
+             Not tried estimate"
+        );
+
+        task_manager.add_task("Book dentist".to_string());
+        let mut transaction = Transaction::edit(&task_manager, 0).unwrap();
+        transaction.step(&mut task_manager, "urgency", |task_manager, id| {
+            task_manager.set_urgency(id, 8.0);
+            Ok(())
+        });
+        transaction.step(&mut task_manager, "recurrence", |task_manager, id| {
+            task_manager.set_recurrence(id, Some("every blue moon"))
+        });
+        assert!(transaction
+            .commit(&mut task_manager)
+            .unwrap_err()
+            .to_string()
+            .ends_with("\nRolled back urgency"));
+        assert_eq!(task_manager.tasks[0].urgency, 3.0);
+        assert!(task_manager.tasks[0].history.is_empty());
+
+        let mut transaction = Transaction::edit(&task_manager, 0).unwrap();
+        transaction.step(&mut task_manager, "estimate", |task_manager, id| {
+            task_manager.set_estimate(id, "soon")
+        });
+        assert!(transaction
+            .commit(&mut task_manager)
+            .unwrap_err()
+            .to_string()
+            .ends_with("\nNothing was changed"));
+        assert!(matches!(
+            Transaction::edit(&task_manager, 4),
+            Err(TaskError::InvalidId(4))
+        ));
+    }
+}
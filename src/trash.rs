@@ -0,0 +1,65 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::TaskError;
+use crate::events::Event;
+use crate::i18n::{self, tr};
+use crate::{Task, TaskManager};
+
+const TRASH_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrashedTask {
+    pub task: Task,
+    #[serde(deserialize_with = "crate::dates::serde_utc::deserialize")]
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl TaskManager {
+    // Removed tasks are kept in the trash until purged
+    pub fn trash_task(&mut self, id: usize) {
+        if self.verify_id(id) {
+            let task = self.tasks[id].clone();
+            self.apply(Event::TaskRemoved { uid: task.uid });
+            self.trash.push(TrashedTask {
+                task,
+                deleted_at: Utc::now(),
+            });
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    pub fn restore_task(&mut self, trash_id: usize) {
+        if trash_id < self.trash.len() {
+            let trashed = self.trash.remove(trash_id);
+            self.apply(Event::TaskAdded { task: trashed.task });
+        } else {
+            eprintln!("{}", TaskError::InvalidId(trash_id));
+        }
+    }
+
+    pub fn purge_trash(&mut self) {
+        let cutoff = Utc::now() - Duration::days(TRASH_RETENTION_DAYS);
+        self.trash.retain(|trashed| trashed.deleted_at > cutoff);
+    }
+
+    pub fn list_trash(&self, config: &Config) {
+        if self.trash.is_empty() {
+            println!("{}", i18n::text("trash_empty", "The trash is empty"));
+        }
+        for (index, trashed) in self.trash.iter().enumerate() {
+            println!(
+                "{}",
+                tr!(
+                    "trash_row",
+                    " -{0}- {1} --- deleted {2}",
+                    index,
+                    trashed.task.title,
+                    config.format_datetime(trashed.deleted_at)
+                )
+            );
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use chrono::Utc;
+use term_size::dimensions;
+
+use crate::checklist::checklist_count;
+use crate::config::Config;
+use crate::dates::{format_due, format_duration, format_relative};
+use crate::error::TaskError;
+use crate::i18n::tr;
+use crate::progress::percent_bar;
+use crate::reminders::format_lead;
+use crate::text::{display_width, pad, truncate, wrap};
+use crate::{Task, TaskManager, DEFAULT_TERMINAL_WIDTH};
+
+const LABEL_WIDTH: usize = 9;
+
+// Cuts a line to width columns, marking the cut with an ellipsis
+fn fit(line: &str, width: usize) -> String {
+    if display_width(line) <= width {
+        return line.to_string();
+    }
+    format!("{}…", truncate(line, width.saturating_sub(1)))
+}
+
+// Widest a card line can be, leaving room for the borders
+fn max_card_width() -> usize {
+    dimensions()
+        .map(|(w, _)| w)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+        .saturating_sub(4)
+}
+
+// A bordered card, each section is a heading followed by its lines
+fn card(title: &str, sections: &[(&str, Vec<String>)]) -> String {
+    let max_width = max_card_width();
+    let lines: Vec<String> = sections
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .flat_map(|(heading, lines)| {
+            std::iter::once(heading.to_string())
+                .chain(lines.iter().map(|line| format!("  {}", line)))
+        })
+        .collect();
+    let width = lines
+        .iter()
+        .map(|line| display_width(line))
+        .chain(std::iter::once(display_width(title) + 2))
+        .max()
+        .unwrap_or(0)
+        .min(max_width);
+    let title = fit(title, width.saturating_sub(2));
+    let mut output = format!(
+        "┌─ {} {}┐\n",
+        title,
+        "─".repeat(width.saturating_sub(display_width(&title) + 1))
+    );
+    for line in lines {
+        output += &format!("│ {} │\n", pad(&fit(&line, width), width));
+    }
+    output += &format!("└{}┘\n", "─".repeat(width + 2));
+    output
+}
+
+fn row(label: &str, value: String) -> (String, String) {
+    (label.to_string(), value)
+}
+
+fn aligned(rows: &[(String, String)]) -> Vec<String> {
+    rows.iter()
+        .map(|(label, value)| format!("{:<LABEL_WIDTH$} {}", label, value))
+        .collect()
+}
+
+// e.g. "Soft due: may be moved automatically"
+fn labeled(label: &str, value: &str) -> String {
+    let mut chars = label.chars();
+    let label: String = chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+    format!("{}: {}", label, value)
+}
+
+fn urgency_value(task: &Task) -> String {
+    if task.urgency_pinned {
+        tr!(
+            "urgency_pinned",
+            "{0} (pinned)",
+            format!("{:.3}", task.effective_urgency)
+        )
+    } else if task.effective_urgency != task.urgency {
+        tr!(
+            "urgency_base",
+            "{0} (base {1})",
+            format!("{:.3}", task.effective_urgency),
+            format!("{:.3}", task.urgency)
+        )
+    } else {
+        format!("{:.3}", task.effective_urgency)
+    }
+}
+
+impl TaskManager {
+    pub fn show_task(&self, id: usize, config: &Config) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let task = &self.tasks[id];
+        let now = Utc::now();
+        // Relative hints are appended to absolute dates unless turned off in config
+        let relative = |text: String| {
+            if config.relative_times {
+                format!(" ({})", text)
+            } else {
+                String::new()
+            }
+        };
+
+        let mut details = vec![
+            row("urgency", urgency_value(task)),
+            row("status", task.status.as_str().to_string()),
+            row("uid", format!("#{}", task.uid)),
+        ];
+        if let Some(project) = &task.project {
+            details.push(row("project", project.clone()));
+        }
+        let virtual_tags = task.virtual_tags(now, config.stale_after_days);
+        let tags: Vec<&str> = task
+            .tags
+            .iter()
+            .map(String::as_str)
+            .chain(virtual_tags.iter().map(|tag| tag.name()))
+            .collect();
+        if !tags.is_empty() {
+            details.push(row("tags", tags.join(" ")));
+        }
+        if let Some(assignee) = &task.assignee {
+            details.push(row("assignee", assignee.clone()));
+        }
+        if let Some(progress) = task.effective_progress {
+            let progress = if config.plain {
+                format!("{}%", progress)
+            } else {
+                percent_bar(progress)
+            };
+            details.push(row("progress", progress));
+        }
+        if let Some(estimate) = task.estimate {
+            details.push(row("estimate", format_duration(estimate)));
+        }
+        if task.soft_due {
+            details.push(row("soft due", "may be moved automatically".to_string()));
+        }
+        if let Some(watched) = &task.watch_file {
+            let last_changed = match watched.last_modified {
+                Some(last_modified) => config.format_datetime(last_modified),
+                None => "file not found".to_string(),
+            };
+            details.push(row(
+                "watching",
+                tr!(
+                    "detail_last_changed",
+                    "{0} (last changed {1})",
+                    watched.path.display(),
+                    last_changed
+                ),
+            ));
+        }
+        for link in task.links.iter() {
+            details.push(row("link", link.clone()));
+        }
+        if let Some(scheduled) = task.scheduled {
+            details.push(row(
+                "scheduled",
+                format!(
+                    "{}{}",
+                    config.format_datetime(scheduled),
+                    relative(format_relative(scheduled, now))
+                ),
+            ));
+        }
+        if let Some(until) = task.until {
+            details.push(row(
+                "until",
+                format!(
+                    "{}{}",
+                    config.format_datetime(until),
+                    relative(format_relative(until, now))
+                ),
+            ));
+        }
+        if let Some(recur) = &task.recur {
+            details.push(row("recurs", recur.clone()));
+        }
+        for (dependency_id, dependency) in self.dependencies(task) {
+            details.push(row(
+                "waits on",
+                format!(
+                    "-{}- {} ({})",
+                    dependency_id,
+                    dependency.title,
+                    dependency.status.as_str()
+                ),
+            ));
+        }
+        for attachment in task.attachments.iter() {
+            details.push(row("attached", attachment.display().to_string()));
+        }
+        for (name, value) in task.custom.iter() {
+            details.push(row(name, value.clone()));
+        }
+        if let Some(external_id) = &task.external_id {
+            details.push(row("imported", external_id.clone()));
+        }
+
+        let mut dates = Vec::new();
+        if let Some(start_time) = task.start_time {
+            dates.push(row(
+                "start",
+                format!(
+                    "{}{}",
+                    config.format_datetime(start_time),
+                    relative(format_relative(start_time, now))
+                ),
+            ));
+        }
+        let due = match task.due_time {
+            Some(due_time) => format!(
+                "{}{}",
+                config.format_datetime(due_time),
+                relative(format_due(due_time, now))
+            ),
+            None => "No Due Date".to_string(),
+        };
+        dates.push(row("due", due));
+        for lead in task.reminders.iter() {
+            let remind = match task.due_time {
+                Some(due_time) => tr!(
+                    "reminder_before",
+                    "{0} before, {1}",
+                    format_lead(*lead),
+                    config.format_datetime(due_time - *lead)
+                ),
+                None => tr!(
+                    "reminder_before_due",
+                    "{0} before the due date",
+                    format_lead(*lead)
+                ),
+            };
+            dates.push(row("remind", remind));
+        }
+        if let Some(planned_on) = task.planned_on {
+            dates.push(row("planned", config.format_date(planned_on)));
+        }
+        if let Some(created_at) = task.created_at {
+            dates.push(row(
+                "created",
+                format!(
+                    "{}{}",
+                    config.format_datetime(created_at),
+                    relative(format_relative(created_at, now))
+                ),
+            ));
+        }
+        if let Some(modified_at) = task.modified_at {
+            dates.push(row("modified", config.format_datetime(modified_at)));
+        }
+
+        let mut notes = Vec::new();
+        if let Some((lines, more)) = task.notes_preview() {
+            notes = lines;
+            if more > 0 {
+                notes.push(tr!(
+                    "notes_more_lines",
+                    "... {0} more lines, see tasks notes {1}",
+                    more,
+                    id
+                ));
+            }
+        }
+
+        let checklist: Vec<String> = task
+            .checklist
+            .iter()
+            .enumerate()
+            .map(|(index, item)| format!("{} {}", index, item.line()))
+            .collect();
+
+        let history = task.history.last().map(|last| {
+            tr!(
+                "changes_last",
+                "{0} changes, last {1}: {2} -> {3}",
+                task.history.len(),
+                last.field,
+                last.old_value,
+                last.new_value
+            )
+        });
+
+        // One labeled line per field, the description unwrapped on one line
+        if config.plain {
+            println!("{}", labeled("ID", &id.to_string()));
+            println!("{}", labeled("title", &task.title));
+            for (label, value) in details.iter().chain(dates.iter()) {
+                println!("{}", labeled(label, value));
+            }
+            if !task.description.is_empty() {
+                let description = task.description.split_whitespace().collect::<Vec<_>>();
+                println!("{}", labeled("description", &description.join(" ")));
+            }
+            if !notes.is_empty() {
+                println!("{}", labeled("notes", &notes.join(" ")));
+            }
+            if !task.checklist.is_empty() {
+                println!(
+                    "{}",
+                    labeled("checklist", &checklist_count(&task.checklist))
+                );
+            }
+            for (index, item) in task.checklist.iter().enumerate() {
+                let state = if item.done { "done" } else { "not done" };
+                println!(
+                    "{}",
+                    labeled(
+                        &tr!("checklist_item", "item {0}", index),
+                        &format!("{}, {}", item.text, state)
+                    )
+                );
+            }
+            if let Some(history) = history {
+                println!("{}", labeled("history", &history));
+            }
+            return;
+        }
+
+        // Section lines are indented by two
+        let description = wrap(&task.description, max_card_width().saturating_sub(2));
+        let checklist_heading = tr!(
+            "checklist_summary",
+            "Checklist, {0}",
+            checklist_count(&task.checklist)
+        );
+        print!(
+            "{}",
+            card(
+                &format!("-{}- {}", id, task.title),
+                &[
+                    ("Details", aligned(&details)),
+                    ("Dates", aligned(&dates)),
+                    ("Description", description),
+                    ("Notes", notes),
+                    (&checklist_heading, checklist),
+                    ("History", history.into_iter().collect()),
+                ],
+            )
+        );
+    }
+
+    // One line per task, e.g. for checking a task from a script or a prompt
+    pub fn show_task_short(&self, id: usize) {
+        if !self.verify_id(id) {
+            eprintln!("{}", TaskError::InvalidId(id));
+            return;
+        }
+        let task = &self.tasks[id];
+        let due = match task.due_time {
+            Some(due_time) => format!(", {}", format_due(due_time, Utc::now())),
+            None => String::new(),
+        };
+        println!(
+            "{}",
+            tr!(
+                "view_row",
+                " -{0}- {1} --- urgency: {2}, {3}{4}",
+                id,
+                task.title,
+                urgency_value(task),
+                task.status.as_str(),
+                due
+            )
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::dates::to_local;
+use crate::{Status, Task};
+
+// Tags worked out from a task whenever they are asked for, never stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VirtualTag {
+    Overdue,
+    DueToday,
+    Active,
+    Done,
+    Planned,
+    Pinned,
+    Stale,
+    Waiting,
+    Blocked,
+}
+
+pub const ALL_VIRTUAL_TAGS: [VirtualTag; 9] = [
+    VirtualTag::Overdue,
+    VirtualTag::DueToday,
+    VirtualTag::Active,
+    VirtualTag::Done,
+    VirtualTag::Planned,
+    VirtualTag::Pinned,
+    VirtualTag::Stale,
+    VirtualTag::Waiting,
+    VirtualTag::Blocked,
+];
+
+// --tag values as filter terms, a bare tag name means +TAG
+pub fn tag_term(tag: &str) -> String {
+    if tag.starts_with(['+', '-']) {
+        tag.to_string()
+    } else {
+        format!("+{}", tag)
+    }
+}
+
+impl VirtualTag {
+    pub fn name(&self) -> &'static str {
+        match self {
+            VirtualTag::Overdue => "OVERDUE",
+            VirtualTag::DueToday => "DUETODAY",
+            VirtualTag::Active => "ACTIVE",
+            VirtualTag::Done => "DONE",
+            VirtualTag::Planned => "PLANNED",
+            VirtualTag::Pinned => "PINNED",
+            VirtualTag::Stale => "STALE",
+            VirtualTag::Waiting => "WAITING",
+            VirtualTag::Blocked => "BLOCKED",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        ALL_VIRTUAL_TAGS
+            .iter()
+            .find(|tag| tag.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    // Open tasks unchanged for more than stale_after_days are STALE, see the config of that name.
+    // BLOCKED follows the tasks depended on, see derive_from_dependencies
+    pub fn applies(&self, task: &Task, now: DateTime<Utc>, stale_after_days: i64) -> bool {
+        let open = task.status != Status::Done;
+        match self {
+            VirtualTag::Overdue => open && task.due_time.is_some_and(|due_time| due_time < now),
+            VirtualTag::DueToday => {
+                open && task
+                    .due_time
+                    .is_some_and(|due_time| to_local(due_time).date() == Local::now().date_naive())
+            }
+            VirtualTag::Active => task.status == Status::Active,
+            VirtualTag::Done => !open,
+            VirtualTag::Planned => open && task.planned_on.is_some(),
+            VirtualTag::Pinned => task.urgency_pinned,
+            VirtualTag::Waiting => open && task.is_waiting(now),
+            VirtualTag::Stale => task.is_stale(now, stale_after_days),
+            VirtualTag::Blocked => open && task.blocked,
+        }
+    }
+
+    // Status tags repeat the status column, so list only shows the others as badges
+    fn is_badge(&self) -> bool {
+        !matches!(self, VirtualTag::Active | VirtualTag::Done)
+    }
+}
+
+impl Task {
+    // Time since anything about the task last changed, or since it was added
+    pub fn untouched_for(&self, now: DateTime<Utc>) -> Duration {
+        match self.modified_at.or(self.created_at) {
+            Some(modified_at) => now - modified_at,
+            None => Duration::zero(),
+        }
+    }
+
+    pub fn is_stale(&self, now: DateTime<Utc>, days: i64) -> bool {
+        self.status != Status::Done && self.untouched_for(now) > Duration::days(days)
+    }
+
+    pub fn virtual_tags(&self, now: DateTime<Utc>, stale_after_days: i64) -> Vec<VirtualTag> {
+        ALL_VIRTUAL_TAGS
+            .iter()
+            .filter(|tag| tag.applies(self, now, stale_after_days))
+            .copied()
+            .collect()
+    }
+
+    // e.g. " [OVERDUE STALE]", empty when there are none
+    pub fn badges(&self, now: DateTime<Utc>, stale_after_days: i64) -> String {
+        let names: Vec<&str> = self
+            .virtual_tags(now, stale_after_days)
+            .iter()
+            .filter(|tag| tag.is_badge())
+            .map(VirtualTag::name)
+            .collect();
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", names.join(" "))
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::metadata;
+use std::path::{Path, PathBuf};
+
+use crate::error::TaskError;
+use crate::events::FieldValue;
+use crate::history::history_datetime;
+use crate::TaskManager;
+
+// A file linked to a task, e.g. a document to review whenever it is updated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchedFile {
+    pub path: PathBuf,
+    // Modification time of the file when it was last checked
+    #[serde(
+        default,
+        deserialize_with = "crate::dates::serde_utc::deserialize_option"
+    )]
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+fn file_modified(path: &Path) -> Option<DateTime<Utc>> {
+    let modified = metadata(path).ok()?.modified().ok()?;
+    Some(DateTime::<Utc>::from(modified))
+}
+
+fn watch_value(watched: &Option<WatchedFile>) -> String {
+    match watched {
+        Some(watched) => watched.path.display().to_string(),
+        None => "none".to_string(),
+    }
+}
+
+impl TaskManager {
+    pub fn set_watch_file(&mut self, id: usize, path: Option<PathBuf>) {
+        if self.verify_id(id) {
+            let watched = path.map(|path| WatchedFile {
+                last_modified: file_modified(&path),
+                path,
+            });
+            let task = &mut self.tasks[id];
+            task.record_change(
+                "watch file",
+                watch_value(&task.watch_file),
+                watch_value(&watched),
+            );
+            self.set_field(id, FieldValue::WatchFile(watched));
+        } else {
+            eprintln!("{}", TaskError::InvalidId(id));
+        }
+    }
+
+    // Checked on every run, a newer modification time is logged as activity on the task
+    pub fn check_watched_files(&mut self) {
+        for id in 0..self.tasks.len() {
+            let task = &mut self.tasks[id];
+            let (path, modified, last_modified) = match &task.watch_file {
+                Some(watched) => (
+                    watched.path.clone(),
+                    file_modified(&watched.path),
+                    watched.last_modified,
+                ),
+                None => continue,
+            };
+            if modified.is_some() && modified > last_modified {
+                task.record_change(
+                    "watched file changed",
+                    history_datetime(last_modified),
+                    history_datetime(modified),
+                );
+                let watched = WatchedFile {
+                    path,
+                    last_modified: modified,
+                };
+                self.set_field(id, FieldValue::WatchFile(Some(watched)));
+            }
+        }
+    }
+}